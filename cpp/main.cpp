@@ -7,6 +7,7 @@
 #include <vector>
 #include <algorithm>
 #include <fstream> // For high score file
+#include <cstdint>
 
 // Rust functions (extern "C")
 extern "C" {
@@ -14,14 +15,57 @@ extern "C" {
         int id;
         float x;
         float y;
-        bool alive;
+        uint8_t alive;
+        float dodge_cooldown;
+        int stun_frames;
+        float last_seen_x;
+        float last_seen_y;
+        float vx;
+        float vy;
+        float health;
+        float max_health;
+        int tether_id;
+        float summon_cooldown;
+        int team;
+        int flinch_frames;
+        int wave_id;
+        float regen_rate;
+        int frames_since_hit;
+        float taunt_x;
+        float taunt_y;
+        int taunt_frames;
+        int type_id;
     };
 
-    int find_nearest_enemy(float player_x, float player_y, Enemy* enemies, int count);
-    void shoot_enemy(int index, Enemy* enemies);
-    void move_enemies_randomly(Enemy* enemies, int count, float speed);
+    enum DeadAimStatus : int {
+        DEADAIM_OK = 0,
+        DEADAIM_NULL_POINTER,
+        DEADAIM_INVALID_INDEX,
+        DEADAIM_INVALID_ARGUMENT,
+        DEADAIM_UTF8_ERROR,
+        DEADAIM_NOT_INITIALIZED,
+        DEADAIM_PANICKED,
+        DEADAIM_RATE_LIMITED,
+        DEADAIM_REJECTED,
+        DEADAIM_BUFFER_TOO_SMALL,
+        DEADAIM_INVALID_DATA,
+        DEADAIM_UNKNOWN_ID,
+    };
+    const char* deadaim_status_message(DeadAimStatus status);
+    const char* last_error_message();
+
+    DeadAimStatus abi_version(uint32_t* out_version);
+    DeadAimStatus sizeof_enemy(size_t* out_size);
+
+    DeadAimStatus find_nearest_enemy(float player_x, float player_y, Enemy* enemies, int count, int* out_index);
+    DeadAimStatus shoot_enemy(int index, Enemy* enemies, int count);
+    DeadAimStatus move_enemies_randomly(Enemy* enemies, int count, float speed);
+    DeadAimStatus enemy_init(Enemy* enemy, int id, float x, float y);
 }
 
+// ABI this host was written against (see abi_version in the Rust library)
+const uint32_t DEADAIM_ABI_VERSION = 2;
+
 // ANSI colors
 #define RESET   "\033[0m"
 #define RED     "\033[31m"
@@ -114,10 +158,7 @@ void play_game() {
         enemies.clear();
         for(int i=0;i<count;i++){
             Enemy e;
-            e.id = i;
-            e.x = rand() % GRID_SIZE;
-            e.y = rand() % GRID_SIZE;
-            e.alive = true;
+            enemy_init(&e, i, rand() % GRID_SIZE, rand() % GRID_SIZE);
             enemies.push_back(e);
         }
     };
@@ -149,10 +190,12 @@ void play_game() {
         if(input=='d' && player_x<GRID_SIZE-1) player_x += PLAYER_SPEED;
 
         // Move enemies (Rust)
-        move_enemies_randomly(enemies.data(), enemies.size(), 0.5f + 0.2f*level);
+        DeadAimStatus moved = move_enemies_randomly(enemies.data(), enemies.size(), 0.5f + 0.2f*level);
+        if(moved != DEADAIM_OK) std::cerr << "move_enemies_randomly: " << deadaim_status_message(moved) << "\n";
 
         // Nearest enemy
-        int nearest = find_nearest_enemy(player_x, player_y, enemies.data(), enemies.size());
+        int nearest = -1;
+        find_nearest_enemy(player_x, player_y, enemies.data(), enemies.size(), &nearest);
 
         if(nearest!=-1){
             float dx = player_x - enemies[nearest].x;
@@ -160,7 +203,7 @@ void play_game() {
             float dist = std::sqrt(dx*dx + dy*dy);
 
             if(input=='s' && dist <= SHOOT_RANGE){
-                shoot_enemy(nearest, enemies.data());
+                shoot_enemy(nearest, enemies.data(), enemies.size());
                 std::cout << GREEN << "Shot enemy id: " << nearest << "!" << RESET << "\n";
                 score += 10 * multiplier;
                 multiplier++;
@@ -201,6 +244,15 @@ void play_game() {
 
 // ================= Main =================
 int main() {
+    uint32_t lib_version = 0;
+    size_t lib_enemy_size = 0;
+    abi_version(&lib_version);
+    sizeof_enemy(&lib_enemy_size);
+    if(lib_version != DEADAIM_ABI_VERSION || lib_enemy_size != sizeof(Enemy)){
+        std::cerr << RED << "deadaim_core ABI mismatch: library v" << lib_version << " (Enemy " << lib_enemy_size
+                  << " bytes), host v" << DEADAIM_ABI_VERSION << " (Enemy " << sizeof(Enemy) << " bytes)" << RESET << "\n";
+        return 1;
+    }
     while(true){
         std::cout << "\033[2J\033[1;1H"; // Clear screen
         display_menu();
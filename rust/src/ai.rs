@@ -0,0 +1,72 @@
+// src/ai.rs
+// Per-enemy AI state machine: what an enemy is doing (idle, patrolling, chasing, attacking,
+// fleeing) and when it switches, so world movement and host animations agree.
+use crate::obstacles::line_of_sight;
+use crate::Enemy;
+
+// A chasing enemy that lost sight gives up this close to where it last saw the player.
+const LAST_SEEN_ARRIVAL: f32 = 0.5;
+
+/// What an enemy's AI is currently doing (reported to hosts as its discriminant).
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AiState {
+    /// Nothing in sight; moves by its steering behavior.
+    Idle,
+    /// Nothing in sight; walking its looping patrol route.
+    Patrol,
+    /// Heading for where it last saw the player.
+    Chase,
+    /// Player within attack range and in sight; holds position.
+    Attack,
+    /// Hurt below the flee threshold with the player nearby; running away.
+    Flee,
+}
+
+impl AiState {
+    /// The state with discriminant `value`, if any.
+    pub(crate) fn from_u8(value: u8) -> Option<Self> {
+        [AiState::Idle, AiState::Patrol, AiState::Chase, AiState::Attack, AiState::Flee].get(value as usize).copied()
+    }
+}
+
+/// Transition thresholds for `world_set_ai_config`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct AiConfig {
+    /// The player is spotted within this distance when in line of sight (see `has_line_of_sight`).
+    pub sight_range: f32,
+    /// Spotted players closer than this are attacked instead of chased.
+    pub attack_range: f32,
+    /// Health fraction (for enemies with a health model) below which the enemy flees while the
+    /// player is within `sight_range`; 0 disables fleeing.
+    pub flee_health_fraction: f32,
+}
+
+impl AiConfig {
+    /// Next state for `e` given the player position; `patrolling` says whether it has a looping
+    /// route to fall back to. Refreshes the enemy's last-seen player position when it sees them.
+    pub(crate) fn next_state(&self, current: AiState, e: &mut Enemy, px: f32, py: f32, patrolling: bool) -> AiState {
+        let resting = if patrolling { AiState::Patrol } else { AiState::Idle };
+        let dist2 = (px - e.x).powi(2) + (py - e.y).powi(2);
+        let in_range = dist2 <= self.sight_range * self.sight_range;
+
+        let hurt = e.max_health > 0.0 && e.health < e.max_health * self.flee_health_fraction;
+        if hurt && in_range {
+            return AiState::Flee;
+        }
+        if in_range && line_of_sight(e.x, e.y, px, py) {
+            e.last_seen_x = px;
+            e.last_seen_y = py;
+            return if dist2 <= self.attack_range * self.attack_range { AiState::Attack } else { AiState::Chase };
+        }
+        let chasing = matches!(current, AiState::Chase | AiState::Attack);
+        let arrived = (e.last_seen_x - e.x).powi(2) + (e.last_seen_y - e.y).powi(2)
+            <= LAST_SEEN_ARRIVAL * LAST_SEEN_ARRIVAL;
+        if chasing && !arrived {
+            AiState::Chase
+        } else {
+            resting
+        }
+    }
+}
@@ -0,0 +1,621 @@
+// src/aim_assist.rs
+// Aim assistance helpers: bend or nudge the player's aim toward targets.
+use crate::error::{arg_mut, ffi_guard, slice_arg, slice_arg_mut, write_opt, write_out, DeadAimStatus};
+use crate::rewards::shoot_enemy_and_reward;
+use crate::rng::with_rng;
+use crate::{k_nearest_matching, nearest_alive_index, nearest_matching, Enemy};
+use std::f32::consts::PI;
+
+/// Wrap an angle (radians) into [-PI, PI].
+pub(crate) fn wrap_angle(a: f32) -> f32 {
+    let mut a = (a + PI) % (2.0 * PI);
+    if a < 0.0 {
+        a += 2.0 * PI;
+    }
+    a - PI
+}
+
+/// Magnetism: bend the player's raw turn input toward the nearest enemy without snapping.
+///
+/// If the nearest alive enemy is within `magnet_range_rad` of `current_angle`, turning toward it
+/// is amplified by `(1 + magnet_strength)` (never carrying the aim past the target) and turning
+/// away is damped by `(1 - magnet_strength)`. `magnet_strength` is clamped to 0..=1.
+/// Writes the adjusted new aim angle in radians, wrapped to [-PI, PI], to `out_angle` and the
+/// magnetizing enemy index (or -1) to `out_target` (may be null).
+// Safe C entry point: `enemies_ptr`, `out_angle`, `out_target` are null-checked before use;
+// the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn aim_magnetism(
+    current_angle: f32,
+    raw_turn: f32,
+    player_x: f32,
+    player_y: f32,
+    enemies_ptr: *const Enemy,
+    count: i32,
+    magnet_strength: f32,
+    magnet_range_rad: f32,
+    out_angle: *mut f32,
+    out_target: *mut i32,
+) -> DeadAimStatus {
+    ffi_guard(|| {
+        let enemies = unsafe { slice_arg(enemies_ptr, count) }?;
+        let mut target: i32 = -1;
+        let mut turn = raw_turn;
+
+        let nearest = nearest_alive_index(player_x, player_y, enemies);
+        if nearest >= 0 && magnet_strength > 0.0 && magnet_range_rad > 0.0 {
+            let e = &enemies[nearest as usize];
+            let to_target = wrap_angle((e.y - player_y).atan2(e.x - player_x) - current_angle);
+            if to_target.abs() <= magnet_range_rad {
+                target = nearest;
+                let strength = magnet_strength.min(1.0);
+                if raw_turn * to_target > 0.0 {
+                    // turning toward: boost, but don't let the boost carry us past the target
+                    let boosted = raw_turn * (1.0 + strength);
+                    turn = if raw_turn.abs() <= to_target.abs() && boosted.abs() > to_target.abs() {
+                        to_target
+                    } else {
+                        boosted
+                    };
+                } else if raw_turn * to_target < 0.0 {
+                    // turning away: damp
+                    turn = raw_turn * (1.0 - strength);
+                }
+            }
+        }
+
+        unsafe {
+            write_out(out_angle, wrap_angle(current_angle + turn))?;
+            write_opt(out_target, target);
+        }
+        Ok(())
+    })
+}
+
+/// Outline of the weapon's spread cone for the reticle: the origin, `segments` points along the
+/// arc at `range` spanning `aim_angle ± spread_rad`, then the origin again to close the polyline.
+/// `out_x`/`out_y` must hold `segments + 2` points; the number written goes to `out_written`.
+/// `InvalidArgument` for a non-positive `segments`.
+// Safe C entry point: `out_x`, `out_y`, `out_written` are null-checked before use;
+// the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn spread_cone_points(
+    origin_x: f32,
+    origin_y: f32,
+    aim_angle: f32,
+    spread_rad: f32,
+    range: f32,
+    segments: i32,
+    out_x: *mut f32,
+    out_y: *mut f32,
+    out_written: *mut i32,
+) -> DeadAimStatus {
+    ffi_guard(|| {
+        if segments <= 0 {
+            return Err(DeadAimStatus::InvalidArgument);
+        }
+        let n = segments as usize + 2;
+        let xs = unsafe { slice_arg_mut(out_x, n) }?;
+        let ys = unsafe { slice_arg_mut(out_y, n) }?;
+        let spread = spread_rad.abs();
+
+        xs[0] = origin_x;
+        ys[0] = origin_y;
+        for i in 0..segments as usize {
+            let t = if segments == 1 { 0.5 } else { i as f32 / (segments - 1) as f32 };
+            let angle = aim_angle - spread + 2.0 * spread * t;
+            xs[i + 1] = origin_x + angle.cos() * range;
+            ys[i + 1] = origin_y + angle.sin() * range;
+        }
+        xs[n - 1] = origin_x;
+        ys[n - 1] = origin_y;
+        unsafe { write_out(out_written, n as i32) }
+    })
+}
+
+/// Aim punch (view kick) when the player is hit: offset `current_angle` by a seeded random amount
+/// in `±punch_magnitude`, so replays reproduce the same kick. Writes the new angle to `out_angle`.
+// Safe C entry point: `out_angle` is null-checked before use; the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn apply_aim_punch(current_angle: f32, punch_magnitude: f32, out_angle: *mut f32) -> DeadAimStatus {
+    ffi_guard(|| {
+        if out_angle.is_null() {
+            return Err(DeadAimStatus::NullPointer);
+        }
+        let m = punch_magnitude.abs();
+        let kick = with_rng(|rng| rng.range_f32(-m, m));
+        unsafe { write_out(out_angle, wrap_angle(current_angle + kick)) }
+    })
+}
+
+/// Decay an outstanding aim-punch offset toward zero: writes
+/// `current_offset * exp(-recovery_rate * dt)` to `out_offset` (the offset unchanged for a
+/// non-positive rate or `dt`).
+// Safe C entry point: `out_offset` is null-checked before use; the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn recover_aim_punch(
+    current_offset: f32,
+    recovery_rate: f32,
+    dt: f32,
+    out_offset: *mut f32,
+) -> DeadAimStatus {
+    ffi_guard(|| {
+        let offset = if recovery_rate <= 0.0 || dt <= 0.0 {
+            current_offset
+        } else {
+            current_offset * (-recovery_rate * dt).exp()
+        };
+        unsafe { write_out(out_offset, offset) }
+    })
+}
+
+/// Aim-lock state kept by the host between frames. `target_id` is an enemy id (-1 = none) and
+/// `progress` grows from 0 to 1 while the crosshair stays on that target.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct AimLock {
+    pub target_id: i32,
+    pub progress: f32,
+}
+
+/// Reset a lock to "no target".
+// Safe C entry point: `lock` is null-checked before use; the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn aim_lock_reset(lock: *mut AimLock) -> DeadAimStatus {
+    ffi_guard(|| {
+        let lock = unsafe { arg_mut(lock) }?;
+        lock.target_id = -1;
+        lock.progress = 0.0;
+        Ok(())
+    })
+}
+
+/// Advance the aim lock by `dt` seconds toward the nearest alive enemy.
+///
+/// While the nearest enemy is the locked one, `progress` builds up over `lock_time` seconds.
+/// When a different enemy becomes nearest, progress decays at `decay_rate` per second and the
+/// lock only switches once it reaches 0, so near-equidistant enemies don't steal it every frame.
+/// If the locked enemy is dead or gone, the lock is dropped and the new nearest acquired in the
+/// same call, skipping the decay.
+/// Writes the index of the locked enemy, or -1 when there is nothing to lock, to `out_index`.
+// Safe C entry point: `lock`, `enemies_ptr`, `out_index` are null-checked before use;
+// the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn aim_lock_update(
+    lock: *mut AimLock,
+    player_x: f32,
+    player_y: f32,
+    enemies_ptr: *const Enemy,
+    count: i32,
+    dt: f32,
+    lock_time: f32,
+    decay_rate: f32,
+    out_index: *mut i32,
+) -> DeadAimStatus {
+    ffi_guard(|| {
+        let lock = unsafe { arg_mut(lock) }?;
+        let enemies = unsafe { slice_arg(enemies_ptr, count) }?;
+        let index = step_aim_lock(lock, player_x, player_y, enemies, dt, lock_time, decay_rate);
+        unsafe { write_out(out_index, index) }
+    })
+}
+
+/// `aim_lock_update` on checked arguments; returns the locked index or -1.
+fn step_aim_lock(
+    lock: &mut AimLock,
+    player_x: f32,
+    player_y: f32,
+    enemies: &[Enemy],
+    dt: f32,
+    lock_time: f32,
+    decay_rate: f32,
+) -> i32 {
+    let nearest = nearest_alive_index(player_x, player_y, enemies);
+    let nearest_id = (nearest >= 0).then(|| enemies[nearest as usize].id);
+
+    let locked = enemies.iter().position(|e| e.is_alive() && e.id == lock.target_id);
+    let Some(locked) = locked.filter(|_| lock.target_id >= 0) else {
+        // no lock, or the locked target died / vanished: re-acquire immediately
+        lock.target_id = nearest_id.unwrap_or(-1);
+        lock.progress = 0.0;
+        return nearest;
+    };
+
+    let dt = dt.max(0.0);
+    if nearest_id == Some(lock.target_id) {
+        let rate = if lock_time > 0.0 { dt / lock_time } else { 1.0 };
+        lock.progress = (lock.progress + rate).min(1.0);
+        return locked as i32;
+    }
+    lock.progress = (lock.progress - decay_rate.max(0.0) * dt).max(0.0);
+    if lock.progress > 0.0 {
+        return locked as i32;
+    }
+    lock.target_id = nearest_id.unwrap_or(-1);
+    nearest
+}
+
+/// Skill-shot helper: try an aim direction toward each alive enemy and keep the one whose
+/// piercing beam (`beam_width` wide, extending forward from the origin) passes through the most
+/// alive enemies. Ties keep the direction toward the nearer candidate. Writes the unit direction to
+/// `out_dir_x`/`out_dir_y` and the hit count to `out_hits` (0, with no direction written, if no
+/// enemy is alive).
+// Safe C entry point: `enemies_ptr`, `out_dir_x`, `out_dir_y`, `out_hits` are null-checked before use;
+// the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn best_pierce_line(
+    origin_x: f32,
+    origin_y: f32,
+    enemies_ptr: *const Enemy,
+    count: i32,
+    beam_width: f32,
+    out_dir_x: *mut f32,
+    out_dir_y: *mut f32,
+    out_hits: *mut i32,
+) -> DeadAimStatus {
+    ffi_guard(|| {
+        let enemies = unsafe { slice_arg(enemies_ptr, count) }?;
+        if out_dir_x.is_null() || out_dir_y.is_null() {
+            return Err(DeadAimStatus::NullPointer);
+        }
+        let half_width = beam_width.max(0.0) * 0.5;
+
+        let mut best: Option<(i32, f32, f32, f32)> = None; // (hits, dist, dir_x, dir_y)
+        for c in enemies.iter().filter(|e| e.is_alive()) {
+            let (cx, cy) = (c.x - origin_x, c.y - origin_y);
+            let dist = (cx * cx + cy * cy).sqrt();
+            if dist <= f32::EPSILON {
+                continue;
+            }
+            let (dx, dy) = (cx / dist, cy / dist);
+            let hits = enemies
+                .iter()
+                .filter(|e| e.is_alive())
+                .filter(|e| {
+                    let (ex, ey) = (e.x - origin_x, e.y - origin_y);
+                    let along = ex * dx + ey * dy;
+                    let across = (ex * dy - ey * dx).abs();
+                    along >= 0.0 && across <= half_width
+                })
+                .count() as i32;
+            let better = match best {
+                None => true,
+                Some((best_hits, best_dist, _, _)) => hits > best_hits || (hits == best_hits && dist < best_dist),
+            };
+            if better {
+                best = Some((hits, dist, dx, dy));
+            }
+        }
+
+        let Some((hits, _, dx, dy)) = best else {
+            return unsafe { write_out(out_hits, 0) };
+        };
+        unsafe {
+            out_dir_x.write(dx);
+            out_dir_y.write(dy);
+            write_out(out_hits, hits)
+        }
+    })
+}
+
+/// Angle (radians) between the unit aim (`ax`, `ay`) and the direction from the player to `e`,
+/// plus the squared distance. An enemy on top of the player counts as dead ahead.
+fn aim_deviation(player_x: f32, player_y: f32, ax: f32, ay: f32, e: &Enemy) -> (f32, f32) {
+    let (dx, dy) = (e.x - player_x, e.y - player_y);
+    let dist2 = dx * dx + dy * dy;
+    if dist2 <= f32::EPSILON {
+        return (0.0, dist2);
+    }
+    let cos = (dx * ax + dy * ay) / dist2.sqrt();
+    (cos.clamp(-1.0, 1.0).acos(), dist2)
+}
+
+/// Flick-aim target: the alive enemy with the smallest angle between the aim direction
+/// (`aim_dir_x`, `aim_dir_y`, any length) and the direction to it, within `max_angle_rad`.
+/// Equal angles prefer the nearer enemy. Writes its index, or -1. `InvalidArgument` for a zero
+/// aim direction or a negative/NaN angle.
+// Safe C entry point: `enemies_ptr`, `out_index` are null-checked before use; the rest of the contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn nearest_enemy_to_aim(
+    player_x: f32,
+    player_y: f32,
+    aim_dir_x: f32,
+    aim_dir_y: f32,
+    enemies_ptr: *const Enemy,
+    count: i32,
+    max_angle_rad: f32,
+    out_index: *mut i32,
+) -> DeadAimStatus {
+    ffi_guard(|| {
+        let enemies = unsafe { slice_arg(enemies_ptr, count) }?;
+        let aim_len = (aim_dir_x * aim_dir_x + aim_dir_y * aim_dir_y).sqrt();
+        if aim_len <= f32::EPSILON || max_angle_rad.is_nan() || max_angle_rad < 0.0 {
+            return Err(DeadAimStatus::InvalidArgument);
+        }
+        let (ax, ay) = (aim_dir_x / aim_len, aim_dir_y / aim_len);
+
+        let mut best: Option<(usize, f32, f32)> = None; // (index, angle, dist2)
+        for (i, e) in enemies.iter().enumerate().filter(|(_, e)| e.is_alive()) {
+            let (angle, dist2) = aim_deviation(player_x, player_y, ax, ay, e);
+            if angle > max_angle_rad {
+                continue;
+            }
+            let better = match best {
+                None => true,
+                Some((_, best_angle, best_dist2)) => {
+                    angle < best_angle || (angle == best_angle && dist2 < best_dist2)
+                }
+            };
+            if better {
+                best = Some((i, angle, dist2));
+            }
+        }
+        unsafe { write_out(out_index, best.map_or(-1, |(i, _, _)| i as i32)) }
+    })
+}
+
+/// View-cone auto-aim: the nearest alive enemy within `max_range` whose direction is at most
+/// `half_angle_rad` off the aim direction (`aim_dx`, `aim_dy`, any length), so enemies behind
+/// the player are never picked. Writes its index, or -1. `InvalidArgument` for a zero aim
+/// direction, a NaN angle or a negative/NaN range.
+// Safe C entry point: `enemies_ptr`, `out_index` are null-checked before use; the rest of the contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn find_nearest_in_cone(
+    player_x: f32,
+    player_y: f32,
+    aim_dx: f32,
+    aim_dy: f32,
+    half_angle_rad: f32,
+    max_range: f32,
+    enemies_ptr: *const Enemy,
+    count: i32,
+    out_index: *mut i32,
+) -> DeadAimStatus {
+    ffi_guard(|| {
+        let enemies = unsafe { slice_arg(enemies_ptr, count) }?;
+        let aim_len = (aim_dx * aim_dx + aim_dy * aim_dy).sqrt();
+        if aim_len <= f32::EPSILON || half_angle_rad.is_nan() || max_range.is_nan() || max_range < 0.0 {
+            return Err(DeadAimStatus::InvalidArgument);
+        }
+        let (ax, ay) = (aim_dx / aim_len, aim_dy / aim_len);
+        let min_cos = half_angle_rad.clamp(0.0, PI).cos();
+        let index = nearest_matching(player_x, player_y, enemies, |_, e| {
+            let (dx, dy) = (e.x - player_x, e.y - player_y);
+            let dist = (dx * dx + dy * dy).sqrt();
+            // an enemy on top of the player is inside any cone
+            dist <= max_range && (dist <= f32::EPSILON || (dx * ax + dy * ay) / dist >= min_cos)
+        });
+        unsafe { write_out(out_index, index) }
+    })
+}
+
+// A held assist target is only dropped for one at most this fraction of its angular deviation.
+const ASSIST_SWITCH_RATIO: f32 = 0.75;
+
+/// Assist target remembered between frames for hysteresis; `target_id` -1 = none.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct AimAssistState {
+    pub target_id: i32,
+}
+
+/// Snap-style aim assist: rotate the aim vector (`aim_dx`, `aim_dy`) by `strength` (0..=1) of
+/// the way toward the best target, the alive enemy with the smallest angular deviation within
+/// `max_angle`. With a non-null `state`, the previous target is kept while it stays alive and in
+/// the cone unless another is clearly better aimed (under 3/4 of its deviation), so the assist
+/// doesn't flicker between two enemies. Writes the adjusted aim (same length as the input) to
+/// `out_dx`/`out_dy` and the target index, or -1 (aim passed through unchanged), to
+/// `out_target`.
+// Safe C entry point: `enemies_ptr`, `state`, `out_dx`, `out_dy`, `out_target` are null-checked before use;
+// the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn assist_aim(
+    player_x: f32,
+    player_y: f32,
+    aim_dx: f32,
+    aim_dy: f32,
+    strength: f32,
+    max_angle: f32,
+    enemies_ptr: *const Enemy,
+    count: i32,
+    state: *mut AimAssistState,
+    out_dx: *mut f32,
+    out_dy: *mut f32,
+    out_target: *mut i32,
+) -> DeadAimStatus {
+    ffi_guard(|| {
+        if out_dx.is_null() || out_dy.is_null() || out_target.is_null() {
+            return Err(DeadAimStatus::NullPointer);
+        }
+        let mut state = unsafe { state.as_mut() };
+        let enemies = unsafe { slice_arg(enemies_ptr, count) }?;
+        let aim_len = (aim_dx * aim_dx + aim_dy * aim_dy).sqrt();
+        let best = if aim_len > f32::EPSILON && max_angle >= 0.0 {
+            let (ax, ay) = (aim_dx / aim_len, aim_dy / aim_len);
+            let in_cone = |i: usize| {
+                let (angle, dist2) = aim_deviation(player_x, player_y, ax, ay, &enemies[i]);
+                (angle <= max_angle).then_some((i, angle, dist2))
+            };
+            let best = (0..enemies.len())
+                .filter(|&i| enemies[i].is_alive())
+                .filter_map(in_cone)
+                .min_by(|a, b| a.1.total_cmp(&b.1).then(a.2.total_cmp(&b.2)));
+            let held = state
+                .as_ref()
+                .filter(|s| s.target_id >= 0)
+                .and_then(|s| enemies.iter().position(|e| e.is_alive() && e.id == s.target_id))
+                .and_then(in_cone);
+            match (held, best) {
+                (Some(h), Some(b)) if b.1 >= h.1 * ASSIST_SWITCH_RATIO => Some(h),
+                (_, b) => b,
+            }
+        } else {
+            None
+        };
+
+        if let Some(s) = state.as_mut() {
+            s.target_id = best.map_or(-1, |(i, _, _)| enemies[i].id);
+        }
+        let (mut dx, mut dy) = (aim_dx, aim_dy);
+        if let Some((i, _, _)) = best.filter(|&(_, _, dist2)| dist2 > f32::EPSILON) {
+            let e = &enemies[i];
+            let current = aim_dy.atan2(aim_dx);
+            let to_target = wrap_angle((e.y - player_y).atan2(e.x - player_x) - current);
+            let angle = current + to_target * strength.clamp(0.0, 1.0);
+            dx = angle.cos() * aim_len;
+            dy = angle.sin() * aim_len;
+        }
+        unsafe {
+            out_dx.write(dx);
+            out_dy.write(dy);
+            write_out(out_target, best.map_or(-1, |(i, _, _)| i as i32))
+        }
+    })
+}
+
+/// Most targets a `MultiLock` can hold.
+pub const MULTILOCK_CAPACITY: usize = 8;
+
+/// Lock-on missile state: the first `count` entries of `target_ids` are locked enemy ids.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct MultiLock {
+    pub target_ids: [i32; MULTILOCK_CAPACITY],
+    pub count: i32,
+}
+
+/// Lock up to `max_locks` (at most `MULTILOCK_CAPACITY`) of the nearest alive enemies within
+/// `range`, nearest first and without duplicate ids, replacing any previous locks.
+/// Writes the number locked to `out_locked` (may be null).
+// Safe C entry point: `lock`, `enemies_ptr`, `out_locked` are null-checked before use;
+// the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn multilock_acquire(
+    lock: *mut MultiLock,
+    player_x: f32,
+    player_y: f32,
+    enemies_ptr: *const Enemy,
+    count: i32,
+    max_locks: i32,
+    range: f32,
+    out_locked: *mut i32,
+) -> DeadAimStatus {
+    ffi_guard(|| {
+        let lock = unsafe { arg_mut(lock) }?;
+        let enemies = unsafe { slice_arg(enemies_ptr, count) }?;
+        lock.target_ids = [-1; MULTILOCK_CAPACITY];
+        lock.count = 0;
+        let max_locks = max_locks.clamp(0, MULTILOCK_CAPACITY as i32) as usize;
+        let r2 = range * range;
+        let in_range = |_, e: &Enemy| (e.x - player_x).powi(2) + (e.y - player_y).powi(2) <= r2;
+
+        let mut locked = 0;
+        for i in k_nearest_matching(player_x, player_y, enemies, enemies.len(), in_range) {
+            if locked == max_locks {
+                break;
+            }
+            let id = enemies[i].id;
+            if !lock.target_ids[..locked].contains(&id) {
+                lock.target_ids[locked] = id;
+                locked += 1;
+            }
+        }
+        lock.count = locked as i32;
+        unsafe { write_opt(out_locked, locked as i32) };
+        Ok(())
+    })
+}
+
+/// Fire at every locked target: each one still alive is killed and paid `per_kill` through
+/// `shoot_enemy_and_reward` (so the kill validator and rate limit apply per kill; a vetoed or
+/// rate-limited payout doesn't stop the volley). The lock is cleared afterwards. Writes the
+/// number of targets killed to `out_killed` (may be null); outside a running match nothing is
+/// killed.
+// Safe C entry point: `lock`, `enemies_ptr`, `out_killed` are null-checked before use;
+// the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn multilock_fire(
+    lock: *mut MultiLock,
+    enemies_ptr: *mut Enemy,
+    count: i32,
+    wallet_ptr: *const u8,
+    wallet_len: usize,
+    per_kill: u64,
+    now_ms: u64,
+    out_killed: *mut i32,
+) -> DeadAimStatus {
+    ffi_guard(|| {
+        let lock = unsafe { arg_mut(lock) }?;
+        unsafe { slice_arg_mut(enemies_ptr, count) }?;
+        let locked = lock.count.clamp(0, MULTILOCK_CAPACITY as i32) as usize;
+        let mut killed = 0;
+        for &id in &lock.target_ids[..locked] {
+            let enemies = unsafe { slice_arg(enemies_ptr, count) }?;
+            let Some(index) = enemies.iter().position(|e| e.is_alive() && e.id == id) else {
+                continue;
+            };
+            shoot_enemy_and_reward(enemies_ptr, count, index as i32, wallet_ptr, wallet_len, per_kill, now_ms);
+            if !unsafe { slice_arg(enemies_ptr, count) }?[index].is_alive() {
+                killed += 1;
+            }
+        }
+        lock.target_ids = [-1; MULTILOCK_CAPACITY];
+        lock.count = 0;
+        unsafe { write_opt(out_killed, killed) };
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::combat::apply_damage;
+
+    fn update(lock: &mut AimLock, enemies: &[Enemy], dt: f32) -> i32 {
+        let mut index = -2;
+        let status = aim_lock_update(lock, 0.0, 0.0, enemies.as_ptr(), enemies.len() as i32, dt, 0.5, 1.0, &mut index);
+        assert_eq!(status, DeadAimStatus::Ok);
+        index
+    }
+
+    #[test]
+    fn lock_reacquires_the_same_frame_its_target_dies() {
+        let mut enemies = [Enemy::new(10, 1.0, 0.0), Enemy::new(11, 3.0, 0.0)];
+        let mut lock = AimLock { target_id: -1, progress: 0.0 };
+        assert_eq!(update(&mut lock, &enemies, 0.1), 0);
+        assert_eq!(update(&mut lock, &enemies, 0.4), 0);
+        assert_eq!(lock.target_id, 10);
+        assert!(lock.progress > 0.0);
+
+        assert_eq!(apply_damage(enemies.as_mut_ptr(), 2, 0, 5.0, std::ptr::null_mut()), DeadAimStatus::Ok);
+        assert!(!enemies[0].is_alive());
+        assert_eq!(update(&mut lock, &enemies, 0.0), 1);
+        assert_eq!((lock.target_id, lock.progress), (11, 0.0));
+    }
+
+    #[test]
+    fn closer_enemy_waits_for_the_lock_to_decay() {
+        let mut enemies = [Enemy::new(10, 2.0, 0.0), Enemy::new(11, 5.0, 0.0)];
+        let mut lock = AimLock { target_id: -1, progress: 0.0 };
+        update(&mut lock, &enemies, 0.0);
+        update(&mut lock, &enemies, 0.5);
+        assert_eq!(lock.progress, 1.0);
+
+        enemies[1].x = 1.0;
+        assert_eq!(update(&mut lock, &enemies, 0.5), 0);
+        assert_eq!(update(&mut lock, &enemies, 0.5), 1);
+        assert_eq!(lock.target_id, 11);
+    }
+}
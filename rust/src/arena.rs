@@ -0,0 +1,87 @@
+// src/arena.rs
+// Playfield bounds the movement functions keep enemies inside of.
+use crate::error::{ffi_guard, DeadAimStatus};
+use crate::Enemy;
+use std::sync::Mutex;
+
+/// What happens to an enemy that moves past the arena edge.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BoundaryMode {
+    /// Stop at the edge; velocity into the wall is zeroed.
+    Clamp,
+    /// Reappear at the opposite edge.
+    Wrap,
+    /// Reflect off the edge, flipping that velocity component.
+    Bounce,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Arena {
+    min_x: f32,
+    min_y: f32,
+    max_x: f32,
+    max_y: f32,
+    mode: BoundaryMode,
+}
+
+static ARENA: Mutex<Option<Arena>> = Mutex::new(None);
+
+/// Confine every mover (`move_enemies_*`, `world_tick`, ...) to the rectangle
+/// [`min_x`, `max_x`] x [`min_y`, `max_y`], handling enemies that cross an edge per `mode`.
+/// `InvalidArgument` (leaving the current bounds) for non-finite or empty bounds.
+#[no_mangle]
+pub extern "C" fn set_arena_bounds(
+    min_x: f32,
+    min_y: f32,
+    max_x: f32,
+    max_y: f32,
+    mode: BoundaryMode,
+) -> DeadAimStatus {
+    ffi_guard(|| {
+        let finite = [min_x, min_y, max_x, max_y].iter().all(|v| v.is_finite());
+        if !finite || min_x >= max_x || min_y >= max_y {
+            return Err(DeadAimStatus::InvalidArgument);
+        }
+        *ARENA.lock().unwrap_or_else(|e| e.into_inner()) = Some(Arena { min_x, min_y, max_x, max_y, mode });
+        Ok(())
+    })
+}
+
+/// Remove the arena bounds; enemies move unbounded again.
+#[no_mangle]
+pub extern "C" fn clear_arena_bounds() -> DeadAimStatus {
+    ffi_guard(|| {
+        *ARENA.lock().unwrap_or_else(|e| e.into_inner()) = None;
+        Ok(())
+    })
+}
+
+/// Apply the arena bounds (if any) to every alive enemy after a movement update.
+pub(crate) fn confine_enemies(enemies: &mut [Enemy]) {
+    let Some(arena) = *ARENA.lock().unwrap_or_else(|e| e.into_inner()) else {
+        return;
+    };
+    for e in enemies.iter_mut().filter(|e| e.is_alive()) {
+        confine_axis(&mut e.x, &mut e.vx, arena.min_x, arena.max_x, arena.mode);
+        confine_axis(&mut e.y, &mut e.vy, arena.min_y, arena.max_y, arena.mode);
+    }
+}
+
+fn confine_axis(pos: &mut f32, vel: &mut f32, min: f32, max: f32, mode: BoundaryMode) {
+    if *pos >= min && *pos <= max {
+        return;
+    }
+    match mode {
+        BoundaryMode::Clamp => {
+            *pos = pos.clamp(min, max);
+            *vel = 0.0;
+        }
+        BoundaryMode::Wrap => *pos = min + (*pos - min).rem_euclid(max - min),
+        BoundaryMode::Bounce => {
+            // fold the overshoot back in; anything wider than the arena ends up at the far edge
+            *pos = if *pos < min { (2.0 * min - *pos).min(max) } else { (2.0 * max - *pos).max(min) };
+            *vel = -*vel;
+        }
+    }
+}
@@ -0,0 +1,288 @@
+// src/ballistics.rs
+// Projectile math: intercept (target lead) solving and aim-point generation.
+use crate::combat::best_aoe_center_where;
+use crate::error::{arg_ref, ffi_guard, slice_arg, slice_arg_mut, write_opt, write_out, DeadAimStatus};
+use crate::Enemy;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+// f32 bits of the height below which sampled trajectories stop; defaults to -inf (no floor).
+static TRAJECTORY_FLOOR: AtomicU32 = AtomicU32::new(0xff80_0000);
+
+/// One semi-implicit Euler step under gravity (gravity pulls toward -y).
+pub(crate) fn integrate_step(x: &mut f32, y: &mut f32, vx: f32, vy: &mut f32, gravity: f32, dt: f32) {
+    *vy -= gravity * dt;
+    *x += vx * dt;
+    *y += *vy * dt;
+}
+
+/// Solve for the point where a projectile fired from (`origin_x`, `origin_y`) at `projectile_speed`
+/// meets `enemy`, assuming the enemy keeps its current velocity (`vx`/`vy`, units per tick, so
+/// `projectile_speed` is in units per tick too). Returns `None` if no intercept exists.
+pub(crate) fn solve_intercept(origin_x: f32, origin_y: f32, enemy: &Enemy, projectile_speed: f32) -> Option<(f32, f32)> {
+    if projectile_speed <= 0.0 {
+        return None;
+    }
+    let (dx, dy) = (enemy.x - origin_x, enemy.y - origin_y);
+    let (vx, vy) = (enemy.vx, enemy.vy);
+    // |d + v t| = s t  =>  (v.v - s^2) t^2 + 2 (d.v) t + d.d = 0
+    let a = vx * vx + vy * vy - projectile_speed * projectile_speed;
+    let b = 2.0 * (dx * vx + dy * vy);
+    let c = dx * dx + dy * dy;
+
+    let t = if a.abs() < 1e-6 {
+        // projectile and target speeds match: linear equation
+        if b.abs() < 1e-6 {
+            return None;
+        }
+        -c / b
+    } else {
+        let disc = b * b - 4.0 * a * c;
+        if disc < 0.0 {
+            return None;
+        }
+        let sq = disc.sqrt();
+        let (t1, t2) = ((-b - sq) / (2.0 * a), (-b + sq) / (2.0 * a));
+        let (lo, hi) = if t1 < t2 { (t1, t2) } else { (t2, t1) };
+        if lo >= 0.0 {
+            lo
+        } else {
+            hi
+        }
+    };
+    if !t.is_finite() || t < 0.0 {
+        return None;
+    }
+    Some((enemy.x + vx * t, enemy.y + vy * t))
+}
+
+/// Target lead: where to aim a projectile of `projectile_speed` (units per tick) from the player
+/// so it meets the alive enemy at `enemy_index`, given the enemy's current `vx`/`vy`. Writes the
+/// aim point to `out_x`/`out_y` and true to `out_intercepted` (may be null). When no intercept
+/// exists (target outruns the shot) the enemy's current position is written instead, with false.
+/// `InvalidIndex` for a bad index or a dead enemy.
+// Safe C entry point: `enemies_ptr`, `out_x`, `out_y`, `out_intercepted` are null-checked before use;
+// the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn compute_lead_point(
+    player_x: f32,
+    player_y: f32,
+    projectile_speed: f32,
+    enemies_ptr: *const Enemy,
+    count: i32,
+    enemy_index: i32,
+    out_x: *mut f32,
+    out_y: *mut f32,
+    out_intercepted: *mut bool,
+) -> DeadAimStatus {
+    ffi_guard(|| {
+        let enemies = unsafe { slice_arg(enemies_ptr, count) }?;
+        let e = usize::try_from(enemy_index).ok().and_then(|i| enemies.get(i)).filter(|e| e.is_alive());
+        let e = e.ok_or(DeadAimStatus::InvalidIndex)?;
+        if out_x.is_null() || out_y.is_null() {
+            return Err(DeadAimStatus::NullPointer);
+        }
+        let lead = solve_intercept(player_x, player_y, e, projectile_speed);
+        let (x, y) = lead.unwrap_or((e.x, e.y));
+        unsafe {
+            out_x.write(x);
+            out_y.write(y);
+            write_opt(out_intercepted, lead.is_some());
+        }
+        Ok(())
+    })
+}
+
+/// Lead-and-spread: solve the intercept for `enemy`, then spread `pellets` aim points evenly
+/// across `±spread_rad` around it (same range as the intercept), writing them to `out_x`/`out_y`
+/// (each at least `pellets` long) and their number to `out_written` (0 if no intercept).
+// Safe C entry point: `enemy`, `out_x`, `out_y`, `out_written` are null-checked before use;
+// the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn predict_spread_intercepts(
+    player_x: f32,
+    player_y: f32,
+    enemy: *const Enemy,
+    projectile_speed: f32,
+    spread_rad: f32,
+    pellets: i32,
+    out_x: *mut f32,
+    out_y: *mut f32,
+    out_written: *mut i32,
+) -> DeadAimStatus {
+    ffi_guard(|| {
+        let enemy = unsafe { arg_ref(enemy) }?;
+        let xs = unsafe { slice_arg_mut(out_x, pellets) }?;
+        let ys = unsafe { slice_arg_mut(out_y, pellets) }?;
+        if out_written.is_null() {
+            return Err(DeadAimStatus::NullPointer);
+        }
+        let Some((ix, iy)) = solve_intercept(player_x, player_y, enemy, projectile_speed) else {
+            return unsafe { write_out(out_written, 0) };
+        };
+        let (dx, dy) = (ix - player_x, iy - player_y);
+        let range = (dx * dx + dy * dy).sqrt();
+        let base = dy.atan2(dx);
+        let spread = spread_rad.abs();
+
+        let n = xs.len();
+        let mut written = 0;
+        for i in 0..n {
+            let offset = if n == 1 { 0.0 } else { -spread + 2.0 * spread * i as f32 / (n - 1) as f32 };
+            let angle = base + offset;
+            let (px, py) = (player_x + angle.cos() * range, player_y + angle.sin() * range);
+            if px.is_finite() && py.is_finite() {
+                xs[written] = px;
+                ys[written] = py;
+                written += 1;
+            }
+        }
+        unsafe { write_out(out_written, written as i32) }
+    })
+}
+
+/// Set the height below which `sample_trajectory` stops sampling (e.g. ground level).
+/// Pass negative infinity to disable the floor; `InvalidArgument` for NaN.
+#[no_mangle]
+pub extern "C" fn set_trajectory_floor(floor_y: f32) -> DeadAimStatus {
+    ffi_guard(|| {
+        if floor_y.is_nan() {
+            return Err(DeadAimStatus::InvalidArgument);
+        }
+        TRAJECTORY_FLOOR.store(floor_y.to_bits(), Ordering::Relaxed);
+        Ok(())
+    })
+}
+
+/// Sample a lobbed projectile's arc for rendering: starting at the origin, write up to `steps`
+/// positions spaced `dt` apart (gravity pulls toward -y) into `out_x`/`out_y`, stopping early
+/// once the arc drops below the trajectory floor, and the number written to `out_written`.
+/// `InvalidArgument` for a non-positive `dt`.
+// Safe C entry point: `out_x`, `out_y`, `out_written` are null-checked before use;
+// the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn sample_trajectory(
+    origin_x: f32,
+    origin_y: f32,
+    vx: f32,
+    vy: f32,
+    gravity: f32,
+    steps: i32,
+    dt: f32,
+    out_x: *mut f32,
+    out_y: *mut f32,
+    out_written: *mut i32,
+) -> DeadAimStatus {
+    ffi_guard(|| {
+        let xs = unsafe { slice_arg_mut(out_x, steps) }?;
+        let ys = unsafe { slice_arg_mut(out_y, steps) }?;
+        if dt.is_nan() || dt <= 0.0 {
+            return Err(DeadAimStatus::InvalidArgument);
+        }
+        let floor = f32::from_bits(TRAJECTORY_FLOOR.load(Ordering::Relaxed));
+
+        let (mut x, mut y, mut vy) = (origin_x, origin_y, vy);
+        let mut written = 0;
+        while written < xs.len() {
+            if y < floor {
+                break;
+            }
+            xs[written] = x;
+            ys[written] = y;
+            written += 1;
+            integrate_step(&mut x, &mut y, vx, &mut vy, gravity, dt);
+        }
+        unsafe { write_out(out_written, written as i32) }
+    })
+}
+
+/// Low-arc elevation (radians) that lands a shot fired at `speed` on level ground `distance`
+/// away under `gravity`; `None` when the target is out of range. Without gravity the shot is flat.
+pub(crate) fn launch_angle(distance: f32, speed: f32, gravity: f32) -> Option<f32> {
+    if speed <= 0.0 || distance.is_nan() || distance < 0.0 {
+        return None;
+    }
+    if gravity <= 0.0 {
+        return Some(0.0);
+    }
+    // range = v^2 sin(2θ) / g
+    let s = distance * gravity / (speed * speed);
+    (s <= 1.0).then(|| 0.5 * s.asin())
+}
+
+/// Solve the low-arc launch elevation for a lob of `distance` at `speed` under `gravity` (level
+/// ground), writing whether it is reachable to `out_reachable` and, if so, the elevation to
+/// `out_angle`. Unreachable means the distance is beyond `speed^2 / gravity`.
+// Safe C entry point: `out_angle`, `out_reachable` are null-checked before use;
+// the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn solve_launch_angle(
+    distance: f32,
+    speed: f32,
+    gravity: f32,
+    out_angle: *mut f32,
+    out_reachable: *mut bool,
+) -> DeadAimStatus {
+    ffi_guard(|| {
+        if out_angle.is_null() {
+            return Err(DeadAimStatus::NullPointer);
+        }
+        let angle = launch_angle(distance, speed, gravity);
+        unsafe { write_out(out_reachable, angle.is_some()) }?;
+        if let Some(angle) = angle {
+            unsafe { out_angle.write(angle) };
+        }
+        Ok(())
+    })
+}
+
+/// Grenade auto-aim: among blast centers within throwing range of the player, pick the one that
+/// catches the most alive enemies (see `best_aoe_center`) and solve the low-arc elevation to reach
+/// it. Writes whether a cluster is reachable to `out_found` and, if so, the elevation to
+/// `out_angle`, the number caught to `out_caught`, and the blast center to
+/// `out_center_x`/`out_center_y` (all four may be null). `InvalidArgument` for a negative or NaN
+/// blast radius.
+// Safe C entry point: every raw pointer argument is null-checked before use; the rest of
+// the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn best_grenade_throw(
+    player_x: f32,
+    player_y: f32,
+    blast_radius: f32,
+    throw_speed: f32,
+    gravity: f32,
+    enemies_ptr: *const Enemy,
+    count: i32,
+    out_found: *mut bool,
+    out_angle: *mut f32,
+    out_caught: *mut i32,
+    out_center_x: *mut f32,
+    out_center_y: *mut f32,
+) -> DeadAimStatus {
+    ffi_guard(|| {
+        let enemies = unsafe { slice_arg(enemies_ptr, count) }?;
+        if blast_radius.is_nan() || blast_radius < 0.0 {
+            return Err(DeadAimStatus::InvalidArgument);
+        }
+        if out_found.is_null() {
+            return Err(DeadAimStatus::NullPointer);
+        }
+        let dist = |x: f32, y: f32| ((x - player_x).powi(2) + (y - player_y).powi(2)).sqrt();
+        let reachable = |x, y| launch_angle(dist(x, y), throw_speed, gravity).is_some();
+        let throw = best_aoe_center_where(enemies, blast_radius, reachable)
+            .and_then(|(x, y, caught)| Some((launch_angle(dist(x, y), throw_speed, gravity)?, caught, x, y)));
+        unsafe {
+            if let Some((angle, caught, x, y)) = throw {
+                write_opt(out_angle, angle);
+                write_opt(out_caught, caught);
+                write_opt(out_center_x, x);
+                write_opt(out_center_y, y);
+            }
+            write_out(out_found, throw.is_some())
+        }
+    })
+}
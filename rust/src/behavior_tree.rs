@@ -0,0 +1,144 @@
+// src/behavior_tree.rs
+// Host-authored behavior trees, sent as bytes so enemy logic can change without a rebuild.
+//
+// Encoding (pre-order, one node after another):
+//   Sequence:  0x01 | child_count u8 | children...   (succeeds if every child does, in order)
+//   Selector:  0x02 | child_count u8 | children...   (succeeds at the first child that does)
+//   Condition: 0x03 | condition u8 | param f32 LE
+//   Action:    0x04 | action u8
+// A tick walks the tree and stops at the first action reached; that action is the enemy's
+// movement for the tick. Conditions and actions are the `BT_COND_*` / `BT_ACT_*` values.
+use crate::obstacles::line_of_sight;
+use crate::rng::RngState;
+use crate::Enemy;
+
+const NODE_SEQUENCE: u8 = 1;
+const NODE_SELECTOR: u8 = 2;
+const NODE_CONDITION: u8 = 3;
+const NODE_ACTION: u8 = 4;
+// Deepest nesting accepted, so hostile input can't blow the stack.
+const MAX_DEPTH: usize = 32;
+
+/// Player within `param` world units.
+pub const BT_COND_PLAYER_WITHIN: u8 = 0;
+/// Player within `param` world units and in line of sight (see `has_line_of_sight`).
+pub const BT_COND_PLAYER_IN_SIGHT: u8 = 1;
+/// Health fraction below `param` (enemies without a health model never qualify).
+pub const BT_COND_HEALTH_BELOW: u8 = 2;
+/// Passes with probability `param` (0..=1), rolled from the world's RNG.
+pub const BT_COND_CHANCE: u8 = 3;
+
+/// Random walk.
+pub const BT_ACT_WANDER: u8 = 0;
+/// Head for the player.
+pub const BT_ACT_SEEK_PLAYER: u8 = 1;
+/// Run from the player.
+pub const BT_ACT_FLEE_PLAYER: u8 = 2;
+/// Stand still.
+pub const BT_ACT_HOLD: u8 = 3;
+/// Walk the enemy's route or patrol, if it has one (fails otherwise).
+pub const BT_ACT_FOLLOW_ROUTE: u8 = 4;
+
+#[derive(Clone, Debug)]
+enum Node {
+    Sequence(Vec<Node>),
+    Selector(Vec<Node>),
+    Condition(u8, f32),
+    Action(u8),
+}
+
+/// A parsed tree; see the module header for the encoding.
+#[derive(Clone, Debug)]
+pub(crate) struct BehaviorTree {
+    root: Node,
+    /// The encoding it was parsed from, kept for world snapshots.
+    bytes: Vec<u8>,
+}
+
+/// What the enemy sees when its tree is evaluated.
+pub(crate) struct BtContext<'a> {
+    pub player_x: f32,
+    pub player_y: f32,
+    pub has_route: bool,
+    pub rng: &'a mut RngState,
+}
+
+impl BehaviorTree {
+    /// Parse an encoded tree; `None` for truncated or malformed input, unknown node, condition
+    /// or action codes, nesting deeper than 32, or trailing bytes.
+    pub(crate) fn parse(bytes: &[u8]) -> Option<Self> {
+        let mut pos = 0;
+        let root = parse_node(bytes, &mut pos, 0)?;
+        (pos == bytes.len()).then(|| BehaviorTree { root, bytes: bytes.to_vec() })
+    }
+
+    pub(crate) fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Run one tick for `e` and return the chosen `BT_ACT_*` action, or `None` if no action
+    /// was reached.
+    pub(crate) fn decide(&self, e: &Enemy, ctx: &mut BtContext) -> Option<u8> {
+        let mut action = None;
+        eval(&self.root, e, ctx, &mut action);
+        action
+    }
+}
+
+fn parse_node(bytes: &[u8], pos: &mut usize, depth: usize) -> Option<Node> {
+    if depth > MAX_DEPTH {
+        return None;
+    }
+    let mut byte = || {
+        let b = *bytes.get(*pos)?;
+        *pos += 1;
+        Some(b)
+    };
+    match byte()? {
+        kind @ (NODE_SEQUENCE | NODE_SELECTOR) => {
+            let count = byte()?;
+            let children = (0..count).map(|_| parse_node(bytes, pos, depth + 1)).collect::<Option<_>>()?;
+            Some(if kind == NODE_SEQUENCE { Node::Sequence(children) } else { Node::Selector(children) })
+        }
+        NODE_CONDITION => {
+            let cond = byte()?;
+            let param = f32::from_le_bytes(bytes.get(*pos..*pos + 4)?.try_into().ok()?);
+            *pos += 4;
+            (cond <= BT_COND_CHANCE).then_some(Node::Condition(cond, param))
+        }
+        NODE_ACTION => {
+            let action = byte()?;
+            (action <= BT_ACT_FOLLOW_ROUTE).then_some(Node::Action(action))
+        }
+        _ => None,
+    }
+}
+
+/// Evaluate `node`; once an action is picked every caller unwinds as a success.
+fn eval(node: &Node, e: &Enemy, ctx: &mut BtContext, action: &mut Option<u8>) -> bool {
+    match node {
+        Node::Sequence(children) => {
+            // an action picked mid-sequence ends the tick as a success
+            children.iter().all(|c| eval(c, e, ctx, action) && action.is_none()) || action.is_some()
+        }
+        Node::Selector(children) => children.iter().any(|c| eval(c, e, ctx, action)),
+        Node::Condition(cond, param) => {
+            let dist2 = (ctx.player_x - e.x).powi(2) + (ctx.player_y - e.y).powi(2);
+            match *cond {
+                BT_COND_PLAYER_WITHIN => dist2 <= param * param,
+                BT_COND_PLAYER_IN_SIGHT => {
+                    dist2 <= param * param && line_of_sight(e.x, e.y, ctx.player_x, ctx.player_y)
+                }
+                BT_COND_HEALTH_BELOW => e.max_health > 0.0 && e.health < e.max_health * param,
+                _ => ctx.rng.next_f32() < *param,
+            }
+        }
+        Node::Action(act) => {
+            if *act == BT_ACT_FOLLOW_ROUTE && !ctx.has_route {
+                return false;
+            }
+            *action = Some(*act);
+            true
+        }
+    }
+}
@@ -0,0 +1,162 @@
+// src/callbacks.rs
+// Push notifications for kills and payouts, as an alternative to draining the event queue:
+// `extern "C"` function pointers for native hosts, JS functions for the wasm frontend.
+use crate::error::{ffi_guard, DeadAimStatus};
+use crate::Enemy;
+use std::cell::{Cell, RefCell};
+use std::sync::Mutex;
+
+/// Native kill hook: the enemy's id and where it died.
+type KillCallback = extern "C" fn(enemy_id: i32, x: f32, y: f32);
+/// Native payout hook: the enemy paid for (-1 if not a kill reward) and the amount sent.
+type RewardCallback = extern "C" fn(enemy_id: i32, amount: u64);
+
+static KILL_CALLBACK: Mutex<Option<KillCallback>> = Mutex::new(None);
+static REWARD_CALLBACK: Mutex<Option<RewardCallback>> = Mutex::new(None);
+
+thread_local! {
+    /// How many `deferred` calls are running on this thread, and the callbacks they held back.
+    static DEFER_DEPTH: Cell<u32> = const { Cell::new(0) };
+    static DEFERRED: RefCell<Vec<Box<dyn FnOnce()>>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Leaves a `deferred` call, dropping what it held back if it panicked.
+struct DeferScope;
+
+impl Drop for DeferScope {
+    fn drop(&mut self) {
+        let depth = DEFER_DEPTH.with(|d| {
+            d.set(d.get() - 1);
+            d.get()
+        });
+        if depth == 0 && std::thread::panicking() {
+            DEFERRED.with(|q| q.borrow_mut().clear());
+        }
+    }
+}
+
+/// Run `f`, holding back every callback it triggers, then fire them in order. The registry runs
+/// world calls under this with the world locked inside `f`, so callbacks fire after the lock is
+/// released and may call back into the same world.
+pub(crate) fn deferred<T>(f: impl FnOnce() -> T) -> T {
+    DEFER_DEPTH.with(|d| d.set(d.get() + 1));
+    let out = {
+        let _scope = DeferScope;
+        f()
+    };
+    if DEFER_DEPTH.with(Cell::get) == 0 {
+        for call in DEFERRED.with(|q| std::mem::take(&mut *q.borrow_mut())) {
+            call();
+        }
+    }
+    out
+}
+
+/// Invoke a host callback now, or once the enclosing `deferred` call returns.
+pub(crate) fn dispatch(call: impl FnOnce() + 'static) {
+    if DEFER_DEPTH.with(Cell::get) > 0 {
+        DEFERRED.with(|q| q.borrow_mut().push(Box::new(call)));
+    } else {
+        call();
+    }
+}
+
+/// Install (or clear, with null) the callback fired for every enemy kill, on any kill path.
+#[no_mangle]
+pub extern "C" fn register_kill_callback(cb: Option<KillCallback>) -> DeadAimStatus {
+    ffi_guard(|| {
+        *KILL_CALLBACK.lock().unwrap_or_else(|e| e.into_inner()) = cb;
+        Ok(())
+    })
+}
+
+/// Install (or clear, with null) the callback fired for every payout dispatched to the wallet
+/// hook (see `reward_player`).
+#[no_mangle]
+pub extern "C" fn register_reward_callback(cb: Option<RewardCallback>) -> DeadAimStatus {
+    ffi_guard(|| {
+        *REWARD_CALLBACK.lock().unwrap_or_else(|e| e.into_inner()) = cb;
+        Ok(())
+    })
+}
+
+/// Fire the kill hooks for `e` (see `dispatch`).
+pub(crate) fn notify_kill(e: &Enemy) {
+    // copy the callback out so it may re-register itself
+    let cb = *KILL_CALLBACK.lock().unwrap_or_else(|e| e.into_inner());
+    let (id, x, y) = (e.id, e.x, e.y);
+    if let Some(cb) = cb {
+        dispatch(move || cb(id, x, y));
+    }
+    #[cfg(target_arch = "wasm32")]
+    dispatch(move || js::notify_kill(id, x, y));
+}
+
+/// Fire the payout hooks (see `dispatch`).
+pub(crate) fn notify_reward(enemy_id: i32, amount: u64) {
+    let cb = *REWARD_CALLBACK.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(cb) = cb {
+        dispatch(move || cb(enemy_id, amount));
+    }
+    #[cfg(target_arch = "wasm32")]
+    dispatch(move || js::notify_reward(enemy_id, amount));
+}
+
+#[cfg(target_arch = "wasm32")]
+mod js {
+    use crate::js_log;
+    use std::cell::RefCell;
+    use wasm_bindgen::prelude::*;
+
+    #[wasm_bindgen]
+    extern "C" {
+        /// The JS `Function` type (the one `js_sys::Function` binds), declared here so the
+        /// crate doesn't pull in `js-sys` for two calls.
+        #[derive(Clone, Debug)]
+        pub type Function;
+
+        #[wasm_bindgen(method, catch, js_name = call)]
+        fn call2(this: &Function, context: &JsValue, a: &JsValue, b: &JsValue) -> Result<JsValue, JsValue>;
+
+        #[wasm_bindgen(method, catch, js_name = call)]
+        fn call3(this: &Function, context: &JsValue, a: &JsValue, b: &JsValue, c: &JsValue) -> Result<JsValue, JsValue>;
+    }
+
+    thread_local! {
+        static ON_ENEMY_KILLED: RefCell<Option<Function>> = const { RefCell::new(None) };
+        static ON_REWARD: RefCell<Option<Function>> = const { RefCell::new(None) };
+    }
+
+    /// Call `cb(enemyId, x, y)` for every enemy kill; `undefined`/`null` clears it.
+    #[wasm_bindgen]
+    pub fn set_on_enemy_killed(cb: Option<Function>) {
+        ON_ENEMY_KILLED.with(|slot| *slot.borrow_mut() = cb);
+    }
+
+    /// Call `cb(enemyId, amount)` for every payout dispatched (`enemyId` -1 when not a kill
+    /// reward; `amount` as a Number); `undefined`/`null` clears it.
+    #[wasm_bindgen]
+    pub fn set_on_reward(cb: Option<Function>) {
+        ON_REWARD.with(|slot| *slot.borrow_mut() = cb);
+    }
+
+    pub(super) fn notify_kill(id: i32, x: f32, y: f32) {
+        // clone out of the slot so the callback may replace itself
+        let Some(cb) = ON_ENEMY_KILLED.with(|slot| slot.borrow().clone()) else {
+            return;
+        };
+        let args = (JsValue::from(id), JsValue::from(x), JsValue::from(y));
+        if cb.call3(&JsValue::NULL, &args.0, &args.1, &args.2).is_err() {
+            js_log("on_enemy_killed callback threw");
+        }
+    }
+
+    pub(super) fn notify_reward(enemy_id: i32, amount: u64) {
+        let Some(cb) = ON_REWARD.with(|slot| slot.borrow().clone()) else {
+            return;
+        };
+        if cb.call2(&JsValue::NULL, &JsValue::from(enemy_id), &JsValue::from_f64(amount as f64)).is_err() {
+            js_log("on_reward callback threw");
+        }
+    }
+}
@@ -0,0 +1,91 @@
+// src/collision.rs
+// Enemy-vs-enemy separation, so crowds spread out instead of collapsing onto one spot.
+use crate::combat::hit_radius_of;
+use crate::error::{ffi_guard, slice_arg_mut, write_opt, DeadAimStatus};
+use crate::obstacles::circle_blocked;
+use crate::Enemy;
+use std::collections::HashMap;
+
+/// Push apart every pair of alive enemies whose hitboxes (see `hit_radius_of`) overlap, each
+/// moving half the overlap along the line between their centers (enemies stacked exactly on top
+/// of each other split along x). A push that would move an enemy into an obstacle is skipped.
+/// Candidate pairs come from a uniform grid sized to the largest hitbox, so only neighboring
+/// cells are compared. Returns the number of overlapping pairs resolved.
+pub(crate) fn separate_enemies(enemies: &mut [Enemy]) -> usize {
+    let radii: Vec<f32> = enemies.iter().map(hit_radius_of).collect();
+    let cell_size = enemies
+        .iter()
+        .zip(&radii)
+        .filter(|(e, _)| e.is_alive())
+        .fold(0.0f32, |max, (_, &r)| max.max(r))
+        * 2.0;
+    if cell_size.is_nan() || cell_size <= 0.0 {
+        return 0;
+    }
+    let cell_of = |x: f32, y: f32| ((x / cell_size).floor() as i32, (y / cell_size).floor() as i32);
+    let mut cells: HashMap<(i32, i32), Vec<usize>> = HashMap::new();
+    for (i, e) in enemies.iter().enumerate().filter(|(_, e)| e.is_alive()) {
+        cells.entry(cell_of(e.x, e.y)).or_default().push(i);
+    }
+
+    let mut resolved = 0;
+    for i in 0..enemies.len() {
+        if !enemies[i].is_alive() {
+            continue;
+        }
+        let (cx, cy) = cell_of(enemies[i].x, enemies[i].y);
+        for gx in cx.saturating_sub(1)..=cx.saturating_add(1) {
+            for gy in cy.saturating_sub(1)..=cy.saturating_add(1) {
+                let Some(bucket) = cells.get(&(gx, gy)) else {
+                    continue;
+                };
+                for &j in bucket.iter().filter(|&&j| j > i) {
+                    if push_apart(enemies, i, j, radii[i] + radii[j]) {
+                        resolved += 1;
+                    }
+                }
+            }
+        }
+    }
+    resolved
+}
+
+/// Separate enemies `i` < `j` if their centers are closer than `reach`.
+fn push_apart(enemies: &mut [Enemy], i: usize, j: usize, reach: f32) -> bool {
+    let (dx, dy) = (enemies[j].x - enemies[i].x, enemies[j].y - enemies[i].y);
+    let dist2 = dx * dx + dy * dy;
+    if dist2 >= reach * reach {
+        return false;
+    }
+    let dist = dist2.sqrt();
+    let (nx, ny) = if dist > 0.0 { (dx / dist, dy / dist) } else { (1.0, 0.0) };
+    let half = (reach - dist) * 0.5;
+    for (k, sign) in [(i, -half), (j, half)] {
+        let (x, y) = (enemies[k].x + nx * sign, enemies[k].y + ny * sign);
+        if !circle_blocked(x, y, hit_radius_of(&enemies[k])) {
+            enemies[k].x = x;
+            enemies[k].y = y;
+        }
+    }
+    true
+}
+
+/// Resolve overlaps between the alive enemies in the array (see `world_set_enemy_collision` for
+/// worlds, which do this every movement update). Writes the number of overlapping pairs pushed
+/// apart to `out_resolved` (may be null); call again to settle dense crowds further.
+// Safe C entry point: `enemies_ptr`, `out_resolved` are null-checked before use;
+// the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn resolve_enemy_collisions(
+    enemies_ptr: *mut Enemy,
+    count: i32,
+    out_resolved: *mut i32,
+) -> DeadAimStatus {
+    ffi_guard(|| {
+        let enemies = unsafe { slice_arg_mut(enemies_ptr, count) }?;
+        let resolved = separate_enemies(enemies);
+        unsafe { write_opt(out_resolved, resolved as i32) };
+        Ok(())
+    })
+}
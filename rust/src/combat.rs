@@ -0,0 +1,853 @@
+// src/combat.rs
+// Combat reactions and effects applied to enemy arrays.
+use crate::enemy_types::enemy_type;
+use crate::error::{ffi_guard, slice_arg, slice_arg_mut, write_opt, write_out, DeadAimStatus, FfiResult};
+use crate::events::enemy_killed;
+use crate::match_state::with_part;
+use crate::obstacles::Rect;
+use crate::rng::with_rng;
+use crate::spawner::check_wave_cleared;
+use crate::Enemy;
+use std::sync::atomic::{AtomicI32, AtomicU32, Ordering};
+use std::sync::Mutex;
+
+// f32 bits of the health fraction below which an enemy goes berserk; defaults to 0.25.
+static BERSERK_THRESHOLD: AtomicU32 = AtomicU32::new(0x3e80_0000);
+// f32 bits of the speed/damage multiplier applied while berserk; defaults to 1.5.
+static BERSERK_MULTIPLIER: AtomicU32 = AtomicU32::new(0x3fc0_0000);
+
+// f32 bits of the cooldown (seconds) applied after a successful dodge; defaults to 2.0.
+static DODGE_COOLDOWN: AtomicU32 = AtomicU32::new(0x4000_0000);
+
+// Frames of hit stagger applied to an enemy that survives damage; defaults to 6.
+static FLINCH_DURATION: AtomicI32 = AtomicI32::new(6);
+
+/// Set how many movement ticks a surviving enemy flinches (half speed) after taking damage;
+/// 0 disables flinching. `InvalidArgument` for a negative count.
+#[no_mangle]
+pub extern "C" fn set_flinch_duration(frames: i32) -> DeadAimStatus {
+    ffi_guard(|| {
+        if frames < 0 {
+            return Err(DeadAimStatus::InvalidArgument);
+        }
+        FLINCH_DURATION.store(frames, Ordering::Relaxed);
+        Ok(())
+    })
+}
+
+// f32 bits of the default radius of an enemy's circular hitbox; defaults to 0.5.
+static HIT_RADIUS: AtomicU32 = AtomicU32::new(0x3f00_0000);
+
+/// Set the radius of the circular hitbox projectiles, hitscan shots and enemy collision use, for
+/// enemies whose type doesn't define its own. `InvalidArgument` for a negative/NaN radius.
+#[no_mangle]
+pub extern "C" fn set_enemy_hit_radius(radius: f32) -> DeadAimStatus {
+    ffi_guard(|| {
+        if radius.is_nan() || radius < 0.0 {
+            return Err(DeadAimStatus::InvalidArgument);
+        }
+        HIT_RADIUS.store(radius.to_bits(), Ordering::Relaxed);
+        Ok(())
+    })
+}
+
+/// Writes the hitbox radius enemies of `type_id` use for shots, projectiles and enemy collision:
+/// the type's own radius (see `register_enemy_type`), or `set_enemy_hit_radius`'s value for
+/// unregistered types and types that don't set one.
+// Safe C entry point: `out_radius` is null-checked before use; the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn enemy_type_hit_radius(type_id: i32, out_radius: *mut f32) -> DeadAimStatus {
+    ffi_guard(|| unsafe { write_out(out_radius, type_hit_radius(type_id)) })
+}
+
+fn type_hit_radius(type_id: i32) -> f32 {
+    match enemy_type(type_id).filter(|t| t.hit_radius > 0.0) {
+        Some(t) => t.hit_radius,
+        None => f32::from_bits(HIT_RADIUS.load(Ordering::Relaxed)),
+    }
+}
+
+/// Hitbox radius of `e` (see `enemy_type_hit_radius`).
+pub(crate) fn hit_radius_of(e: &Enemy) -> f32 {
+    type_hit_radius(e.type_id)
+}
+
+/// Earliest fraction `t` in 0..=1 along the segment (`x0`, `y0`) -> (`x1`, `y1`) that lies inside
+/// the circle of `radius` around (`cx`, `cy`); 0 when the segment starts inside it.
+fn segment_circle_hit(x0: f32, y0: f32, x1: f32, y1: f32, cx: f32, cy: f32, radius: f32) -> Option<f32> {
+    let (dx, dy) = (x1 - x0, y1 - y0);
+    let (fx, fy) = (x0 - cx, y0 - cy);
+    let c = fx * fx + fy * fy - radius * radius;
+    if c <= 0.0 {
+        return Some(0.0);
+    }
+    let a = dx * dx + dy * dy;
+    let b = fx * dx + fy * dy;
+    if a <= f32::EPSILON || b >= 0.0 {
+        // degenerate segment, or heading away from the circle
+        return None;
+    }
+    let disc = b * b - a * c;
+    if disc < 0.0 {
+        return None;
+    }
+    let t = (-b - disc.sqrt()) / a;
+    (t <= 1.0).then_some(t)
+}
+
+/// The alive enemy whose hitbox the segment (`x0`, `y0`) -> (`x1`, `y1`) enters first, with the
+/// entry fraction along the segment; ties keep the lower index.
+pub(crate) fn first_enemy_on_segment(enemies: &[Enemy], x0: f32, y0: f32, x1: f32, y1: f32) -> Option<(usize, f32)> {
+    let mut first: Option<(usize, f32)> = None;
+    for (i, e) in enemies.iter().enumerate().filter(|(_, e)| e.is_alive()) {
+        if let Some(t) = segment_circle_hit(x0, y0, x1, y1, e.x, e.y, hit_radius_of(e)) {
+            if first.is_none_or(|(_, best)| t < best) {
+                first = Some((i, t));
+            }
+        }
+    }
+    first
+}
+
+/// Hit reactions for a damaged enemy: restart its regen delay and, if it survived, stagger it
+/// without shortening a longer flinch in progress.
+fn register_hit(e: &mut Enemy) {
+    e.frames_since_hit = 0;
+    if e.is_alive() {
+        e.flinch_frames = e.flinch_frames.max(FLINCH_DURATION.load(Ordering::Relaxed));
+    }
+}
+
+/// Set the cooldown (seconds) an enemy must wait between dodges. `InvalidArgument` for a
+/// negative/NaN value.
+#[no_mangle]
+pub extern "C" fn set_dodge_cooldown(seconds: f32) -> DeadAimStatus {
+    ffi_guard(|| {
+        if seconds.is_nan() || seconds < 0.0 {
+            return Err(DeadAimStatus::InvalidArgument);
+        }
+        DODGE_COOLDOWN.store(seconds.to_bits(), Ordering::Relaxed);
+        Ok(())
+    })
+}
+
+/// Count down every enemy's `dodge_cooldown` by `dt` seconds (floored at 0).
+// Safe C entry point: `enemies_ptr` is null-checked before use; the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn tick_dodge_cooldowns(enemies_ptr: *mut Enemy, count: i32, dt: f32) -> DeadAimStatus {
+    ffi_guard(|| {
+        step_dodge_cooldowns(unsafe { slice_arg_mut(enemies_ptr, count) }?, dt);
+        Ok(())
+    })
+}
+
+pub(crate) fn step_dodge_cooldowns(enemies: &mut [Enemy], dt: f32) {
+    for e in enemies.iter_mut() {
+        if e.dodge_cooldown > 0.0 {
+            e.dodge_cooldown = (e.dodge_cooldown - dt).max(0.0);
+        }
+    }
+}
+
+/// Self-healing tick: every alive enemy with a health model that hasn't been hit for at least
+/// `regen_delay_frames` ticks regains `regen_rate` health (capped at `max_health`). Each call
+/// also advances every alive enemy's `frames_since_hit`.
+// Safe C entry point: `enemies_ptr` is null-checked before use; the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn tick_regen(enemies_ptr: *mut Enemy, count: i32, regen_delay_frames: i32) -> DeadAimStatus {
+    ffi_guard(|| {
+        let enemies = unsafe { slice_arg_mut(enemies_ptr, count) }?;
+        for e in enemies.iter_mut().filter(|e| e.is_alive()) {
+            if e.max_health > 0.0 && e.regen_rate > 0.0 && e.frames_since_hit >= regen_delay_frames {
+                e.health = (e.health + e.regen_rate).min(e.max_health);
+            }
+            e.frames_since_hit = e.frames_since_hit.saturating_add(1);
+        }
+        Ok(())
+    })
+}
+
+/// The alive enemy at `index`; `InvalidIndex` if there is none.
+fn alive_at(enemies: &mut [Enemy], index: i32) -> FfiResult<&mut Enemy> {
+    let e = usize::try_from(index).ok().and_then(|i| enemies.get_mut(i));
+    e.filter(|e| e.is_alive()).ok_or(DeadAimStatus::InvalidIndex)
+}
+
+/// Teleport-dodge: if the enemy at `index` is alive and its cooldown is ready, displace it
+/// `dodge_dist` perpendicular to the threat direction (left/right picked by the seeded RNG),
+/// reset its cooldown, and write true to `out_dodged` (may be null); false while the cooldown
+/// runs. `InvalidIndex` for a bad index or a dead enemy.
+// Safe C entry point: `enemies_ptr`, `out_dodged` are null-checked before use;
+// the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn try_dodge(
+    enemies_ptr: *mut Enemy,
+    count: i32,
+    index: i32,
+    threat_x: f32,
+    threat_y: f32,
+    dodge_dist: f32,
+    out_dodged: *mut bool,
+) -> DeadAimStatus {
+    ffi_guard(|| {
+        let e = alive_at(unsafe { slice_arg_mut(enemies_ptr, count) }?, index)?;
+        if e.dodge_cooldown > 0.0 {
+            unsafe { write_opt(out_dodged, false) };
+            return Ok(());
+        }
+
+        // direction the threat is coming from; fall back to +x when it sits on the enemy
+        let (mut dx, mut dy) = (e.x - threat_x, e.y - threat_y);
+        let len = (dx * dx + dy * dy).sqrt();
+        if len > f32::EPSILON {
+            dx /= len;
+            dy /= len;
+        } else {
+            dx = 1.0;
+            dy = 0.0;
+        }
+        let side = if with_rng(|rng| rng.next_bool()) { 1.0 } else { -1.0 };
+        e.x += -dy * side * dodge_dist;
+        e.y += dx * side * dodge_dist;
+        e.dodge_cooldown = f32::from_bits(DODGE_COOLDOWN.load(Ordering::Relaxed));
+        unsafe { write_opt(out_dodged, true) };
+        Ok(())
+    })
+}
+
+/// Stun a single alive enemy for `frames` movement ticks (never shortens a longer active stun).
+/// `InvalidIndex` for a bad index or a dead enemy.
+// Safe C entry point: `enemies_ptr` is null-checked before use; the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn stun_enemy(enemies_ptr: *mut Enemy, count: i32, index: i32, frames: i32) -> DeadAimStatus {
+    ffi_guard(|| {
+        let e = alive_at(unsafe { slice_arg_mut(enemies_ptr, count) }?, index)?;
+        e.stun_frames = e.stun_frames.max(frames);
+        Ok(())
+    })
+}
+
+/// Crowd control: freeze every alive enemy within `radius` of the center for `freeze_frames`
+/// movement ticks (never shortening a longer active stun). Writes the number frozen to
+/// `out_frozen` (may be null). `InvalidArgument` for a negative radius or non-positive frames.
+// Safe C entry point: `enemies_ptr`, `out_frozen` are null-checked before use;
+// the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn freeze_enemies_in_radius(
+    center_x: f32,
+    center_y: f32,
+    radius: f32,
+    freeze_frames: i32,
+    enemies_ptr: *mut Enemy,
+    count: i32,
+    out_frozen: *mut i32,
+) -> DeadAimStatus {
+    ffi_guard(|| {
+        let enemies = unsafe { slice_arg_mut(enemies_ptr, count) }?;
+        if radius.is_nan() || radius < 0.0 || freeze_frames <= 0 {
+            return Err(DeadAimStatus::InvalidArgument);
+        }
+        let r2 = radius * radius;
+        let mut frozen = 0;
+        for e in enemies.iter_mut().filter(|e| e.is_alive()) {
+            let dx = e.x - center_x;
+            let dy = e.y - center_y;
+            if dx * dx + dy * dy <= r2 {
+                e.stun_frames = e.stun_frames.max(freeze_frames);
+                frozen += 1;
+            }
+        }
+        unsafe { write_opt(out_frozen, frozen) };
+        Ok(())
+    })
+}
+
+/// Taunt: every alive enemy within `radius` of (`taunt_x`, `taunt_y`) pursues that point instead
+/// of the player in `move_enemies_seek` for the next `duration_frames` ticks. A new taunt
+/// replaces any earlier one. Writes the number taunted to `out_taunted` (may be null).
+/// `InvalidArgument` for a negative radius or non-positive duration.
+// Safe C entry point: `enemies_ptr`, `out_taunted` are null-checked before use;
+// the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn apply_taunt(
+    enemies_ptr: *mut Enemy,
+    count: i32,
+    taunt_x: f32,
+    taunt_y: f32,
+    radius: f32,
+    duration_frames: i32,
+    out_taunted: *mut i32,
+) -> DeadAimStatus {
+    ffi_guard(|| {
+        let enemies = unsafe { slice_arg_mut(enemies_ptr, count) }?;
+        if duration_frames <= 0 || radius.is_nan() || radius < 0.0 {
+            return Err(DeadAimStatus::InvalidArgument);
+        }
+        let r2 = radius * radius;
+        let mut taunted = 0;
+        for e in enemies.iter_mut().filter(|e| e.is_alive()) {
+            if (e.x - taunt_x).powi(2) + (e.y - taunt_y).powi(2) <= r2 {
+                e.taunt_x = taunt_x;
+                e.taunt_y = taunt_y;
+                e.taunt_frames = duration_frames;
+                taunted += 1;
+            }
+        }
+        unsafe { write_opt(out_taunted, taunted) };
+        Ok(())
+    })
+}
+
+/// Set the health fraction (0..=1) below which enemies go berserk; 0 disables berserk.
+/// `InvalidArgument` outside 0..=1.
+#[no_mangle]
+pub extern "C" fn set_berserk_threshold(frac: f32) -> DeadAimStatus {
+    ffi_guard(|| {
+        if !(0.0..=1.0).contains(&frac) {
+            return Err(DeadAimStatus::InvalidArgument);
+        }
+        BERSERK_THRESHOLD.store(frac.to_bits(), Ordering::Relaxed);
+        Ok(())
+    })
+}
+
+/// Set the movement speed / attack damage multiplier applied to berserk enemies.
+/// `InvalidArgument` for a non-finite or non-positive multiplier.
+#[no_mangle]
+pub extern "C" fn set_berserk_multiplier(mult: f32) -> DeadAimStatus {
+    ffi_guard(|| {
+        if !mult.is_finite() || mult <= 0.0 {
+            return Err(DeadAimStatus::InvalidArgument);
+        }
+        BERSERK_MULTIPLIER.store(mult.to_bits(), Ordering::Relaxed);
+        Ok(())
+    })
+}
+
+/// An alive enemy with a health model whose health has dropped below the berserk threshold.
+pub(crate) fn is_berserk(e: &Enemy) -> bool {
+    let threshold = f32::from_bits(BERSERK_THRESHOLD.load(Ordering::Relaxed));
+    e.is_alive() && e.max_health > 0.0 && e.health < threshold * e.max_health
+}
+
+/// Speed/damage multiplier for `e`: the berserk multiplier while enraged, otherwise 1.
+pub(crate) fn berserk_multiplier(e: &Enemy) -> f32 {
+    if is_berserk(e) {
+        f32::from_bits(BERSERK_MULTIPLIER.load(Ordering::Relaxed))
+    } else {
+        1.0
+    }
+}
+
+/// Writes whether the enemy at `index` is berserk (for the renderer's rage visual).
+// Safe C entry point: `enemies_ptr`, `out_berserk` are null-checked before use;
+// the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn enemy_is_berserk(
+    enemies_ptr: *const Enemy,
+    count: i32,
+    index: i32,
+    out_berserk: *mut bool,
+) -> DeadAimStatus {
+    ffi_guard(|| {
+        let e = enemy_at(unsafe { slice_arg(enemies_ptr, count) }?, index)?;
+        unsafe { write_out(out_berserk, is_berserk(e)) }
+    })
+}
+
+/// The enemy at `index`; `InvalidIndex` outside the array.
+fn enemy_at(enemies: &[Enemy], index: i32) -> FfiResult<&Enemy> {
+    usize::try_from(index).ok().and_then(|i| enemies.get(i)).ok_or(DeadAimStatus::InvalidIndex)
+}
+
+/// Writes the multiplier the host should apply to the attack damage of the enemy at `index`
+/// (1.0 normally, the berserk multiplier while enraged).
+// Safe C entry point: `enemies_ptr`, `out_multiplier` are null-checked before use;
+// the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn enemy_damage_multiplier(
+    enemies_ptr: *const Enemy,
+    count: i32,
+    index: i32,
+    out_multiplier: *mut f32,
+) -> DeadAimStatus {
+    ffi_guard(|| {
+        let e = enemy_at(unsafe { slice_arg(enemies_ptr, count) }?, index)?;
+        unsafe { write_out(out_multiplier, berserk_multiplier(e)) }
+    })
+}
+
+/// Index of the alive enemy tethered to `enemies[index]`, if the link is still mutual.
+fn tether_partner(enemies: &[Enemy], index: usize) -> Option<usize> {
+    let e = &enemies[index];
+    if e.tether_id < 0 {
+        return None;
+    }
+    enemies
+        .iter()
+        .enumerate()
+        .find(|&(j, p)| j != index && p.is_alive() && p.id == e.tether_id && p.tether_id == e.id)
+        .map(|(j, _)| j)
+}
+
+/// Subtract `damage` from one enemy, killing it at zero health; survivors flinch.
+fn damage_single(e: &mut Enemy, damage: f32) {
+    if e.max_health <= 0.0 {
+        // no health model: any hit is lethal
+        e.health = 0.0;
+        e.set_alive(false);
+        return;
+    }
+    e.health = (e.health - damage).max(0.0);
+    if e.health <= 0.0 {
+        e.set_alive(false);
+    }
+    register_hit(e);
+}
+
+/// Link two alive enemies so damage to either is split between them (see `shoot_enemy_damage`).
+/// Any previous tether of either enemy is replaced. `InvalidIndex` for bad, dead or identical
+/// indices.
+// Safe C entry point: `enemies_ptr` is null-checked before use; the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn tether_enemies(enemies_ptr: *mut Enemy, count: i32, index_a: i32, index_b: i32) -> DeadAimStatus {
+    ffi_guard(|| {
+        let enemies = unsafe { slice_arg_mut(enemies_ptr, count) }?;
+        let valid = |i: i32| usize::try_from(i).ok().filter(|&i| enemies.get(i).is_some_and(Enemy::is_alive));
+        let (Some(a), Some(b)) = (valid(index_a), valid(index_b)) else {
+            return Err(DeadAimStatus::InvalidIndex);
+        };
+        if a == b {
+            return Err(DeadAimStatus::InvalidIndex);
+        }
+        enemies[a].tether_id = enemies[b].id;
+        enemies[b].tether_id = enemies[a].id;
+        Ok(())
+    })
+}
+
+/// Health-model damage entry point: subtract `amount` hit points from the alive enemy at `index`
+/// and write its remaining health to `out_health` (may be null; 0 once killed, `alive` follows
+/// health reaching zero). Goes through the same tether/flinch/wave path as `shoot_enemy_damage`.
+/// `InvalidIndex` for a bad index or an already-dead enemy.
+#[no_mangle]
+pub extern "C" fn apply_damage(
+    enemies_ptr: *mut Enemy,
+    count: i32,
+    index: i32,
+    amount: f32,
+    out_health: *mut f32,
+) -> DeadAimStatus {
+    shoot_enemy_damage(enemies_ptr, count, index, amount, out_health)
+}
+
+/// Deal `damage` to the enemy at `index`. Enemies without a health model die from any hit.
+///
+/// A tethered enemy splits the damage evenly with its partner; whatever one of them cannot
+/// absorb spills over to the other, and the pair only dies once both are at zero health.
+/// A tether whose partner has died (or was re-tethered) is broken on the next hit, and the
+/// survivor takes full damage from then on.
+/// Writes the hit enemy's remaining health to `out_health` (may be null); `InvalidIndex` for a
+/// bad index or an already-dead enemy.
+// Safe C entry point: `enemies_ptr`, `out_health` are null-checked before use;
+// the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn shoot_enemy_damage(
+    enemies_ptr: *mut Enemy,
+    count: i32,
+    index: i32,
+    damage: f32,
+    out_health: *mut f32,
+) -> DeadAimStatus {
+    ffi_guard(|| {
+        let enemies = unsafe { slice_arg_mut(enemies_ptr, count) }?;
+        let i = usize::try_from(index).ok().filter(|&i| i < enemies.len() && enemies[i].is_alive());
+        let health = damage_enemy(enemies, i.ok_or(DeadAimStatus::InvalidIndex)?, damage);
+        unsafe { write_opt(out_health, health) };
+        Ok(())
+    })
+}
+
+// Spawn-protection rectangles; a player standing inside one can't be hurt. Registered worlds
+// keep their own (see `match_state`).
+static SAFE_ZONES: Mutex<Vec<Rect>> = Mutex::new(Vec::new());
+
+fn with_safe_zones<T>(f: impl FnOnce(&mut Vec<Rect>) -> T) -> T {
+    with_part(&SAFE_ZONES, |m| &mut m.safe_zones, f)
+}
+
+/// Replace the spawn-protection zones (a count of 0 clears them). While the player stands inside
+/// a zone, enemies touching it deal no damage during `world_tick`, so spawn camping doesn't work.
+// Safe C entry point: `zones_ptr` is null-checked before use; the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn set_safe_zones(zones_ptr: *const Rect, count: i32) -> DeadAimStatus {
+    ffi_guard(|| {
+        let zones = unsafe { slice_arg(zones_ptr, count) }?;
+        with_safe_zones(|safe| {
+            safe.clear();
+            safe.extend_from_slice(zones);
+        });
+        Ok(())
+    })
+}
+
+/// Writes whether (`x`, `y`) lies in a spawn-protection zone.
+// Safe C entry point: `out_inside` is null-checked before use; the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn in_safe_zone(x: f32, y: f32, out_inside: *mut bool) -> DeadAimStatus {
+    ffi_guard(|| unsafe { write_out(out_inside, safe_at(x, y)) })
+}
+
+/// True when (`x`, `y`) lies in a spawn-protection zone.
+pub(crate) fn safe_at(x: f32, y: f32) -> bool {
+    with_safe_zones(|safe| safe.iter().any(|z| z.contains(x, y)))
+}
+
+/// Shared damage path (tether-aware) for every damage source; `enemies[i]` must be alive.
+/// Kills are reported to the wave tracker and the heatmap. Returns the enemy's remaining health.
+pub(crate) fn damage_enemy(enemies: &mut [Enemy], i: usize, damage: f32) -> f32 {
+    let partner = tether_partner(enemies, i).filter(|&j| enemies[j].is_alive());
+    let health = split_damage(enemies, i, damage);
+    let mut killed = false;
+    for k in [Some(i), partner].into_iter().flatten().filter(|&k| !enemies[k].is_alive()) {
+        enemy_killed(&enemies[k]);
+        killed = true;
+    }
+    if killed {
+        check_wave_cleared(enemies);
+    }
+    health
+}
+
+/// Health bookkeeping behind `damage_enemy`, including the tether split.
+fn split_damage(enemies: &mut [Enemy], i: usize, damage: f32) -> f32 {
+    let damage = damage.max(0.0);
+
+    let partner = tether_partner(enemies, i)
+        .filter(|&j| enemies[i].max_health > 0.0 && enemies[j].max_health > 0.0);
+    let Some(j) = partner else {
+        // untethered, or the partner is gone: the tether breaks and full damage applies
+        enemies[i].tether_id = -1;
+        damage_single(&mut enemies[i], damage);
+        return enemies[i].health;
+    };
+
+    // each takes half; whatever one can't absorb spills over to the other
+    let (hi, hj) = (enemies[i].health, enemies[j].health);
+    let half = damage * 0.5;
+    let (mut di, mut dj) = (half.min(hi), half.min(hj));
+    let mut rest = damage - di - dj;
+    let extra = rest.min(hi - di);
+    di += extra;
+    rest -= extra;
+    dj += rest.min(hj - dj);
+    enemies[i].health = hi - di;
+    enemies[j].health = hj - dj;
+
+    if enemies[i].health <= 0.0 && enemies[j].health <= 0.0 {
+        for k in [i, j] {
+            enemies[k].health = 0.0;
+            enemies[k].set_alive(false);
+            enemies[k].tether_id = -1;
+        }
+    }
+    register_hit(&mut enemies[i]);
+    if dj > 0.0 {
+        register_hit(&mut enemies[j]);
+    }
+    enemies[i].health
+}
+
+/// Linear falloff factor for an AoE centered at (`cx`, `cy`): 1 at the center, 0 at the edge,
+/// `None` outside the radius.
+fn falloff(e: &Enemy, cx: f32, cy: f32, radius: f32) -> Option<f32> {
+    if radius <= 0.0 {
+        return None;
+    }
+    let dist = ((e.x - cx).powi(2) + (e.y - cy).powi(2)).sqrt();
+    (dist <= radius).then(|| 1.0 - dist / radius)
+}
+
+/// Push `e` directly away from (`cx`, `cy`) by `distance` (along +x if it sits on the center).
+fn push_away(e: &mut Enemy, cx: f32, cy: f32, distance: f32) {
+    let (dx, dy) = (e.x - cx, e.y - cy);
+    let len = (dx * dx + dy * dy).sqrt();
+    let (ux, uy) = if len > f32::EPSILON { (dx / len, dy / len) } else { (1.0, 0.0) };
+    e.x += ux * distance;
+    e.y += uy * distance;
+}
+
+/// Which enemies an area effect may touch: with `respect_teams`, the attacker's team is spared.
+#[derive(Clone, Copy)]
+struct TeamFilter {
+    respect_teams: bool,
+    attacker_team: i32,
+}
+
+impl TeamFilter {
+    const ALL: TeamFilter = TeamFilter { respect_teams: false, attacker_team: 0 };
+
+    fn affects(&self, e: &Enemy) -> bool {
+        !self.respect_teams || e.team != self.attacker_team
+    }
+}
+
+/// Indices of alive, affected enemies inside the AoE with their falloff, snapshotted before any
+/// damage is applied (damage can kill tethered partners mid-loop).
+fn aoe_targets(enemies: &[Enemy], cx: f32, cy: f32, radius: f32, teams: TeamFilter) -> Vec<(usize, f32)> {
+    enemies
+        .iter()
+        .enumerate()
+        .filter(|(_, e)| e.is_alive() && teams.affects(e))
+        .filter_map(|(i, e)| falloff(e, cx, cy, radius).map(|f| (i, f)))
+        .collect()
+}
+
+/// Area damage: every alive enemy within `radius` takes `max_damage` scaled linearly from full at
+/// the center to zero at the edge. With `respect_teams` (friendly fire off), enemies on
+/// `attacker_team` are left untouched. Writes the number hit to `out_hit` and the number killed
+/// (including tethered partners) to `out_killed`; either may be null.
+#[no_mangle]
+pub extern "C" fn apply_aoe_damage(
+    center_x: f32,
+    center_y: f32,
+    radius: f32,
+    max_damage: f32,
+    enemies_ptr: *mut Enemy,
+    count: i32,
+    respect_teams: bool,
+    attacker_team: i32,
+    out_hit: *mut i32,
+    out_killed: *mut i32,
+) -> DeadAimStatus {
+    nova_blast(
+        center_x,
+        center_y,
+        radius,
+        max_damage,
+        0.0,
+        enemies_ptr,
+        count,
+        respect_teams,
+        attacker_team,
+        out_hit,
+        out_killed,
+    )
+}
+
+/// Radial knockback: push every alive enemy within `radius` outward by `force` scaled linearly
+/// from full at the center to zero at the edge. Writes the number pushed to `out_pushed` (may be
+/// null).
+// Safe C entry point: `enemies_ptr`, `out_pushed` are null-checked before use;
+// the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn apply_knockback(
+    center_x: f32,
+    center_y: f32,
+    radius: f32,
+    force: f32,
+    enemies_ptr: *mut Enemy,
+    count: i32,
+    out_pushed: *mut i32,
+) -> DeadAimStatus {
+    ffi_guard(|| {
+        let enemies = unsafe { slice_arg_mut(enemies_ptr, count) }?;
+        let targets = aoe_targets(enemies, center_x, center_y, radius, TeamFilter::ALL);
+        for &(i, f) in &targets {
+            push_away(&mut enemies[i], center_x, center_y, force * f);
+        }
+        unsafe { write_opt(out_pushed, targets.len() as i32) };
+        Ok(())
+    })
+}
+
+/// Nova: AoE damage plus knockback with the same linear falloff on both. Every alive enemy within
+/// `radius` takes `max_damage * f` and is pushed outward by `knockback_force * f`, where `f` is 1
+/// at the center and 0 at the edge. With `respect_teams` (friendly fire off), enemies on
+/// `attacker_team` take neither damage nor knockback. Writes the number hit to `out_hit` and the
+/// number killed to `out_killed`; either may be null.
+// Safe C entry point: `enemies_ptr`, `out_hit`, `out_killed` are null-checked before use;
+// the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn nova_blast(
+    center_x: f32,
+    center_y: f32,
+    radius: f32,
+    max_damage: f32,
+    knockback_force: f32,
+    enemies_ptr: *mut Enemy,
+    count: i32,
+    respect_teams: bool,
+    attacker_team: i32,
+    out_hit: *mut i32,
+    out_killed: *mut i32,
+) -> DeadAimStatus {
+    ffi_guard(|| {
+        let enemies = unsafe { slice_arg_mut(enemies_ptr, count) }?;
+        let alive_before = enemies.iter().filter(|e| e.is_alive()).count();
+        let teams = TeamFilter { respect_teams, attacker_team };
+        let targets = aoe_targets(enemies, center_x, center_y, radius, teams);
+        for &(i, f) in &targets {
+            if enemies[i].is_alive() {
+                damage_enemy(enemies, i, max_damage * f);
+            }
+            if knockback_force != 0.0 {
+                push_away(&mut enemies[i], center_x, center_y, knockback_force * f);
+            }
+        }
+        let killed = alive_before - enemies.iter().filter(|e| e.is_alive()).count();
+        unsafe {
+            write_opt(out_hit, targets.len() as i32);
+            write_opt(out_killed, killed as i32);
+        }
+        Ok(())
+    })
+}
+
+/// Densest blast spot among alive enemies accepted by `accept_center`: every alive enemy's
+/// position is a candidate center, scored by how many alive enemies lie within `radius` of it.
+/// Ties keep the earlier candidate. Returns `(x, y, caught)`.
+pub(crate) fn best_aoe_center_where(
+    enemies: &[Enemy],
+    radius: f32,
+    mut accept_center: impl FnMut(f32, f32) -> bool,
+) -> Option<(f32, f32, i32)> {
+    let r2 = radius * radius;
+    let mut best: Option<(f32, f32, i32)> = None;
+    for c in enemies.iter().filter(|e| e.is_alive()) {
+        if !accept_center(c.x, c.y) {
+            continue;
+        }
+        let caught = enemies
+            .iter()
+            .filter(|e| e.is_alive() && (e.x - c.x).powi(2) + (e.y - c.y).powi(2) <= r2)
+            .count() as i32;
+        if best.is_none_or(|(_, _, n)| caught > n) {
+            best = Some((c.x, c.y, caught));
+        }
+    }
+    best
+}
+
+/// AoE auto-aim: the blast center (at an alive enemy's position) that catches the most alive
+/// enemies within `blast_radius`. Writes it to `out_x`/`out_y` and the number caught to
+/// `out_caught`: 0, with nothing else written, when no enemy is alive. `InvalidArgument` for a
+/// negative or NaN radius.
+// Safe C entry point: `enemies_ptr`, `out_x`, `out_y`, `out_caught` are null-checked before use;
+// the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn best_aoe_center(
+    enemies_ptr: *const Enemy,
+    count: i32,
+    blast_radius: f32,
+    out_x: *mut f32,
+    out_y: *mut f32,
+    out_caught: *mut i32,
+) -> DeadAimStatus {
+    ffi_guard(|| {
+        let enemies = unsafe { slice_arg(enemies_ptr, count) }?;
+        if blast_radius.is_nan() || blast_radius < 0.0 {
+            return Err(DeadAimStatus::InvalidArgument);
+        }
+        if out_x.is_null() || out_y.is_null() {
+            return Err(DeadAimStatus::NullPointer);
+        }
+        let Some((x, y, caught)) = best_aoe_center_where(enemies, blast_radius, |_, _| true) else {
+            return unsafe { write_out(out_caught, 0) };
+        };
+        unsafe {
+            out_x.write(x);
+            out_y.write(y);
+            write_out(out_caught, caught)
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::steering::move_enemies_orbit;
+
+    fn squad() -> Vec<Enemy> {
+        (0..4)
+            .map(|i| {
+                let mut e = Enemy::new(i, 0.5 * i as f32, 0.0);
+                e.team = if i % 2 == 0 { 1 } else { 2 };
+                e
+            })
+            .collect()
+    }
+
+    #[test]
+    fn friendly_fire_off_spares_the_attackers_team() {
+        let mut enemies = squad();
+        let before: Vec<_> = enemies.iter().map(|e| (e.x, e.y)).collect();
+        let (mut hit, mut killed) = (0, 0);
+        let status = nova_blast(0.0, 0.0, 10.0, 5.0, 1.0, enemies.as_mut_ptr(), 4, true, 1, &mut hit, &mut killed);
+        assert_eq!(status, DeadAimStatus::Ok);
+        assert_eq!((hit, killed), (2, 2));
+        for (e, &(x, y)) in enemies.iter().zip(&before) {
+            let ally = e.team == 1;
+            assert_eq!(e.is_alive(), ally, "enemy {}", e.id);
+            if ally {
+                assert_eq!((e.health, e.x, e.y), (1.0, x, y), "enemy {}", e.id);
+            } else {
+                assert!(e.x > x, "foe {} was not knocked back", e.id);
+            }
+        }
+    }
+
+    #[test]
+    fn friendly_fire_on_hits_everyone() {
+        let mut enemies = squad();
+        let (mut hit, mut killed) = (0, 0);
+        let status = apply_aoe_damage(0.0, 0.0, 10.0, 5.0, enemies.as_mut_ptr(), 4, false, 1, &mut hit, &mut killed);
+        assert_eq!(status, DeadAimStatus::Ok);
+        assert_eq!((hit, killed), (4, 4));
+        assert!(enemies.iter().all(|e| !e.is_alive()));
+    }
+
+    /// Distance each enemy covers in one orbit tick around the origin at speed 1.
+    fn step_lengths(enemies: &mut [Enemy]) -> Vec<f32> {
+        let before: Vec<_> = enemies.iter().map(|e| (e.x, e.y)).collect();
+        let status = move_enemies_orbit(enemies.as_mut_ptr(), 2, 0.0, 0.0, 10.0, 1.0, true);
+        assert_eq!(status, DeadAimStatus::Ok);
+        enemies.iter().zip(before).map(|(e, (x, y))| ((e.x - x).powi(2) + (e.y - y).powi(2)).sqrt()).collect()
+    }
+
+    #[test]
+    fn hit_enemy_moves_at_half_speed_while_flinching() {
+        let mut enemies = [Enemy::new(0, 10.0, 0.0), Enemy::new(1, -10.0, 0.0)];
+        enemies[0].max_health = 3.0;
+        enemies[0].health = 3.0;
+        let mut health = 0.0;
+        assert_eq!(shoot_enemy_damage(enemies.as_mut_ptr(), 2, 0, 1.0, &mut health), DeadAimStatus::Ok);
+        assert_eq!(health, 2.0);
+        let frames = enemies[0].flinch_frames;
+        assert!(frames > 0);
+
+        for _ in 0..frames {
+            let steps = step_lengths(&mut enemies);
+            assert!((steps[0] - 0.5).abs() < 1e-4 && (steps[1] - 1.0).abs() < 1e-4, "{steps:?}");
+        }
+        assert_eq!(enemies[0].flinch_frames, 0);
+        let steps = step_lengths(&mut enemies);
+        assert!((steps[0] - 1.0).abs() < 1e-4, "{steps:?}");
+    }
+}
@@ -0,0 +1,75 @@
+// src/enemy_types.rs
+// Archetype table (grunt, elite, boss, ...) keyed by `Enemy::type_id`.
+use crate::error::{ffi_guard, write_out, DeadAimStatus};
+use crate::Enemy;
+use std::sync::Mutex;
+
+/// Per-archetype stats, registered with `register_enemy_type`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct EnemyType {
+    /// Multiplies every mover's speed for enemies of this type.
+    pub speed_multiplier: f32,
+    /// Starting (and maximum) health; <= 0 means no health model (dies from any hit).
+    pub max_health: f32,
+    /// Hitbox radius for shots, projectiles and enemy collision; <= 0 uses `set_enemy_hit_radius`'s value.
+    pub hit_radius: f32,
+    /// Score awarded for a kill (see `enemy_type_score_value`).
+    pub score_value: i32,
+    /// Scales token payouts for killing this type.
+    pub reward_multiplier: f32,
+}
+
+// Indexed by type id; unregistered ids are `None`.
+static ENEMY_TYPES: Mutex<Vec<Option<EnemyType>>> = Mutex::new(Vec::new());
+
+/// Register (or replace) the stats for `type_id`; null unregisters it. Enemies whose type is
+/// unregistered behave like untyped ones. `InvalidArgument` for a negative id.
+// Safe C entry point: `stats` is null-checked before use; the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn register_enemy_type(type_id: i32, stats: *const EnemyType) -> DeadAimStatus {
+    ffi_guard(|| {
+        let index = usize::try_from(type_id).map_err(|_| DeadAimStatus::InvalidArgument)?;
+        let stats = unsafe { stats.as_ref() }.copied();
+        let mut types = ENEMY_TYPES.lock().unwrap_or_else(|e| e.into_inner());
+        if types.len() <= index {
+            types.resize(index + 1, None);
+        }
+        types[index] = stats;
+        Ok(())
+    })
+}
+
+/// The registered stats for `type_id`, if any.
+pub(crate) fn enemy_type(type_id: i32) -> Option<EnemyType> {
+    let types = ENEMY_TYPES.lock().unwrap_or_else(|e| e.into_inner());
+    usize::try_from(type_id).ok().and_then(|i| types.get(i).copied().flatten())
+}
+
+/// `Enemy::new` of the given type, starting at the type's full health.
+pub(crate) fn new_typed(id: i32, x: f32, y: f32, type_id: i32) -> Enemy {
+    let mut e = Enemy::new(id, x, y);
+    e.type_id = type_id;
+    if let Some(t) = enemy_type(type_id).filter(|t| t.max_health > 0.0) {
+        e.health = t.max_health;
+        e.max_health = t.max_health;
+    }
+    e
+}
+
+/// `enemy_init` for an enemy of `type_id`, taking its health from the registered type.
+// Safe C entry point: `enemy` is null-checked before use; the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn enemy_init_typed(enemy: *mut Enemy, id: i32, x: f32, y: f32, type_id: i32) -> DeadAimStatus {
+    ffi_guard(|| unsafe { write_out(enemy, new_typed(id, x, y, type_id)) })
+}
+
+/// Writes the score value of a kill of `type_id`; 0 for unregistered types.
+// Safe C entry point: `out_value` is null-checked before use; the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn enemy_type_score_value(type_id: i32, out_value: *mut i32) -> DeadAimStatus {
+    ffi_guard(|| unsafe { write_out(out_value, enemy_type(type_id).map_or(0, |t| t.score_value)) })
+}
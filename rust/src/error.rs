@@ -0,0 +1,184 @@
+// src/error.rs
+// Status codes returned by every FFI entry point (actual results go through trailing `out_*`
+// parameters, which must be non-null unless documented "may be null"), and panic containment:
+// every `extern "C"` body runs inside `ffi_guard`, so a panic never unwinds into the host. A
+// query that finds nothing still succeeds and writes its documented "none" value (-1 for an
+// index), so `Ok` always means the outputs were written.
+use std::cell::RefCell;
+use std::ffi::{c_char, CString};
+use std::panic::{self, AssertUnwindSafe};
+use std::slice;
+
+/// Result of an FFI call (0 = success). C/C++ hosts can log failures with
+/// `deadaim_status_message`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DeadAimStatus {
+    Ok,
+    /// A required pointer was null (or a buffer pointer was null with a non-zero length).
+    NullPointer,
+    /// An enemy, wave or other index was negative or out of range, or names a slot that can't
+    /// take the call (e.g. an enemy that is already dead).
+    InvalidIndex,
+    /// A count, speed or other value was out of range (negative, NaN, ...).
+    InvalidArgument,
+    /// A string argument was not valid UTF-8.
+    Utf8Error,
+    /// The call needs state that isn't set up yet (e.g. a running match).
+    NotInitialized,
+    /// The library panicked; see `last_error_message`.
+    Panicked,
+    /// The wallet hit the reward rate limit (see `set_reward_rate_limit`).
+    RateLimited,
+    /// The kill validator refused the reward (see `set_kill_validator`).
+    Rejected,
+    /// An output buffer is too small; size it with the matching `*_size` query.
+    BufferTooSmall,
+    /// Input bytes are malformed, truncated or from an incompatible format version.
+    InvalidData,
+    /// No registered world has this id (see `create_world`).
+    UnknownId,
+}
+
+/// Shorthand for the body of a status-returning FFI function.
+pub(crate) type FfiResult<T = ()> = Result<T, DeadAimStatus>;
+
+impl DeadAimStatus {
+    fn message(self) -> &'static [u8] {
+        match self {
+            DeadAimStatus::Ok => b"ok\0",
+            DeadAimStatus::NullPointer => b"null pointer\0",
+            DeadAimStatus::InvalidIndex => b"invalid index\0",
+            DeadAimStatus::InvalidArgument => b"invalid argument\0",
+            DeadAimStatus::Utf8Error => b"string is not valid utf-8\0",
+            DeadAimStatus::NotInitialized => b"not initialized\0",
+            DeadAimStatus::Panicked => b"internal panic\0",
+            DeadAimStatus::RateLimited => b"rate limited\0",
+            DeadAimStatus::Rejected => b"rejected by validator\0",
+            DeadAimStatus::BufferTooSmall => b"buffer too small\0",
+            DeadAimStatus::InvalidData => b"malformed input data\0",
+            DeadAimStatus::UnknownId => b"unknown world id\0",
+        }
+    }
+}
+
+/// Human-readable, NUL-terminated description of `status` for logs. The string is static; do
+/// not free it.
+#[no_mangle]
+pub extern "C" fn deadaim_status_message(status: DeadAimStatus) -> *const c_char {
+    status.message().as_ptr().cast()
+}
+
+thread_local! {
+    // Message of the last panic caught on this thread.
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Run an FFI body, turning `Err(status)` into `status` and a panic into `Panicked` plus a
+/// message for `last_error_message`. Shared state is behind poison-tolerant locks, so carrying
+/// on after a caught panic is sound.
+pub(crate) fn ffi_guard(body: impl FnOnce() -> FfiResult) -> DeadAimStatus {
+    match panic::catch_unwind(AssertUnwindSafe(body)) {
+        Ok(Ok(())) => DeadAimStatus::Ok,
+        Ok(Err(status)) => status,
+        Err(payload) => {
+            let message = payload
+                .downcast_ref::<&str>()
+                .map(|s| s.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "unknown panic".to_string());
+            let message = CString::new(message.replace('\0', " ")).unwrap_or_default();
+            LAST_ERROR.with(|slot| *slot.borrow_mut() = Some(message));
+            DeadAimStatus::Panicked
+        }
+    }
+}
+
+/// `Ok` as a result, so a nested FFI call can be propagated with `?`.
+pub(crate) fn check(status: DeadAimStatus) -> FfiResult {
+    match status {
+        DeadAimStatus::Ok => Ok(()),
+        status => Err(status),
+    }
+}
+
+/// Borrow a required pointer argument; `NullPointer` if it is null.
+///
+/// # Safety
+/// A non-null `ptr` must point to a valid `T` for the returned lifetime.
+pub(crate) unsafe fn arg_ref<'a, T>(ptr: *const T) -> FfiResult<&'a T> {
+    ptr.as_ref().ok_or(DeadAimStatus::NullPointer)
+}
+
+/// Mutable counterpart of `arg_ref`.
+///
+/// # Safety
+/// A non-null `ptr` must point to a valid `T` not aliased elsewhere for the returned lifetime.
+pub(crate) unsafe fn arg_mut<'a, T>(ptr: *mut T) -> FfiResult<&'a mut T> {
+    ptr.as_mut().ok_or(DeadAimStatus::NullPointer)
+}
+
+/// Store `value` through a required out-parameter (which may be uninitialized); `NullPointer`
+/// if it is null.
+///
+/// # Safety
+/// A non-null `out` must be valid for a write of `T`.
+pub(crate) unsafe fn write_out<T>(out: *mut T, value: T) -> FfiResult {
+    if out.is_null() {
+        return Err(DeadAimStatus::NullPointer);
+    }
+    out.write(value);
+    Ok(())
+}
+
+/// Store `value` through an out-parameter that may be null (then nothing is written).
+///
+/// # Safety
+/// A non-null `out` must be valid for a write of `T`.
+pub(crate) unsafe fn write_opt<T>(out: *mut T, value: T) {
+    if !out.is_null() {
+        out.write(value);
+    }
+}
+
+/// Borrow a C array of `len` elements: empty for a zero length (the pointer may then be null),
+/// `NullPointer` for a null pointer with a non-zero length, `InvalidArgument` for a negative
+/// length.
+///
+/// # Safety
+/// A non-null `ptr` must point to at least `len` valid, initialized elements.
+pub(crate) unsafe fn slice_arg<'a, T>(ptr: *const T, len: impl TryInto<usize>) -> FfiResult<&'a [T]> {
+    let len = len.try_into().map_err(|_| DeadAimStatus::InvalidArgument)?;
+    match (ptr.is_null(), len) {
+        (_, 0) => Ok(&[]),
+        (true, _) => Err(DeadAimStatus::NullPointer),
+        (false, len) => Ok(slice::from_raw_parts(ptr, len)),
+    }
+}
+
+/// Mutable counterpart of `slice_arg`.
+///
+/// # Safety
+/// A non-null `ptr` must point to at least `len` valid elements not aliased elsewhere.
+pub(crate) unsafe fn slice_arg_mut<'a, T>(ptr: *mut T, len: impl TryInto<usize>) -> FfiResult<&'a mut [T]> {
+    let len = len.try_into().map_err(|_| DeadAimStatus::InvalidArgument)?;
+    match (ptr.is_null(), len) {
+        (_, 0) => Ok(&mut []),
+        (true, _) => Err(DeadAimStatus::NullPointer),
+        (false, len) => Ok(slice::from_raw_parts_mut(ptr, len)),
+    }
+}
+
+/// NUL-terminated message of the last panic caught on the calling thread, or null if there has
+/// been none since `clear_last_error`. Owned by the library; valid until the next caught panic
+/// or `clear_last_error` on this thread.
+#[no_mangle]
+pub extern "C" fn last_error_message() -> *const c_char {
+    LAST_ERROR.with(|slot| slot.borrow().as_ref().map_or(std::ptr::null(), |m| m.as_ptr()))
+}
+
+/// Forget the calling thread's last panic message.
+#[no_mangle]
+pub extern "C" fn clear_last_error() {
+    LAST_ERROR.with(|slot| *slot.borrow_mut() = None);
+}
@@ -0,0 +1,126 @@
+// src/events.rs
+// Ring buffer of simulation events the host drains once per frame instead of polling state.
+// Registered worlds keep their own queue (see `match_state`).
+use crate::callbacks::{notify_kill, notify_reward};
+use crate::error::{ffi_guard, slice_arg_mut, write_out, DeadAimStatus};
+use crate::heatmap::record_kill;
+use crate::match_state::with_part;
+use crate::Enemy;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+// Events held before the oldest start being dropped.
+const EVENT_CAPACITY: usize = 1024;
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GameEventKind {
+    /// An enemy died (any kill path); `x`/`y` is where.
+    EnemyKilled,
+    /// An enemy was spawned: added to a world, raised by a wave or summoner, or respawned.
+    EnemySpawned,
+    /// The world player took `value` damage at `x`/`y`.
+    PlayerDamaged,
+    /// A payout of `amount` was dispatched to the wallet hook (`enemy_id` -1 if not for a kill).
+    RewardQueued,
+}
+
+/// One event from `drain_events`; fields a kind doesn't use are 0 (`enemy_id` -1).
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct GameEvent {
+    pub kind: GameEventKind,
+    pub enemy_id: i32,
+    pub x: f32,
+    pub y: f32,
+    pub value: f32,
+    pub amount: u64,
+}
+
+#[derive(Clone, Default)]
+pub(crate) struct EventQueue {
+    events: VecDeque<GameEvent>,
+    dropped: u64,
+}
+
+static EVENTS: Mutex<EventQueue> = Mutex::new(EventQueue { events: VecDeque::new(), dropped: 0 });
+
+/// Run `f` on the installed world's queue, else the crate-wide one.
+fn with_queue<T>(f: impl FnOnce(&mut EventQueue) -> T) -> T {
+    with_part(&EVENTS, |m| &mut m.events, f)
+}
+
+fn push(kind: GameEventKind, enemy_id: i32, x: f32, y: f32, value: f32, amount: u64) {
+    with_queue(|queue| {
+        if queue.events.len() == EVENT_CAPACITY {
+            queue.events.pop_front();
+            queue.dropped += 1;
+        }
+        queue.events.push_back(GameEvent { kind, enemy_id, x, y, value, amount });
+    })
+}
+
+/// Report a kill: queues `EnemyKilled`, feeds the heatmap and fires the kill callbacks. Every
+/// kill path calls this.
+pub(crate) fn enemy_killed(e: &Enemy) {
+    record_kill(e.x, e.y);
+    push(GameEventKind::EnemyKilled, e.id, e.x, e.y, 0.0, 0);
+    notify_kill(e);
+}
+
+pub(crate) fn enemy_spawned(e: &Enemy) {
+    push(GameEventKind::EnemySpawned, e.id, e.x, e.y, 0.0, 0);
+}
+
+pub(crate) fn player_damaged(x: f32, y: f32, damage: f32) {
+    push(GameEventKind::PlayerDamaged, -1, x, y, damage, 0);
+}
+
+pub(crate) fn reward_queued(enemy_id: i32, amount: u64) {
+    push(GameEventKind::RewardQueued, enemy_id, 0.0, 0.0, 0.0, amount);
+    notify_reward(enemy_id, amount);
+}
+
+/// Move up to `cap` queued events, oldest first, into `out_ptr` and write how many were moved to
+/// `out_written`; the rest stay queued for the next call. The queue holds the latest 1024 events.
+// Safe C entry point: `out_ptr`, `out_written` are null-checked before use; the rest of the contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn drain_events(out_ptr: *mut GameEvent, cap: i32, out_written: *mut i32) -> DeadAimStatus {
+    ffi_guard(|| {
+        let out = unsafe { slice_arg_mut(out_ptr, cap) }?;
+        if out_written.is_null() {
+            return Err(DeadAimStatus::NullPointer);
+        }
+        let n = with_queue(|queue| {
+            let n = queue.events.len().min(out.len());
+            for (o, event) in out.iter_mut().zip(queue.events.drain(..n)) {
+                *o = event;
+            }
+            n
+        });
+        unsafe { write_out(out_written, n as i32) }
+    })
+}
+
+/// Writes the number of events waiting to be drained.
+// Safe C entry point: `out_count` is null-checked before use; the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn pending_event_count(out_count: *mut i32) -> DeadAimStatus {
+    ffi_guard(|| unsafe { write_out(out_count, with_queue(|queue| queue.events.len() as i32)) })
+}
+
+/// Writes the events discarded because the queue was full since the last call (the counter
+/// resets).
+// Safe C entry point: `out_dropped` is null-checked before use; the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn take_dropped_event_count(out_dropped: *mut u64) -> DeadAimStatus {
+    ffi_guard(|| {
+        if out_dropped.is_null() {
+            return Err(DeadAimStatus::NullPointer);
+        }
+        unsafe { write_out(out_dropped, with_queue(|queue| std::mem::take(&mut queue.dropped))) }
+    })
+}
@@ -0,0 +1,102 @@
+// src/heatmap.rs
+// Kill and player-death locations, binned on demand into a grid for level-design heatmaps.
+// Registered worlds keep their own samples (see `match_state`).
+use crate::error::{ffi_guard, slice_arg_mut, write_out, DeadAimStatus};
+use crate::match_state::with_part;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Samples {
+    kills: Vec<(f32, f32)>,
+    deaths: Vec<(f32, f32)>,
+}
+
+static SAMPLES: Mutex<Samples> = Mutex::new(Samples { kills: Vec::new(), deaths: Vec::new() });
+
+/// Run `f` on the installed world's samples, else the crate-wide ones.
+fn with_samples<T>(f: impl FnOnce(&mut Samples) -> T) -> T {
+    with_part(&SAMPLES, |m| &mut m.heatmap, f)
+}
+
+/// One non-empty heatmap cell from `get_heatmap`: it covers
+/// [`cell_x * cell_size`, `(cell_x + 1) * cell_size`) on each axis.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct HeatmapCell {
+    pub cell_x: i32,
+    pub cell_y: i32,
+    pub kills: i32,
+    pub player_deaths: i32,
+}
+
+/// Note an enemy killed at (`x`, `y`); fed by `events::enemy_killed`.
+pub(crate) fn record_kill(x: f32, y: f32) {
+    if x.is_finite() && y.is_finite() {
+        with_samples(|samples| samples.kills.push((x, y)));
+    }
+}
+
+/// Note a player death at (`x`, `y`) (world players are recorded automatically).
+/// `InvalidArgument` for a non-finite position.
+#[no_mangle]
+pub extern "C" fn heatmap_record_player_death(x: f32, y: f32) -> DeadAimStatus {
+    ffi_guard(|| {
+        if !x.is_finite() || !y.is_finite() {
+            return Err(DeadAimStatus::InvalidArgument);
+        }
+        record_player_death(x, y);
+        Ok(())
+    })
+}
+
+/// Note a player death at (`x`, `y`), skipping non-finite positions.
+pub(crate) fn record_player_death(x: f32, y: f32) {
+    if x.is_finite() && y.is_finite() {
+        with_samples(|samples| samples.deaths.push((x, y)));
+    }
+}
+
+/// Bin every recorded kill and player death into square cells of `cell_size` and write the
+/// non-empty cells, ordered by row (`cell_y`) then column, to `out_buffer` (up to `out_len`),
+/// and the total number of non-empty cells to `out_total`. `InvalidArgument` for a non-positive
+/// cell size.
+// Safe C entry point: `out_buffer`, `out_total` are null-checked before use; the rest of the contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn get_heatmap(
+    cell_size: f32,
+    out_buffer: *mut HeatmapCell,
+    out_len: i32,
+    out_total: *mut i32,
+) -> DeadAimStatus {
+    ffi_guard(|| {
+        let out = unsafe { slice_arg_mut(out_buffer, out_len) }?;
+        if !(cell_size.is_finite() && cell_size > 0.0) {
+            return Err(DeadAimStatus::InvalidArgument);
+        }
+        let cell_of = |&(x, y): &(f32, f32)| ((y / cell_size).floor() as i32, (x / cell_size).floor() as i32);
+        let mut cells: BTreeMap<(i32, i32), (i32, i32)> = BTreeMap::new();
+        with_samples(|samples| {
+            for p in &samples.kills {
+                cells.entry(cell_of(p)).or_default().0 += 1;
+            }
+            for p in &samples.deaths {
+                cells.entry(cell_of(p)).or_default().1 += 1;
+            }
+        });
+        for (o, (&(cell_y, cell_x), &(kills, player_deaths))) in out.iter_mut().zip(&cells) {
+            *o = HeatmapCell { cell_x, cell_y, kills, player_deaths };
+        }
+        unsafe { write_out(out_total, cells.len() as i32) }
+    })
+}
+
+/// Forget every recorded kill and death.
+#[no_mangle]
+pub extern "C" fn clear_heatmap() -> DeadAimStatus {
+    ffi_guard(|| {
+        with_samples(|samples| *samples = Samples::default());
+        Ok(())
+    })
+}
@@ -1,21 +1,220 @@
 // src/lib.rs
-use rand::Rng;
+
+use crate::error::{ffi_guard, slice_arg, slice_arg_mut, write_opt, write_out, DeadAimStatus};
 use std::f32;
-use std::slice;
+use std::sync::atomic::{AtomicU32, Ordering};
 
+#[cfg(target_arch = "wasm32")]
 use wasm_bindgen::prelude::*;
 
+pub mod ai;
+pub mod aim_assist;
+pub mod arena;
+pub mod ballistics;
+pub mod behavior_tree;
+pub mod callbacks;
+pub mod collision;
+pub mod combat;
+pub mod enemy_types;
+pub mod error;
+pub mod events;
+pub mod heatmap;
+pub mod match_state;
+pub mod obstacles;
+pub mod pathfinding;
+pub mod player;
+pub mod projectiles;
+pub mod registry;
+pub mod replay;
+pub mod rewards;
+pub mod rng;
+pub mod scoring;
+pub mod serialize;
+pub mod session;
+pub mod spatial;
+pub mod spawner;
+pub mod stats;
+pub mod status;
+pub mod steering;
+pub mod targeting;
+pub mod weapons;
+pub mod world;
+
 // When compiled to wasm, enable console logging if you want
 #[cfg(feature = "wasm")]
 extern crate console_error_panic_hook;
 
+/// Version of the C ABI: exported struct layouts and function signatures. Bumped on any
+/// incompatible change; hosts compare it (and `sizeof_enemy`) against what they were built for.
+pub const ABI_VERSION: u32 = 2;
+
 #[repr(C)]
 #[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(default))]
 pub struct Enemy {
     pub id: i32,
     pub x: f32,
     pub y: f32,
-    pub alive: bool,
+    /// 1 while alive, 0 once dead (any non-zero value counts as alive). A `u8` rather than a
+    /// `bool` so its size doesn't depend on the host compiler; use `is_alive`/`set_alive`.
+    /// Serialized as a boolean.
+    #[cfg_attr(feature = "serde", serde(with = "alive_as_bool"))]
+    pub alive: u8,
+    /// Seconds until this enemy may dodge again (0 = ready).
+    pub dodge_cooldown: f32,
+    /// Remaining frames this enemy is stunned/frozen and cannot move.
+    pub stun_frames: i32,
+    /// Where this enemy last saw the player (seek target); starts at its own spawn position.
+    pub last_seen_x: f32,
+    pub last_seen_y: f32,
+    /// Velocity in world units per movement tick; written by the movers (or the host).
+    pub vx: f32,
+    pub vy: f32,
+    /// Hit points (the enemy dies when they reach 0); `max_health` <= 0 means the enemy has no
+    /// health model and dies from any hit.
+    pub health: f32,
+    pub max_health: f32,
+    /// Id of the enemy this one shares damage with, or -1 when untethered.
+    pub tether_id: i32,
+    /// Seconds until this summoner spawns minions again; negative for non-summoners.
+    pub summon_cooldown: f32,
+    /// Faction id; AoE with friendly fire off spares enemies on the attacker's team.
+    pub team: i32,
+    /// Remaining frames of hit stagger; movers run at half speed while positive.
+    pub flinch_frames: i32,
+    /// Wave this enemy belongs to (see `start_wave`), or -1 for none.
+    pub wave_id: i32,
+    /// Health regained per `tick_regen` once out of combat (0 = no regeneration).
+    pub regen_rate: f32,
+    /// Regen ticks since this enemy last took damage.
+    pub frames_since_hit: i32,
+    /// Point a taunt forces this enemy to pursue while `taunt_frames` > 0.
+    pub taunt_x: f32,
+    pub taunt_y: f32,
+    /// Remaining seek ticks this enemy is taunted.
+    pub taunt_frames: i32,
+    /// Archetype index (grunt, elite, boss, ...); 0 by default.
+    pub type_id: i32,
+}
+
+impl Enemy {
+    /// A freshly spawned, alive enemy with all AI state at rest.
+    pub fn new(id: i32, x: f32, y: f32) -> Self {
+        Enemy {
+            id,
+            x,
+            y,
+            alive: 1,
+            dodge_cooldown: 0.0,
+            stun_frames: 0,
+            last_seen_x: x,
+            last_seen_y: y,
+            vx: 0.0,
+            vy: 0.0,
+            health: 1.0,
+            max_health: 1.0,
+            tether_id: -1,
+            summon_cooldown: -1.0,
+            team: 0,
+            flinch_frames: 0,
+            wave_id: -1,
+            regen_rate: 0.0,
+            frames_since_hit: 0,
+            taunt_x: x,
+            taunt_y: y,
+            taunt_frames: 0,
+            type_id: 0,
+        }
+    }
+
+    pub fn is_alive(&self) -> bool {
+        self.alive != 0
+    }
+
+    pub fn set_alive(&mut self, alive: bool) {
+        self.alive = alive as u8;
+    }
+}
+
+/// An alive enemy with id 0 at the origin; fills in fields missing from deserialized enemies.
+impl Default for Enemy {
+    fn default() -> Self {
+        Enemy::new(0, 0.0, 0.0)
+    }
+}
+
+#[cfg(feature = "serde")]
+mod alive_as_bool {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(alive: &u8, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_bool(*alive != 0)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<u8, D::Error> {
+        bool::deserialize(d).map(u8::from)
+    }
+}
+
+/// Writes `ABI_VERSION` of this build to `out_version`; hosts should refuse to run on a mismatch.
+// Safe C entry point: `out_version` is null-checked before use; the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn abi_version(out_version: *mut u32) -> DeadAimStatus {
+    ffi_guard(|| unsafe { write_out(out_version, ABI_VERSION) })
+}
+
+/// Writes the size in bytes of `Enemy` in this build to `out_size`, for hosts to check against
+/// their own mirror of it.
+// Safe C entry point: `out_size` is null-checked before use; the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn sizeof_enemy(out_size: *mut usize) -> DeadAimStatus {
+    ffi_guard(|| unsafe { write_out(out_size, std::mem::size_of::<Enemy>()) })
+}
+
+/// Initialize an enemy slot with `Enemy::new` defaults so hosts don't have to track every field.
+// Safe C entry point: `enemy` is null-checked before use; the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn enemy_init(enemy: *mut Enemy, id: i32, x: f32, y: f32) -> DeadAimStatus {
+    ffi_guard(|| unsafe { write_out(enemy, Enemy::new(id, x, y)) })
+}
+
+/// Allocate an array of `count` enemies with Rust's layout and alignment, each initialized by
+/// `Enemy::new` at the origin with its index as id, so hosts needn't size `Enemy` themselves.
+/// Writes the array to `out_enemies`; `InvalidArgument` for a non-positive count. Free with
+/// `enemies_free` and the same count.
+// Safe C entry point: `out_enemies` is null-checked before use; the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn enemies_alloc(count: i32, out_enemies: *mut *mut Enemy) -> DeadAimStatus {
+    ffi_guard(|| {
+        if out_enemies.is_null() {
+            return Err(DeadAimStatus::NullPointer);
+        }
+        if count <= 0 {
+            return Err(DeadAimStatus::InvalidArgument);
+        }
+        let enemies: Box<[Enemy]> = (0..count).map(|i| Enemy::new(i, 0.0, 0.0)).collect();
+        unsafe { write_out(out_enemies, Box::into_raw(enemies).cast::<Enemy>()) }
+    })
+}
+
+/// Free an array from `enemies_alloc`; `count` must be the count it was allocated with. Null is
+/// ignored; `InvalidArgument` for a non-positive count with a non-null pointer.
+#[no_mangle]
+pub extern "C" fn enemies_free(enemies_ptr: *mut Enemy, count: i32) -> DeadAimStatus {
+    ffi_guard(|| {
+        if enemies_ptr.is_null() {
+            return Ok(());
+        }
+        if count <= 0 {
+            return Err(DeadAimStatus::InvalidArgument);
+        }
+        drop(unsafe { Box::from_raw(std::ptr::slice_from_raw_parts_mut(enemies_ptr, count as usize)) });
+        Ok(())
+    })
 }
 
 // ---------- WASM / JS interop hooks (frontend must provide these) ----------
@@ -71,26 +270,40 @@ pub fn init() {
 // ---------- Core functions exposed to C++ (via pointer interfaces) ----------
 // Note: C++ expects pointers to Enemy; we accept *const Enemy / *mut Enemy and count.
 
-/// Find nearest alive enemy; returns index (0-based) or -1 if none.
-/// Safe C ABI wrapper compatible with C++ (use with raw pointers).
+/// Find nearest alive enemy; writes its index (0-based), or -1 if none, to `out_index`.
+/// Safe C ABI wrapper compatible with C++ (use with raw pointers). Like every export taking an
+/// enemy array, `NullPointer` for a null array with a non-zero count and `InvalidArgument` for a
+/// negative count.
 #[no_mangle]
 pub extern "C" fn find_nearest_enemy(
     player_x: f32,
     player_y: f32,
     enemies_ptr: *const Enemy,
     count: i32,
-) -> i32 {
-    // Safety: caller must ensure pointer + count is valid
-    if enemies_ptr.is_null() || count <= 0 {
-        return -1;
-    }
-    let enemies = unsafe { slice::from_raw_parts(enemies_ptr, count as usize) };
+    out_index: *mut i32,
+) -> DeadAimStatus {
+    let kind = DistanceKind::Euclidean;
+    find_nearest_enemy_with(player_x, player_y, enemies_ptr, count, kind, std::ptr::null(), out_index)
+}
 
+
+/// Index of the nearest alive enemy in `enemies`, or -1 if none are alive.
+pub(crate) fn nearest_alive_index(player_x: f32, player_y: f32, enemies: &[Enemy]) -> i32 {
+    nearest_by_kind(player_x, player_y, enemies, DistanceKind::Euclidean, DistanceParams { wx: 1.0, wy: 1.0 })
+}
+
+/// Index of the nearest alive enemy for which `accept(index, enemy)` holds, or -1.
+pub(crate) fn nearest_matching(
+    player_x: f32,
+    player_y: f32,
+    enemies: &[Enemy],
+    mut accept: impl FnMut(usize, &Enemy) -> bool,
+) -> i32 {
     let mut nearest_index: i32 = -1;
     let mut min_dist2: f32 = f32::MAX;
 
     for (i, e) in enemies.iter().enumerate() {
-        if !e.alive {
+        if !e.is_alive() || !accept(i, e) {
             continue;
         }
         let dx = player_x - e.x;
@@ -105,39 +318,512 @@ pub extern "C" fn find_nearest_enemy(
     nearest_index
 }
 
-/// Shoot enemy at index => mark alive = false
+/// Indices of the up-to-`k` nearest alive enemies accepted by `accept`, nearest first
+/// (ties keep array order).
+pub(crate) fn k_nearest_matching(
+    player_x: f32,
+    player_y: f32,
+    enemies: &[Enemy],
+    k: usize,
+    mut accept: impl FnMut(usize, &Enemy) -> bool,
+) -> Vec<usize> {
+    let mut candidates: Vec<(f32, usize)> = enemies
+        .iter()
+        .enumerate()
+        .filter(|&(i, e)| e.is_alive() && accept(i, e))
+        .map(|(i, e)| ((player_x - e.x).powi(2) + (player_y - e.y).powi(2), i))
+        .collect();
+    candidates.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)));
+    candidates.into_iter().take(k).map(|(_, i)| i).collect()
+}
+
+/// Batch nearest query: for each of `point_count` query points, write the index of the nearest
+/// alive enemy (or -1) to `out_indices[i]`. Amortizes FFI overhead for crowd AI.
+// Safe C entry point: `points_x`, `points_y`, `enemies_ptr`, `out_indices` are null-checked before use;
+// the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
 #[no_mangle]
-pub extern "C" fn shoot_enemy(index: i32, enemies_ptr: *mut Enemy) {
-    if enemies_ptr.is_null() || index < 0 {
-        return;
-    }
-    unsafe {
-        let e_ptr = enemies_ptr.offset(index as isize);
-        (*e_ptr).alive = false;
+pub extern "C" fn find_nearest_batch(
+    points_x: *const f32,
+    points_y: *const f32,
+    point_count: i32,
+    enemies_ptr: *const Enemy,
+    enemy_count: i32,
+    out_indices: *mut i32,
+) -> DeadAimStatus {
+    ffi_guard(|| {
+        let xs = unsafe { slice_arg(points_x, point_count) }?;
+        let ys = unsafe { slice_arg(points_y, point_count) }?;
+        let out = unsafe { slice_arg_mut(out_indices, point_count) }?;
+        let enemies = unsafe { slice_arg(enemies_ptr, enemy_count) }?;
+        for ((x, y), o) in xs.iter().zip(ys).zip(out.iter_mut()) {
+            *o = nearest_alive_index(*x, *y, enemies);
+        }
+        Ok(())
+    })
+}
+
+/// Multi-target query (chain lightning, shotgun prioritization): write the indices of up to `k`
+/// nearest alive enemies, nearest first (ties keep array order), into `out_indices` (capacity
+/// `out_len`), and the number written to `out_found`. `InvalidArgument` for a negative `k`.
+// Safe C entry point: `enemies_ptr`, `out_indices`, `out_found` are null-checked before use;
+// the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn find_k_nearest_enemies(
+    player_x: f32,
+    player_y: f32,
+    enemies_ptr: *const Enemy,
+    count: i32,
+    k: i32,
+    out_indices: *mut i32,
+    out_len: i32,
+    out_found: *mut i32,
+) -> DeadAimStatus {
+    ffi_guard(|| {
+        let enemies = unsafe { slice_arg(enemies_ptr, count) }?;
+        let out = unsafe { slice_arg_mut(out_indices, out_len) }?;
+        let k = usize::try_from(k).map_err(|_| DeadAimStatus::InvalidArgument)?.min(out.len());
+        let nearest = k_nearest_matching(player_x, player_y, enemies, k, |_, _| true);
+        for (o, &i) in out.iter_mut().zip(&nearest) {
+            *o = i as i32;
+        }
+        unsafe { write_out(out_found, nearest.len() as i32) }
+    })
+}
+
+/// Objective defense: the alive enemy nearest the goal point (e.g. the bomb site or escort
+/// target), i.e. the one closest to completing the objective. Writes its index, or -1.
+#[no_mangle]
+pub extern "C" fn find_enemy_nearest_goal(
+    goal_x: f32,
+    goal_y: f32,
+    enemies_ptr: *const Enemy,
+    count: i32,
+    out_index: *mut i32,
+) -> DeadAimStatus {
+    find_nearest_enemy(goal_x, goal_y, enemies_ptr, count, out_index)
+}
+
+/// Sniper targeting: the alive enemy farthest from the player; ties keep the lower index.
+/// Writes its index, or -1 if none are alive.
+#[no_mangle]
+pub extern "C" fn find_farthest_enemy(
+    player_x: f32,
+    player_y: f32,
+    enemies_ptr: *const Enemy,
+    count: i32,
+    out_index: *mut i32,
+) -> DeadAimStatus {
+    find_farthest_enemy_within(player_x, player_y, enemies_ptr, count, f32::INFINITY, out_index)
+}
+
+/// `find_farthest_enemy` capped at `max_radius` (inclusive): the farthest alive enemy still in
+/// weapon range. Writes its index, or -1; `InvalidArgument` for a negative or NaN radius.
+// Safe C entry point: `enemies_ptr`, `out_index` are null-checked before use; the rest of the contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn find_farthest_enemy_within(
+    player_x: f32,
+    player_y: f32,
+    enemies_ptr: *const Enemy,
+    count: i32,
+    max_radius: f32,
+    out_index: *mut i32,
+) -> DeadAimStatus {
+    ffi_guard(|| {
+        let enemies = unsafe { slice_arg(enemies_ptr, count) }?;
+        if max_radius.is_nan() || max_radius < 0.0 {
+            return Err(DeadAimStatus::InvalidArgument);
+        }
+        let max_dist2 = max_radius * max_radius;
+        let mut farthest_index: i32 = -1;
+        let mut best_dist2 = -1.0f32;
+        for (i, e) in enemies.iter().enumerate().filter(|(_, e)| e.is_alive()) {
+            let dist2 = (e.x - player_x).powi(2) + (e.y - player_y).powi(2);
+            if dist2 <= max_dist2 && dist2 > best_dist2 {
+                best_dist2 = dist2;
+                farthest_index = i as i32;
+            }
+        }
+        unsafe { write_out(out_index, farthest_index) }
+    })
+}
+
+/// Debounced nearest: keep `current_index` (if it is still alive) unless another alive enemy
+/// is closer by at least `grace_dist` world units of actual distance. With no valid current
+/// target this is plain `find_nearest_enemy`. Writes the kept or switched index, or -1.
+// Safe C entry point: `enemies_ptr`, `out_index` are null-checked before use; the rest of the contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn find_nearest_enemy_grace(
+    player_x: f32,
+    player_y: f32,
+    enemies_ptr: *const Enemy,
+    count: i32,
+    current_index: i32,
+    grace_dist: f32,
+    out_index: *mut i32,
+) -> DeadAimStatus {
+    ffi_guard(|| {
+        let enemies = unsafe { slice_arg(enemies_ptr, count) }?;
+        let nearest = nearest_alive_index(player_x, player_y, enemies);
+        let current = usize::try_from(current_index).ok().and_then(|i| enemies.get(i));
+        let index = match current.filter(|e| e.is_alive()) {
+            None => nearest,
+            Some(_) if nearest < 0 || nearest == current_index => current_index,
+            Some(current) => {
+                let dist = |e: &Enemy| ((e.x - player_x).powi(2) + (e.y - player_y).powi(2)).sqrt();
+                if dist(current) - dist(&enemies[nearest as usize]) >= grace_dist.max(0.0) {
+                    nearest
+                } else {
+                    current_index
+                }
+            }
+        };
+        unsafe { write_out(out_index, index) }
+    })
+}
+
+/// `find_nearest_enemy` with offsets and squared distances computed in f64. In very large worlds
+/// the f32 `dx*dx + dy*dy` loses precision (near 1e18 adjacent f32 values are ~7e10 apart, so
+/// offsets smaller than that vanish), far enemies tie and the wrong one can win; f64 keeps the
+/// ranking correct.
+#[no_mangle]
+pub extern "C" fn find_nearest_enemy_precise(
+    player_x: f32,
+    player_y: f32,
+    enemies_ptr: *const Enemy,
+    count: i32,
+    out_index: *mut i32,
+) -> DeadAimStatus {
+    let kind = DistanceKind::EuclideanF64;
+    find_nearest_enemy_with(player_x, player_y, enemies_ptr, count, kind, std::ptr::null(), out_index)
+}
+
+// ---------- Distance metrics ----------
+
+/// Distance used to rank enemies in `find_nearest_enemy_with`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DistanceKind {
+    /// Straight-line distance, f32 (what `find_nearest_enemy` uses).
+    Euclidean,
+    /// Straight-line distance accumulated in f64, for very large coordinates.
+    EuclideanF64,
+    /// |dx| + |dy|, for grid movement without diagonals.
+    Manhattan,
+    /// max(|dx|, |dy|), for grid movement with free diagonals.
+    Chebyshev,
+    /// Euclidean with the axes scaled by `DistanceParams::wx`/`wy` (e.g. to flatten height).
+    WeightedAxes,
+}
+
+/// Extra parameters for `DistanceKind`s that need them; a null pointer means weights of 1.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct DistanceParams {
+    pub wx: f32,
+    pub wy: f32,
+}
+
+/// Ranking key for enemy `e` under `kind` (monotonic in the distance; squared where that's cheaper).
+fn distance_key(player_x: f32, player_y: f32, e: &Enemy, kind: DistanceKind, params: DistanceParams) -> f64 {
+    let (dx, dy) = (e.x - player_x, e.y - player_y);
+    match kind {
+        DistanceKind::Euclidean => (dx * dx + dy * dy) as f64,
+        DistanceKind::EuclideanF64 => {
+            // take the offset in f64 too, so huge coordinates don't cancel out in f32
+            let dx = e.x as f64 - player_x as f64;
+            let dy = e.y as f64 - player_y as f64;
+            dx * dx + dy * dy
+        }
+        DistanceKind::Manhattan => (dx.abs() + dy.abs()) as f64,
+        DistanceKind::Chebyshev => dx.abs().max(dy.abs()) as f64,
+        DistanceKind::WeightedAxes => {
+            let (wx, wy) = (dx * params.wx, dy * params.wy);
+            (wx * wx + wy * wy) as f64
+        }
     }
 }
 
-/// Move enemies randomly. `speed` is max delta per call.
+/// Nearest alive enemy under any `DistanceKind`; `params` may be null. Writes the index or -1.
+/// Every metric-specific nearest query is a thin wrapper over this.
+// Safe C entry point: `enemies_ptr`, `params`, `out_index` are null-checked before use;
+// the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
 #[no_mangle]
-pub extern "C" fn move_enemies_randomly(
-    enemies_ptr: *mut Enemy,
+pub extern "C" fn find_nearest_enemy_with(
+    player_x: f32,
+    player_y: f32,
+    enemies_ptr: *const Enemy,
     count: i32,
-    speed: f32,
-) {
-    if enemies_ptr.is_null() || count <= 0 || speed <= 0.0 {
-        return;
+    kind: DistanceKind,
+    params: *const DistanceParams,
+    out_index: *mut i32,
+) -> DeadAimStatus {
+    ffi_guard(|| {
+        let enemies = unsafe { slice_arg(enemies_ptr, count) }?;
+        let params = unsafe { params.as_ref() }.copied().unwrap_or(DistanceParams { wx: 1.0, wy: 1.0 });
+        unsafe { write_out(out_index, nearest_by_kind(player_x, player_y, enemies, kind, params)) }
+    })
+}
+
+/// `find_nearest_enemy_with` over a slice.
+fn nearest_by_kind(player_x: f32, player_y: f32, enemies: &[Enemy], kind: DistanceKind, params: DistanceParams) -> i32 {
+    let mut nearest_index: i32 = -1;
+    let mut min_key = f64::INFINITY;
+    for (i, e) in enemies.iter().enumerate().filter(|(_, e)| e.is_alive()) {
+        let key = distance_key(player_x, player_y, e, kind, params);
+        if key < min_key {
+            min_key = key;
+            nearest_index = i as i32;
+        }
     }
-    let enemies = unsafe { slice::from_raw_parts_mut(enemies_ptr, count as usize) };
-    let mut rng = rand::thread_rng();
+    nearest_index
+}
+
+/// Click-to-target: find the alive enemy whose circle of `pick_radius` contains the point
+/// (`world_x`, `world_y`); a `pick_radius` of 0 uses each enemy's own hitbox (see
+/// `set_enemy_hit_radius`). When several circles overlap the point, the enemy closest to it wins.
+/// Writes its index, or -1 if the point is over empty space; `InvalidArgument` for a negative or
+/// NaN radius.
+// Safe C entry point: `enemies_ptr`, `out_index` are null-checked before use; the rest of the contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn pick_enemy_at(
+    world_x: f32,
+    world_y: f32,
+    pick_radius: f32,
+    enemies_ptr: *const Enemy,
+    count: i32,
+    out_index: *mut i32,
+) -> DeadAimStatus {
+    ffi_guard(|| {
+        let enemies = unsafe { slice_arg(enemies_ptr, count) }?;
+        if pick_radius.is_nan() || pick_radius < 0.0 {
+            return Err(DeadAimStatus::InvalidArgument);
+        }
+        let mut picked: i32 = -1;
+        let mut best_dist2 = f32::MAX;
+        for (i, e) in enemies.iter().enumerate() {
+            if !e.is_alive() {
+                continue;
+            }
+            let dx = world_x - e.x;
+            let dy = world_y - e.y;
+            let dist2 = dx * dx + dy * dy;
+            let radius = if pick_radius > 0.0 { pick_radius } else { combat::hit_radius_of(e) };
+            if dist2 <= radius * radius && dist2 < best_dist2 {
+                best_dist2 = dist2;
+                picked = i as i32;
+            }
+        }
+        unsafe { write_out(out_index, picked) }
+    })
+}
 
+/// Cheap proximity trigger: writes true as soon as any alive enemy is found within `radius` of
+/// the center. Short-circuits instead of scanning for the nearest. `InvalidArgument` for a
+/// negative or NaN radius.
+// Safe C entry point: `enemies_ptr`, `out_any` are null-checked before use; the rest of the contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn any_enemy_within(
+    center_x: f32,
+    center_y: f32,
+    radius: f32,
+    enemies_ptr: *const Enemy,
+    count: i32,
+    out_any: *mut bool,
+) -> DeadAimStatus {
+    ffi_guard(|| {
+        let enemies = unsafe { slice_arg(enemies_ptr, count) }?;
+        if radius.is_nan() || radius < 0.0 {
+            return Err(DeadAimStatus::InvalidArgument);
+        }
+        let r2 = radius * radius;
+        let any = enemies.iter().any(|e| {
+            let dx = e.x - center_x;
+            let dy = e.y - center_y;
+            e.is_alive() && dx * dx + dy * dy <= r2
+        });
+        unsafe { write_out(out_any, any) }
+    })
+}
+
+/// Circle query for AoE: write the indices (ascending) of alive enemies within `radius` of
+/// (`x`, `y`) into `out_indices` (capacity `out_cap`), and the total number inside to
+/// `out_found`; the total may exceed `out_cap` (only the first `out_cap` are written).
+/// `InvalidArgument` for a negative or NaN radius.
+// Safe C entry point: `enemies_ptr`, `out_indices`, `out_found` are null-checked before use;
+// the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn enemies_in_radius(
+    x: f32,
+    y: f32,
+    radius: f32,
+    enemies_ptr: *const Enemy,
+    count: i32,
+    out_indices: *mut i32,
+    out_cap: i32,
+    out_found: *mut i32,
+) -> DeadAimStatus {
+    ffi_guard(|| {
+        let enemies = unsafe { slice_arg(enemies_ptr, count) }?;
+        let out = unsafe { slice_arg_mut(out_indices, out_cap) }?;
+        if radius.is_nan() || radius < 0.0 {
+            return Err(DeadAimStatus::InvalidArgument);
+        }
+        let r2 = radius * radius;
+        let mut found = 0usize;
+        for (i, e) in enemies.iter().enumerate() {
+            if e.is_alive() && (e.x - x).powi(2) + (e.y - y).powi(2) <= r2 {
+                if let Some(o) = out.get_mut(found) {
+                    *o = i as i32;
+                }
+                found += 1;
+            }
+        }
+        unsafe { write_out(out_found, found as i32) }
+    })
+}
+
+/// Dominant threat direction for directional damage indicators: a distance-weighted average of
+/// the unit directions toward every alive enemy (weight 1/distance, so nearer enemies count more),
+/// normalized and written to `out_x`/`out_y`. Enemies exactly on the player are ignored. Writes
+/// whether any alive enemy contributed to `out_found`; the direction is (0, 0) when none did, or
+/// when the threats cancel out exactly (then `out_found` is still true).
+// Safe C entry point: `enemies_ptr`, `out_x`, `out_y`, `out_found` are null-checked before use;
+// the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn threat_direction(
+    player_x: f32,
+    player_y: f32,
+    enemies_ptr: *const Enemy,
+    count: i32,
+    out_x: *mut f32,
+    out_y: *mut f32,
+    out_found: *mut bool,
+) -> DeadAimStatus {
+    ffi_guard(|| {
+        let enemies = unsafe { slice_arg(enemies_ptr, count) }?;
+        if out_x.is_null() || out_y.is_null() || out_found.is_null() {
+            return Err(DeadAimStatus::NullPointer);
+        }
+        let (mut sum_x, mut sum_y) = (0.0f32, 0.0f32);
+        let mut found = false;
+        for e in enemies.iter().filter(|e| e.is_alive()) {
+            let dx = e.x - player_x;
+            let dy = e.y - player_y;
+            let dist2 = dx * dx + dy * dy;
+            if dist2 <= f32::EPSILON {
+                continue;
+            }
+            found = true;
+            // unit direction (d / dist) weighted by 1 / dist
+            sum_x += dx / dist2;
+            sum_y += dy / dist2;
+        }
+        let len = (sum_x * sum_x + sum_y * sum_y).sqrt();
+        let (dir_x, dir_y) = if len <= f32::EPSILON || len.is_nan() { (0.0, 0.0) } else { (sum_x / len, sum_y / len) };
+        unsafe {
+            out_x.write(dir_x);
+            out_y.write(dir_y);
+            out_found.write(found);
+        }
+        Ok(())
+    })
+}
+
+/// Shoot enemy at index => instant kill. Compatibility shim over the health model: health drops
+/// to 0, the enemy is marked dead and the kill counts toward the current wave, as with lethal
+/// `apply_damage`. `index` is checked against `count`, so a stale index can't write past the
+/// array: `InvalidIndex` for an index outside `0..count`.
+// Safe C entry point: `enemies_ptr` is null-checked before use; the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn shoot_enemy(index: i32, enemies_ptr: *mut Enemy, count: i32) -> DeadAimStatus {
+    ffi_guard(|| {
+        let enemies = unsafe { slice_arg_mut(enemies_ptr, count) }?;
+        let e = usize::try_from(index).ok().and_then(|i| enemies.get_mut(i)).ok_or(DeadAimStatus::InvalidIndex)?;
+        let killed = e.is_alive();
+        if killed {
+            events::enemy_killed(e);
+        }
+        e.health = 0.0;
+        e.set_alive(false);
+        if killed {
+            spawner::check_wave_cleared(enemies);
+        }
+        Ok(())
+    })
+}
+
+/// Remove the enemy at `index` entirely (not just kill it) by moving the last enemy into its
+/// slot, keeping the array dense. O(1); array order is not preserved. Writes the new count to
+/// `out_count`; `InvalidIndex` for an index outside `0..count`.
+// Safe C entry point: `enemies_ptr`, `out_count` are null-checked before use; the rest of the contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn remove_enemy(enemies_ptr: *mut Enemy, count: i32, index: i32, out_count: *mut i32) -> DeadAimStatus {
+    ffi_guard(|| {
+        let enemies = unsafe { slice_arg_mut(enemies_ptr, count) }?;
+        let i = usize::try_from(index).ok().filter(|&i| i < enemies.len()).ok_or(DeadAimStatus::InvalidIndex)?;
+        let last = enemies.len() - 1;
+        enemies.swap(i, last);
+        unsafe { write_out(out_count, last as i32) }
+    })
+}
+
+/// Move enemies randomly. `speed` is max delta per call. Draws from the seeded global RNG
+/// (see `set_rng_seed`), so equal seeds reproduce the same walk. Swarm enemies then flock
+/// (see `set_flocking`), and everyone is kept inside the arena (see `set_arena_bounds`).
+/// An empty array or zero speed does nothing; `InvalidArgument` for a negative/NaN speed.
+// Safe C entry point: `enemies_ptr` is null-checked before use; the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn move_enemies_randomly(enemies_ptr: *mut Enemy, count: i32, speed: f32) -> DeadAimStatus {
+    ffi_guard(|| {
+        let enemies = unsafe { slice_arg_mut(enemies_ptr, count) }?;
+        if speed.is_nan() || speed < 0.0 {
+            return Err(DeadAimStatus::InvalidArgument);
+        }
+        if enemies.is_empty() || speed == 0.0 {
+            return Ok(());
+        }
+        rng::with_rng(|rng| move_randomly(enemies, speed, rng));
+        Ok(())
+    })
+}
+
+/// Body of `move_enemies_randomly` drawing from `rng`: random walk, flocking, arena bounds.
+pub(crate) fn move_randomly(enemies: &mut [Enemy], speed: f32, rng: &mut rng::RngState) {
+    random_walk(enemies, speed, |step| rng.range_f32(-step, step));
+    steering::apply_flocking(enemies, speed);
+    arena::confine_enemies(enemies);
+}
+
+/// Random-walk every alive, non-stunned enemy; `sample(step)` returns a delta in [-step, step).
+pub(crate) fn random_walk(enemies: &mut [Enemy], speed: f32, mut sample: impl FnMut(f32) -> f32) {
     for e in enemies.iter_mut() {
-        if e.alive {
+        if e.is_alive() && !consume_stun(e) {
             // small random walk
-            let dx: f32 = rng.gen_range(-speed..speed);
-            let dy: f32 = rng.gen_range(-speed..speed);
+            let step = speed * speed_scale(e) * tick_flinch(e);
+            if step <= 0.0 {
+                e.vx = 0.0;
+                e.vy = 0.0;
+                continue;
+            }
+            let dx: f32 = sample(step);
+            let dy: f32 = sample(step);
             e.x += dx;
             e.y += dy;
-            // clamp to reasonable bounds (e.g., grid 0..=GRID_SIZE-1). caller can clamp as well.
+            e.vx = dx;
+            e.vy = dy;
+            // callers apply the arena bounds (see `set_arena_bounds`) once everyone has moved
             if e.x.is_nan() || e.y.is_nan() {
                 e.x = 0.0;
                 e.y = 0.0;
@@ -146,52 +832,328 @@ pub extern "C" fn move_enemies_randomly(
     }
 }
 
-// ---------- Reward hooks (call frontend to perform actual blockchain ops) ----------
-
-/// Reward player with fungible token amount (smallest unit). Frontend must implement js_send_token.
-/// `wallet` is a null-terminated C string pointer expected from caller; to simplify from C++,
-/// you can call this from the WASM/js layer. For native builds this is a stub.
-#[no_mangle]
-pub extern "C" fn reward_player(wallet_ptr: *const u8, wallet_len: usize, amount: u64) {
-    if wallet_ptr.is_null() || wallet_len == 0 {
-        js_log("reward_player: invalid wallet pointer/len");
-        return;
+/// If the enemy is stunned, burn one stun frame, zero its velocity and return true
+/// (it must not move this tick).
+pub(crate) fn consume_stun(e: &mut Enemy) -> bool {
+    if e.stun_frames > 0 {
+        e.stun_frames -= 1;
+        e.vx = 0.0;
+        e.vy = 0.0;
+        true
+    } else {
+        false
     }
-    // Convert C-style pointer+len to &str
-    let wallet_slice = unsafe { std::slice::from_raw_parts(wallet_ptr, wallet_len) };
-    if let Ok(wallet_str) = std::str::from_utf8(wallet_slice) {
-        // call JS/native hook
-        js_send_token(wallet_str, amount);
-        js_log(&format!("reward_player: sent {} to {}", amount, wallet_str));
+}
+
+/// Speed factor for one movement tick while flinching (0.5, burning a flinch frame), else 1.0.
+pub(crate) fn tick_flinch(e: &mut Enemy) -> f32 {
+    if e.flinch_frames > 0 {
+        e.flinch_frames -= 1;
+        0.5
     } else {
-        js_log("reward_player: wallet string not utf-8");
+        1.0
     }
 }
 
-/// Mint an NFT for a player: frontend must implement js_mint_nft(wallet, metadata)
+/// Combined movement-speed modifier from the enemy's type and combat state (e.g. berserk).
+pub(crate) fn speed_scale(e: &Enemy) -> f32 {
+    let type_speed = enemy_types::enemy_type(e.type_id).map_or(1.0, |t| t.speed_multiplier);
+    type_speed * combat::berserk_multiplier(e)
+}
+
+// ---------- World scale (world units -> meters for reported distances) ----------
+
+// f32 bits of world units per meter; defaults to 1.0 (world units are meters).
+static WORLD_UNITS_PER_METER: AtomicU32 = AtomicU32::new(0x3f80_0000);
+
+/// Set how many world units make up one meter. Every function that reports a distance
+/// divides by this scale, so callers get meters while enemy arrays stay in world units.
+/// `InvalidArgument` (scale unchanged) for a non-finite or non-positive value.
 #[no_mangle]
-pub extern "C" fn mint_nft_for_player(wallet_ptr: *const u8, wallet_len: usize, meta_ptr: *const u8, meta_len: usize) {
-    if wallet_ptr.is_null() || wallet_len == 0 {
-        js_log("mint_nft_for_player: invalid wallet pointer");
-        return;
-    }
-    let wallet_slice = unsafe { std::slice::from_raw_parts(wallet_ptr, wallet_len) };
-    let meta_slice = unsafe { std::slice::from_raw_parts(meta_ptr, meta_len) };
+pub extern "C" fn set_world_scale(units_per_meter: f32) -> DeadAimStatus {
+    ffi_guard(|| {
+        if !units_per_meter.is_finite() || units_per_meter <= 0.0 {
+            return Err(DeadAimStatus::InvalidArgument);
+        }
+        WORLD_UNITS_PER_METER.store(units_per_meter.to_bits(), Ordering::Relaxed);
+        Ok(())
+    })
+}
 
-    if let (Ok(wallet_str), Ok(meta_str)) = (std::str::from_utf8(wallet_slice), std::str::from_utf8(meta_slice)) {
-        js_mint_nft(wallet_str, meta_str);
-        js_log(&format!("mint_nft_for_player: minted for {} metadata={}", wallet_str, meta_str));
-    } else {
-        js_log("mint_nft_for_player: utf-8 conversion failed");
+pub(crate) fn world_units_per_meter() -> f32 {
+    f32::from_bits(WORLD_UNITS_PER_METER.load(Ordering::Relaxed))
+}
+
+/// Convert a world-unit distance to meters.
+pub(crate) fn to_meters(world_dist: f32) -> f32 {
+    world_dist / world_units_per_meter()
+}
+
+/// Like `find_nearest_enemy`, additionally writing the distance in meters to `out_dist` (which
+/// may be null; it is left untouched when no enemy is found).
+// Safe C entry point: `enemies_ptr`, `out_index`, `out_dist` are null-checked before use;
+// the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn find_nearest_enemy_with_distance(
+    player_x: f32,
+    player_y: f32,
+    enemies_ptr: *const Enemy,
+    count: i32,
+    out_index: *mut i32,
+    out_dist: *mut f32,
+) -> DeadAimStatus {
+    ffi_guard(|| {
+        let enemies = unsafe { slice_arg(enemies_ptr, count) }?;
+        let index = nearest_alive_index(player_x, player_y, enemies);
+        unsafe { write_out(out_index, index) }?;
+        if let Some(e) = usize::try_from(index).ok().and_then(|i| enemies.get(i)) {
+            let dist = ((player_x - e.x).powi(2) + (player_y - e.y).powi(2)).sqrt();
+            unsafe { write_opt(out_dist, to_meters(dist)) };
+        }
+        Ok(())
+    })
+}
+
+/// Like `find_nearest_enemy_with_distance`, but writes the *squared* distance (square meters)
+/// to `out_dist2` and skips the sqrt. Squared distances order the same as linear ones, so this is
+/// the cheaper choice for comparison-only callers; take the sqrt yourself if you need meters.
+// Safe C entry point: `enemies_ptr`, `out_index`, `out_dist2` are null-checked before use;
+// the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn find_nearest_enemy_with_dist2(
+    player_x: f32,
+    player_y: f32,
+    enemies_ptr: *const Enemy,
+    count: i32,
+    out_index: *mut i32,
+    out_dist2: *mut f32,
+) -> DeadAimStatus {
+    ffi_guard(|| {
+        let enemies = unsafe { slice_arg(enemies_ptr, count) }?;
+        let index = nearest_alive_index(player_x, player_y, enemies);
+        unsafe { write_out(out_index, index) }?;
+        if let Some(e) = usize::try_from(index).ok().and_then(|i| enemies.get(i)) {
+            let dist2 = (player_x - e.x).powi(2) + (player_y - e.y).powi(2);
+            let scale = world_units_per_meter();
+            unsafe { write_opt(out_dist2, dist2 / (scale * scale)) };
+        }
+        Ok(())
+    })
+}
+
+/// Writes the distance in meters from the player to the enemy at `index` to `out_dist`;
+/// `InvalidIndex` for an index outside `0..count`.
+// Safe C entry point: `enemies_ptr`, `out_dist` are null-checked before use; the rest of the contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn distance_to_enemy(
+    player_x: f32,
+    player_y: f32,
+    enemies_ptr: *const Enemy,
+    count: i32,
+    index: i32,
+    out_dist: *mut f32,
+) -> DeadAimStatus {
+    ffi_guard(|| {
+        let enemies = unsafe { slice_arg(enemies_ptr, count) }?;
+        let e = usize::try_from(index).ok().and_then(|i| enemies.get(i)).ok_or(DeadAimStatus::InvalidIndex)?;
+        unsafe { write_out(out_dist, to_meters(((player_x - e.x).powi(2) + (player_y - e.y).powi(2)).sqrt())) }
+    })
+}
+
+// ---------- Packed enemy data (typed arrays) ----------
+// Hosts that can't share `Enemy` structs (JS in particular) pass enemies as a flat f32 array of
+// `PACKED_ENEMY_STRIDE` values each: x, y, alive (non-zero = alive). A trailing partial entry is
+// ignored.
+
+/// f32 values per enemy in packed enemy data.
+pub const PACKED_ENEMY_STRIDE: usize = 3;
+
+fn packed_alive(packed: &[f32]) -> impl Iterator<Item = (usize, f32, f32)> + '_ {
+    packed.chunks_exact(PACKED_ENEMY_STRIDE).enumerate().filter(|(_, e)| e[2] != 0.0).map(|(i, e)| (i, e[0], e[1]))
+}
+
+fn nearest_packed(player_x: f32, player_y: f32, packed: &[f32]) -> i32 {
+    let mut nearest_index: i32 = -1;
+    let mut min_dist2: f32 = f32::MAX;
+    for (i, x, y) in packed_alive(packed) {
+        let dist2 = (player_x - x).powi(2) + (player_y - y).powi(2);
+        if dist2 < min_dist2 {
+            min_dist2 = dist2;
+            nearest_index = i as i32;
+        }
     }
+    nearest_index
+}
+
+fn in_radius_packed(x: f32, y: f32, radius: f32, packed: &[f32]) -> impl Iterator<Item = usize> + '_ {
+    let r2 = if radius.is_nan() || radius < 0.0 { -1.0 } else { radius * radius };
+    packed_alive(packed).filter(move |&(_, ex, ey)| (ex - x).powi(2) + (ey - y).powi(2) <= r2).map(|(i, _, _)| i)
+}
+
+/// `find_nearest_enemy` over packed enemy data: `len` f32 values at `packed_ptr`. Writes the
+/// enemy index (not the float offset) or -1.
+// Safe C entry point: `packed_ptr`, `out_index` are null-checked before use; the rest of the contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn find_nearest_enemy_packed(
+    player_x: f32,
+    player_y: f32,
+    packed_ptr: *const f32,
+    len: i32,
+    out_index: *mut i32,
+) -> DeadAimStatus {
+    ffi_guard(|| {
+        let packed = unsafe { slice_arg(packed_ptr, len) }?;
+        unsafe { write_out(out_index, nearest_packed(player_x, player_y, packed)) }
+    })
+}
+
+/// `enemies_in_radius` over packed enemy data: `len` f32 values at `packed_ptr`. Writes enemy
+/// indices (ascending) into `out_indices` up to `out_cap` and the total inside to `out_found`.
+// Safe C entry point: `packed_ptr`, `out_indices`, `out_found` are null-checked before use;
+// the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn enemies_in_radius_packed(
+    x: f32,
+    y: f32,
+    radius: f32,
+    packed_ptr: *const f32,
+    len: i32,
+    out_indices: *mut i32,
+    out_cap: i32,
+    out_found: *mut i32,
+) -> DeadAimStatus {
+    ffi_guard(|| {
+        let packed = unsafe { slice_arg(packed_ptr, len) }?;
+        let out = unsafe { slice_arg_mut(out_indices, out_cap) }?;
+        if radius.is_nan() || radius < 0.0 {
+            return Err(DeadAimStatus::InvalidArgument);
+        }
+        let mut found = 0usize;
+        for i in in_radius_packed(x, y, radius, packed) {
+            if let Some(o) = out.get_mut(found) {
+                *o = i as i32;
+            }
+            found += 1;
+        }
+        unsafe { write_out(out_found, found as i32) }
+    })
 }
 
-// ---------- Convenience helpers for WASM/JS usage (optional) ----------
+/// Nearest alive enemy in a `Float32Array` of packed enemy data (see `PACKED_ENEMY_STRIDE`), so
+/// JS can target without building per-enemy objects. Returns the index or -1.
 #[cfg(target_arch = "wasm32")]
 #[wasm_bindgen]
-pub fn wasm_find_nearest_enemy(player_x: f32, player_y: f32, enemies: &JsValue) -> i32 {
-    // This helper expects `enemies` as a JS TypedArray or array of objects matching Enemy layout.
-    // For simplicity, we expect the JS side to manage marshalling. Here we'll just log.
-    js_log("wasm_find_nearest_enemy called - prefer using pointer-based FFI from native C++");
-    -1
-}
\ No newline at end of file
+pub fn wasm_find_nearest_enemy(player_x: f32, player_y: f32, enemies: &[f32]) -> i32 {
+    nearest_packed(player_x, player_y, enemies)
+}
+
+/// Indices (ascending) of the alive enemies within `radius` of (`x`, `y`) in a `Float32Array` of
+/// packed enemy data, returned to JS as a `Uint32Array`.
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn wasm_enemies_in_radius(x: f32, y: f32, radius: f32, enemies: &[f32]) -> Vec<u32> {
+    in_radius_packed(x, y, radius, enemies).map(|i| i as u32).collect()
+}
+
+// ---------- Plain JS object interop ----------
+// For web games that keep enemies as plain `{ id, x, y, alive, health, ... }` objects (property
+// names as in `Enemy`, converted by serde): load them into a module-owned enemy buffer, run any
+// pointer-based export over it via `wasm_enemy_buffer_ptr`/`wasm_enemy_count`, and read the
+// results back. Needs the `serde` feature.
+#[cfg(all(target_arch = "wasm32", feature = "serde"))]
+mod js_objects {
+    use crate::Enemy;
+    use serde_wasm_bindgen::{from_value, to_value};
+    use std::cell::RefCell;
+    use wasm_bindgen::prelude::*;
+
+    thread_local! {
+        static ENEMIES: RefCell<Vec<Enemy>> = const { RefCell::new(Vec::new()) };
+    }
+
+    /// Replace the module's enemy buffer with an array of plain enemy objects; missing
+    /// properties take `Enemy::default()` values. Returns the number stored, or -1 (buffer
+    /// unchanged) if the value isn't an array of enemy objects.
+    #[wasm_bindgen]
+    pub fn wasm_set_enemies(enemies: JsValue) -> i32 {
+        let Ok(parsed) = from_value::<Vec<Enemy>>(enemies) else {
+            return -1;
+        };
+        let count = parsed.len() as i32;
+        ENEMIES.with(|buf| *buf.borrow_mut() = parsed);
+        count
+    }
+
+    /// The module's enemy buffer as an array of fresh plain objects (every `Enemy` field).
+    #[wasm_bindgen]
+    pub fn wasm_get_enemies() -> JsValue {
+        ENEMIES.with(|buf| to_value(&*buf.borrow()).unwrap_or(JsValue::UNDEFINED))
+    }
+
+    /// Start of the enemy buffer in linear memory, to pass as `enemies_ptr` to the pointer-based
+    /// exports; valid until the next `wasm_set_enemies`.
+    #[wasm_bindgen]
+    pub fn wasm_enemy_buffer_ptr() -> *mut Enemy {
+        ENEMIES.with(|buf| buf.borrow_mut().as_mut_ptr())
+    }
+
+    /// Number of enemies in the buffer, to pass as `count`.
+    #[wasm_bindgen]
+    pub fn wasm_enemy_count() -> i32 {
+        ENEMIES.with(|buf| buf.borrow().len() as i32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn precise_nearest_ranks_correctly_near_1e18() {
+        // 1e10 is below the f32 spacing at 1e18: in f32 both offsets round to 1e18 and tie
+        let enemies = [Enemy::new(0, 0.0, 0.0), Enemy::new(1, 1e10, 0.0)];
+        let (player_x, player_y) = (1e18_f32, 0.0);
+        assert_eq!(player_x - enemies[1].x, player_x - enemies[0].x);
+
+        let (mut plain, mut precise) = (-2, -2);
+        assert_eq!(find_nearest_enemy(player_x, player_y, enemies.as_ptr(), 2, &mut plain), DeadAimStatus::Ok);
+        let status = find_nearest_enemy_precise(player_x, player_y, enemies.as_ptr(), 2, &mut precise);
+        assert_eq!(status, DeadAimStatus::Ok);
+        assert_eq!((plain, precise), (0, 1));
+    }
+
+    fn threat(enemies: &[Enemy]) -> (f32, f32, bool) {
+        let (mut x, mut y, mut found) = (f32::NAN, f32::NAN, false);
+        let status = threat_direction(0.0, 0.0, enemies.as_ptr(), enemies.len() as i32, &mut x, &mut y, &mut found);
+        assert_eq!(status, DeadAimStatus::Ok);
+        (x, y, found)
+    }
+
+    #[test]
+    fn threat_direction_leans_toward_the_nearer_enemy() {
+        let mut enemies = [Enemy::new(0, 2.0, 0.0), Enemy::new(1, -8.0, 0.0)];
+        let (x, y, found) = threat(&enemies);
+        assert!(found);
+        assert!((x - 1.0).abs() < 1e-6 && y.abs() < 1e-6, "({x}, {y})");
+
+        enemies[1].x = -2.0;
+        assert_eq!(threat(&enemies), (0.0, 0.0, true));
+
+        enemies.iter_mut().for_each(|e| e.set_alive(false));
+        assert_eq!(threat(&enemies), (0.0, 0.0, false));
+    }
+
+    #[test]
+    fn precise_nearest_agrees_on_ordinary_maps() {
+        let enemies = [Enemy::new(0, 5.0, 5.0), Enemy::new(1, -1.0, 2.0), Enemy::new(2, 3.0, -4.0)];
+        let (mut plain, mut precise) = (-2, -2);
+        find_nearest_enemy(0.5, 0.5, enemies.as_ptr(), 3, &mut plain);
+        find_nearest_enemy_precise(0.5, 0.5, enemies.as_ptr(), 3, &mut precise);
+        assert_eq!((plain, precise), (1, 1));
+    }
+}
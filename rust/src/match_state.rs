@@ -0,0 +1,65 @@
+// src/match_state.rs
+// Per-match state a registered world owns: the event queue, match session, scores, safe zones,
+// heatmap samples and tracked wave. While a registry call runs on a world, the world's state is
+// installed for the calling thread and the crate-wide entry points (and the kill and shot paths)
+// use it in place of the process-wide copy, so matches on different threads never share it.
+use crate::events::EventQueue;
+use crate::heatmap::Samples;
+use crate::obstacles::Rect;
+use crate::scoring::PlayerScore;
+use crate::session::Match;
+use crate::spawner::WaveTracker;
+use std::cell::RefCell;
+use std::sync::Mutex;
+
+#[derive(Clone, Default)]
+pub(crate) struct MatchState {
+    pub(crate) events: EventQueue,
+    pub(crate) session: Option<Match>,
+    pub(crate) scores: Vec<PlayerScore>,
+    pub(crate) safe_zones: Vec<Rect>,
+    pub(crate) heatmap: Samples,
+    pub(crate) wave: WaveTracker,
+}
+
+thread_local! {
+    static INSTALLED: RefCell<Option<MatchState>> = const { RefCell::new(None) };
+}
+
+/// Puts the outer state back (and the installed one, with its changes, into place) even if the
+/// call panics.
+struct Restore<'a> {
+    state: &'a mut MatchState,
+    outer: Option<MatchState>,
+}
+
+impl Drop for Restore<'_> {
+    fn drop(&mut self) {
+        *self.state = INSTALLED.with(|slot| slot.replace(self.outer.take())).unwrap_or_default();
+    }
+}
+
+impl MatchState {
+    /// Run `f` with this state installed for the calling thread, then take it back with whatever
+    /// `f` changed. Installs nest: a state installed inside `f` shadows this one until it returns.
+    pub(crate) fn install<T>(&mut self, f: impl FnOnce() -> T) -> T {
+        let outer = INSTALLED.with(|slot| slot.replace(Some(std::mem::take(self))));
+        let _restore = Restore { state: self, outer };
+        f()
+    }
+}
+
+/// Run `f` on `part` of the installed match state, or on the process-wide `global` when no world
+/// is installed. `f` must not reach match state again.
+pub(crate) fn with_part<S, T>(
+    global: &Mutex<S>,
+    part: fn(&mut MatchState) -> &mut S,
+    f: impl FnOnce(&mut S) -> T,
+) -> T {
+    INSTALLED.with(|slot| {
+        if let Some(state) = slot.borrow_mut().as_mut() {
+            return f(part(state));
+        }
+        f(&mut global.lock().unwrap_or_else(|e| e.into_inner()))
+    })
+}
@@ -0,0 +1,370 @@
+// src/obstacles.rs
+// Obstacle geometry and line-of-sight tests.
+use crate::error::{ffi_guard, slice_arg, slice_arg_mut, write_out, DeadAimStatus, FfiResult};
+use crate::{k_nearest_matching, nearest_matching, Enemy};
+use std::sync::Mutex;
+
+/// Axis-aligned obstacle box in world units.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Rect {
+    pub min_x: f32,
+    pub min_y: f32,
+    pub max_x: f32,
+    pub max_y: f32,
+}
+
+impl Rect {
+    pub fn contains(&self, x: f32, y: f32) -> bool {
+        x >= self.min_x && x <= self.max_x && y >= self.min_y && y <= self.max_y
+    }
+
+    /// Does the circle of `radius` around (x, y) overlap this box?
+    pub fn intersects_circle(&self, x: f32, y: f32, radius: f32) -> bool {
+        let (cx, cy) = (x.clamp(self.min_x, self.max_x), y.clamp(self.min_y, self.max_y));
+        (x - cx).powi(2) + (y - cy).powi(2) < radius * radius || self.contains(x, y)
+    }
+
+    /// Slab test: does the segment (x0,y0)-(x1,y1) touch this box?
+    pub fn intersects_segment(&self, x0: f32, y0: f32, x1: f32, y1: f32) -> bool {
+        self.clip_segment(x0, y0, x1, y1).is_some()
+    }
+
+    /// Parameter range `(t_min, t_max)` within 0..=1 of the segment (x0,y0)-(x1,y1) that lies
+    /// inside this box, or `None` if it misses.
+    fn clip_segment(&self, x0: f32, y0: f32, x1: f32, y1: f32) -> Option<(f32, f32)> {
+        let (mut t_min, mut t_max) = (0.0f32, 1.0f32);
+        for (p0, d, lo, hi) in [
+            (x0, x1 - x0, self.min_x, self.max_x),
+            (y0, y1 - y0, self.min_y, self.max_y),
+        ] {
+            if d.abs() < f32::EPSILON {
+                if p0 < lo || p0 > hi {
+                    return None;
+                }
+            } else {
+                let (mut t0, mut t1) = ((lo - p0) / d, (hi - p0) / d);
+                if t0 > t1 {
+                    std::mem::swap(&mut t0, &mut t1);
+                }
+                t_min = t_min.max(t0);
+                t_max = t_max.min(t1);
+                if t_min > t_max {
+                    return None;
+                }
+            }
+        }
+        Some((t_min, t_max))
+    }
+}
+
+/// True when no obstacle blocks the segment between the two points.
+pub(crate) fn segment_clear(x0: f32, y0: f32, x1: f32, y1: f32, obstacles: &[Rect]) -> bool {
+    !obstacles.iter().any(|r| r.intersects_segment(x0, y0, x1, y1))
+}
+
+/// Per-enemy visibility from the player (1 = clear line of sight, 0 = occluded or dead).
+fn visibility(player_x: f32, player_y: f32, enemies: &[Enemy], obstacles: &[Rect]) -> Vec<u8> {
+    enemies
+        .iter()
+        .map(|e| (e.is_alive() && segment_clear(player_x, player_y, e.x, e.y, obstacles)) as u8)
+        .collect()
+}
+
+/// Run the occlusion pass once per frame: write 1 (visible) or 0 (occluded or dead) for each of
+/// the `count` enemies into `out_visible`, for the `*_masked` queries to reuse.
+// Safe C entry point: `enemies_ptr`, `obstacles_ptr`, `out_visible` are null-checked before use;
+// the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn compute_visibility(
+    player_x: f32,
+    player_y: f32,
+    enemies_ptr: *const Enemy,
+    count: i32,
+    obstacles_ptr: *const Rect,
+    obstacle_count: i32,
+    out_visible: *mut u8,
+) -> DeadAimStatus {
+    ffi_guard(|| {
+        let enemies = unsafe { slice_arg(enemies_ptr, count) }?;
+        let obstacles = unsafe { slice_arg(obstacles_ptr, obstacle_count) }?;
+        let out = unsafe { slice_arg_mut(out_visible, enemies.len()) }?;
+        out.copy_from_slice(&visibility(player_x, player_y, enemies, obstacles));
+        Ok(())
+    })
+}
+
+/// Writes the nearest alive enemy whose `visible[i]` byte (from `compute_visibility`) is
+/// non-zero, or -1.
+// Safe C entry point: `enemies_ptr`, `visible`, `out_index` are null-checked before use;
+// the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn find_nearest_enemy_masked(
+    player_x: f32,
+    player_y: f32,
+    enemies_ptr: *const Enemy,
+    count: i32,
+    visible: *const u8,
+    out_index: *mut i32,
+) -> DeadAimStatus {
+    ffi_guard(|| {
+        let enemies = unsafe { slice_arg(enemies_ptr, count) }?;
+        let mask = unsafe { slice_arg(visible, enemies.len()) }?;
+        unsafe { write_out(out_index, nearest_matching(player_x, player_y, enemies, |i, _| mask[i] != 0)) }
+    })
+}
+
+/// Up to `k` nearest visible enemies (nearest first) per the `visible` mask, written to
+/// `out_indices` (capacity `k`), with the number written in `out_found`. `InvalidArgument` for a
+/// negative `k`.
+// Safe C entry point: `enemies_ptr`, `visible` are null-checked before use; the rest of the contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn find_k_nearest_masked(
+    player_x: f32,
+    player_y: f32,
+    enemies_ptr: *const Enemy,
+    count: i32,
+    visible: *const u8,
+    k: i32,
+    out_indices: *mut i32,
+    out_found: *mut i32,
+) -> DeadAimStatus {
+    ffi_guard(|| {
+        let enemies = unsafe { slice_arg(enemies_ptr, count) }?;
+        let mask = unsafe { slice_arg(visible, enemies.len()) }?;
+        write_k_nearest(player_x, player_y, enemies, |i| mask[i] != 0, k, out_indices, out_found)
+    })
+}
+
+/// Shared tail of the k-nearest visibility queries: at most `k` indices into `out_indices`.
+fn write_k_nearest(
+    player_x: f32,
+    player_y: f32,
+    enemies: &[Enemy],
+    visible: impl Fn(usize) -> bool,
+    k: i32,
+    out_indices: *mut i32,
+    out_found: *mut i32,
+) -> FfiResult {
+    let out = unsafe { slice_arg_mut(out_indices, k) }?;
+    let nearest = k_nearest_matching(player_x, player_y, enemies, out.len(), |i, _| visible(i));
+    for (o, &i) in out.iter_mut().zip(&nearest) {
+        *o = i as i32;
+    }
+    unsafe { write_out(out_found, nearest.len() as i32) }
+}
+
+/// Writes the nearest alive enemy with line of sight to the player, or -1. For several
+/// visibility queries in one frame, prefer `compute_visibility` + the `*_masked` queries.
+// Safe C entry point: `enemies_ptr`, `obstacles_ptr`, `out_index` are null-checked before use;
+// the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn find_nearest_visible_enemy(
+    player_x: f32,
+    player_y: f32,
+    enemies_ptr: *const Enemy,
+    count: i32,
+    obstacles_ptr: *const Rect,
+    obstacle_count: i32,
+    out_index: *mut i32,
+) -> DeadAimStatus {
+    ffi_guard(|| {
+        let enemies = unsafe { slice_arg(enemies_ptr, count) }?;
+        let obstacles = unsafe { slice_arg(obstacles_ptr, obstacle_count) }?;
+        let clear = |_, e: &Enemy| segment_clear(player_x, player_y, e.x, e.y, obstacles);
+        let index = nearest_matching(player_x, player_y, enemies, clear);
+        unsafe { write_out(out_index, index) }
+    })
+}
+
+/// Up to `k` nearest enemies with line of sight to the player, written to `out_indices`
+/// (capacity `k`), with the number written in `out_found`.
+// Safe C entry point: `enemies_ptr`, `obstacles_ptr` are null-checked before use;
+// the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn find_k_nearest_visible(
+    player_x: f32,
+    player_y: f32,
+    enemies_ptr: *const Enemy,
+    count: i32,
+    obstacles_ptr: *const Rect,
+    obstacle_count: i32,
+    k: i32,
+    out_indices: *mut i32,
+    out_found: *mut i32,
+) -> DeadAimStatus {
+    ffi_guard(|| {
+        let enemies = unsafe { slice_arg(enemies_ptr, count) }?;
+        let obstacles = unsafe { slice_arg(obstacles_ptr, obstacle_count) }?;
+        let mask = visibility(player_x, player_y, enemies, obstacles);
+        write_k_nearest(player_x, player_y, enemies, |i| mask[i] != 0, k, out_indices, out_found)
+    })
+}
+
+// ---------- Registered obstacle map (boxes + tile bitmap) ----------
+
+/// Host-registered level geometry consulted by `has_line_of_sight`. Tiles are 1x1 world units
+/// with tile (0, 0) covering [0, 1) x [0, 1); anything outside the bitmap is open.
+struct ObstacleMap {
+    boxes: Vec<Rect>,
+    tiles: Vec<u8>,
+    width: usize,
+    height: usize,
+}
+
+impl ObstacleMap {
+    fn tile_blocked(&self, tx: i64, ty: i64) -> bool {
+        if tx < 0 || ty < 0 || tx as usize >= self.width || ty as usize >= self.height {
+            return false;
+        }
+        self.tiles[ty as usize * self.width + tx as usize] != 0
+    }
+
+    /// Does the circle of `radius` around (x, y) overlap a box or a wall tile?
+    fn circle_blocked(&self, x: f32, y: f32, radius: f32) -> bool {
+        if self.boxes.iter().any(|b| b.intersects_circle(x, y, radius)) {
+            return true;
+        }
+        let (x0, x1) = ((x - radius).floor() as i64, (x + radius).floor() as i64);
+        let (y0, y1) = ((y - radius).floor() as i64, (y + radius).floor() as i64);
+        (y0..=y1).any(|ty| {
+            (x0..=x1).any(|tx| {
+                let tile = Rect { min_x: tx as f32, min_y: ty as f32, max_x: tx as f32 + 1.0, max_y: ty as f32 + 1.0 };
+                self.tile_blocked(tx, ty) && tile.intersects_circle(x, y, radius)
+            })
+        })
+    }
+
+    /// Walk the tiles the segment passes through (grid DDA), clipped to the bitmap first so far
+    /// away endpoints cost nothing extra.
+    fn tiles_clear(&self, x0: f32, y0: f32, x1: f32, y1: f32) -> bool {
+        if self.width == 0 || self.height == 0 {
+            return true;
+        }
+        let bounds = Rect { min_x: 0.0, min_y: 0.0, max_x: self.width as f32, max_y: self.height as f32 };
+        let Some((t0, t1)) = bounds.clip_segment(x0, y0, x1, y1) else {
+            return true;
+        };
+        let (dx, dy) = (x1 - x0, y1 - y0);
+        let (sx, sy) = (x0 + dx * t0, y0 + dy * t0);
+        let (ex, ey) = (x0 + dx * t1, y0 + dy * t1);
+        let (mut tx, mut ty) = (sx.floor() as i64, sy.floor() as i64);
+        let (end_x, end_y) = (ex.floor() as i64, ey.floor() as i64);
+
+        // parametric distance (in units of the full segment) between tile boundaries, per axis
+        let axis = |start: f32, delta: f32, tile: i64| -> (i64, f32, f32) {
+            if delta > 0.0 {
+                (1, (tile as f32 + 1.0 - start) / delta, 1.0 / delta)
+            } else if delta < 0.0 {
+                (-1, (start - tile as f32) / -delta, -1.0 / delta)
+            } else {
+                (0, f32::INFINITY, f32::INFINITY)
+            }
+        };
+        let (step_x, mut next_x, delta_x) = axis(sx, dx, tx);
+        let (step_y, mut next_y, delta_y) = axis(sy, dy, ty);
+
+        let steps = (end_x - tx).abs() + (end_y - ty).abs();
+        for _ in 0..=steps {
+            if self.tile_blocked(tx, ty) {
+                return false;
+            }
+            if tx == end_x && ty == end_y {
+                break;
+            }
+            if next_x < next_y {
+                tx += step_x;
+                next_x += delta_x;
+            } else {
+                ty += step_y;
+                next_y += delta_y;
+            }
+        }
+        true
+    }
+}
+
+static OBSTACLE_MAP: Mutex<ObstacleMap> = Mutex::new(ObstacleMap {
+    boxes: Vec::new(),
+    tiles: Vec::new(),
+    width: 0,
+    height: 0,
+});
+
+/// Register the level's axis-aligned obstacle boxes (replacing earlier ones; a count of 0 clears).
+// Safe C entry point: `boxes_ptr` is null-checked before use; the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn set_obstacle_boxes(boxes_ptr: *const Rect, count: i32) -> DeadAimStatus {
+    ffi_guard(|| {
+        let boxes = unsafe { slice_arg(boxes_ptr, count) }?;
+        let mut map = OBSTACLE_MAP.lock().unwrap_or_else(|e| e.into_inner());
+        map.boxes.clear();
+        map.boxes.extend_from_slice(boxes);
+        Ok(())
+    })
+}
+
+/// Register a row-major `width` x `height` tile occupancy bitmap (non-zero byte = wall), one
+/// byte per 1x1 world-unit tile starting at the origin. The data is copied. A 0 x 0 size clears
+/// the bitmap; `InvalidArgument` for a negative size.
+// Safe C entry point: `tiles_ptr` is null-checked before use; the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn set_obstacle_map(tiles_ptr: *const u8, width: i32, height: i32) -> DeadAimStatus {
+    ffi_guard(|| {
+        let (Ok(w), Ok(h)) = (usize::try_from(width), usize::try_from(height)) else {
+            return Err(DeadAimStatus::InvalidArgument);
+        };
+        let (w, h) = if w == 0 || h == 0 { (0, 0) } else { (w, h) };
+        let tiles = unsafe { slice_arg(tiles_ptr, w * h) }?;
+        let mut map = OBSTACLE_MAP.lock().unwrap_or_else(|e| e.into_inner());
+        map.tiles.clear();
+        map.tiles.extend_from_slice(tiles);
+        map.width = w;
+        map.height = h;
+        Ok(())
+    })
+}
+
+/// Writes whether neither a registered box nor a wall tile blocks the segment (x0,y0)-(x1,y1).
+// Safe C entry point: `out_clear` is null-checked before use; the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn has_line_of_sight(x0: f32, y0: f32, x1: f32, y1: f32, out_clear: *mut bool) -> DeadAimStatus {
+    ffi_guard(|| unsafe { write_out(out_clear, line_of_sight(x0, y0, x1, y1)) })
+}
+
+/// True when neither a registered box nor a wall tile blocks the segment (x0,y0)-(x1,y1).
+pub(crate) fn line_of_sight(x0: f32, y0: f32, x1: f32, y1: f32) -> bool {
+    let map = OBSTACLE_MAP.lock().unwrap_or_else(|e| e.into_inner());
+    segment_clear(x0, y0, x1, y1, &map.boxes) && map.tiles_clear(x0, y0, x1, y1)
+}
+
+/// True when a circle of `radius` at (x, y) overlaps a registered box or wall tile.
+pub(crate) fn circle_blocked(x: f32, y: f32, radius: f32) -> bool {
+    OBSTACLE_MAP.lock().unwrap_or_else(|e| e.into_inner()).circle_blocked(x, y, radius)
+}
+
+/// Writes the nearest alive enemy the player can see through the registered obstacle map, or -1.
+// Safe C entry point: `enemies_ptr`, `out_index` are null-checked before use; the rest of the contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn find_nearest_enemy_in_sight(
+    player_x: f32,
+    player_y: f32,
+    enemies_ptr: *const Enemy,
+    count: i32,
+    out_index: *mut i32,
+) -> DeadAimStatus {
+    ffi_guard(|| {
+        let enemies = unsafe { slice_arg(enemies_ptr, count) }?;
+        let index = nearest_matching(player_x, player_y, enemies, |_, e| line_of_sight(player_x, player_y, e.x, e.y));
+        unsafe { write_out(out_index, index) }
+    })
+}
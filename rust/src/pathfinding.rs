@@ -0,0 +1,172 @@
+// src/pathfinding.rs
+// A* over a host-supplied tile grid, so enemies can route around walls instead of wandering.
+use crate::error::{ffi_guard, slice_arg, slice_arg_mut, write_out, DeadAimStatus, FfiResult};
+use crate::serialize::{Reader, Writer};
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+// Integer step costs (orthogonal, diagonal) keep the search exact and platform independent.
+const COST_STRAIGHT: u32 = 10;
+const COST_DIAGONAL: u32 = 14;
+
+/// Row-major tile grid starting at the world origin; a non-zero tile is a wall (the same
+/// encoding as `set_obstacle_map`).
+#[derive(Clone, Debug, Default)]
+pub(crate) struct NavGrid {
+    tiles: Vec<u8>,
+    width: usize,
+    height: usize,
+    cell_size: f32,
+}
+
+impl NavGrid {
+    /// Copy a `width` x `height` grid of `cell_size` tiles; `NullPointer` for null tiles,
+    /// `InvalidArgument` for bad dimensions.
+    pub(crate) fn from_raw(tiles_ptr: *const u8, width: i32, height: i32, cell_size: f32) -> FfiResult<Self> {
+        if width <= 0 || height <= 0 || !(cell_size.is_finite() && cell_size > 0.0) {
+            return Err(DeadAimStatus::InvalidArgument);
+        }
+        let (width, height) = (width as usize, height as usize);
+        let len = width.checked_mul(height).ok_or(DeadAimStatus::InvalidArgument)?;
+        let tiles = unsafe { slice_arg(tiles_ptr, len) }?.to_vec();
+        Ok(NavGrid { tiles, width, height, cell_size })
+    }
+
+    fn cell_of(&self, x: f32, y: f32) -> Option<(usize, usize)> {
+        let (cx, cy) = ((x / self.cell_size).floor(), (y / self.cell_size).floor());
+        if cx.is_nan() || cy.is_nan() || cx < 0.0 || cy < 0.0 {
+            return None;
+        }
+        let (cx, cy) = (cx as usize, cy as usize);
+        (cx < self.width && cy < self.height).then_some((cx, cy))
+    }
+
+    fn walkable(&self, cx: i64, cy: i64) -> bool {
+        cx >= 0
+            && cy >= 0
+            && (cx as usize) < self.width
+            && (cy as usize) < self.height
+            && self.tiles[cy as usize * self.width + cx as usize] == 0
+    }
+
+    fn center(&self, cell: usize) -> (f32, f32) {
+        let (cx, cy) = (cell % self.width, cell / self.width);
+        ((cx as f32 + 0.5) * self.cell_size, (cy as f32 + 0.5) * self.cell_size)
+    }
+
+    /// Shortest 8-connected path (no cutting wall corners) from (`sx`, `sy`) to (`gx`, `gy`):
+    /// the centers of the tiles after the start tile, ending exactly at the goal. `None` when
+    /// either end is off the grid or walled, or the goal is unreachable.
+    pub(crate) fn find_path(&self, sx: f32, sy: f32, gx: f32, gy: f32) -> Option<Vec<(f32, f32)>> {
+        let (start, goal) = (self.cell_of(sx, sy)?, self.cell_of(gx, gy)?);
+        if !self.walkable(start.0 as i64, start.1 as i64) || !self.walkable(goal.0 as i64, goal.1 as i64) {
+            return None;
+        }
+        let index = |(cx, cy): (usize, usize)| cy * self.width + cx;
+        let heuristic = |cell: usize| {
+            let (dx, dy) = ((cell % self.width).abs_diff(goal.0) as u32, (cell / self.width).abs_diff(goal.1) as u32);
+            COST_STRAIGHT * dx.max(dy) + (COST_DIAGONAL - COST_STRAIGHT) * dx.min(dy)
+        };
+        let (start, goal) = (index(start), index(goal));
+
+        let mut g = vec![u32::MAX; self.tiles.len()];
+        let mut came_from = vec![usize::MAX; self.tiles.len()];
+        // (f, g, cell); ties pop the lower cell index first, so results are deterministic
+        let mut open = BinaryHeap::new();
+        g[start] = 0;
+        open.push(Reverse((heuristic(start), 0u32, start)));
+        while let Some(Reverse((_, cost, cell))) = open.pop() {
+            if cell == goal {
+                break;
+            }
+            if cost > g[cell] {
+                continue;
+            }
+            let (cx, cy) = ((cell % self.width) as i64, (cell / self.width) as i64);
+            for (dx, dy) in [(1, 0), (-1, 0), (0, 1), (0, -1), (1, 1), (1, -1), (-1, 1), (-1, -1)] {
+                let (nx, ny) = (cx + dx, cy + dy);
+                if !self.walkable(nx, ny) {
+                    continue;
+                }
+                let diagonal = dx != 0 && dy != 0;
+                if diagonal && !(self.walkable(cx + dx, cy) && self.walkable(cx, cy + dy)) {
+                    continue;
+                }
+                let next = ny as usize * self.width + nx as usize;
+                let next_cost = cost + if diagonal { COST_DIAGONAL } else { COST_STRAIGHT };
+                if next_cost < g[next] {
+                    g[next] = next_cost;
+                    came_from[next] = cell;
+                    open.push(Reverse((next_cost + heuristic(next), next_cost, next)));
+                }
+            }
+        }
+        if g[goal] == u32::MAX {
+            return None;
+        }
+
+        let mut path = Vec::new();
+        let mut cell = goal;
+        while cell != start {
+            path.push(self.center(cell));
+            cell = came_from[cell];
+        }
+        path.reverse();
+        match path.last_mut() {
+            Some(last) => *last = (gx, gy),
+            None => path.push((gx, gy)),
+        }
+        Some(path)
+    }
+
+    pub(crate) fn write_snapshot(&self, w: &mut Writer) {
+        w.u32(self.width as u32);
+        w.u32(self.height as u32);
+        w.f32(self.cell_size);
+        w.put(&self.tiles);
+    }
+
+    pub(crate) fn read_snapshot(r: &mut Reader) -> Option<Self> {
+        let (width, height, cell_size) = (r.u32()? as i32, r.u32()? as i32, r.f32()?);
+        let len = usize::try_from(width).ok()?.checked_mul(usize::try_from(height).ok()?)?;
+        NavGrid::from_raw(r.bytes(len)?.as_ptr(), width, height, cell_size).ok()
+    }
+}
+
+/// A* from (`start_x`, `start_y`) to (`goal_x`, `goal_y`) over a row-major `width` x `height`
+/// tile grid (non-zero byte = wall, tiles of `cell_size` starting at the origin). Moves are
+/// 8-connected but never cut a wall corner. Writes the waypoints (tile centers after the start
+/// tile, the last one exactly the goal) to `out_x`/`out_y` up to `out_cap`, and the full
+/// waypoint count, or -1 if there is no path, to `out_total`.
+// Safe C entry point: `out_x`, `out_y`, `out_total` are null-checked before use;
+// the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn find_path(
+    tiles_ptr: *const u8,
+    width: i32,
+    height: i32,
+    cell_size: f32,
+    start_x: f32,
+    start_y: f32,
+    goal_x: f32,
+    goal_y: f32,
+    out_x: *mut f32,
+    out_y: *mut f32,
+    out_cap: i32,
+    out_total: *mut i32,
+) -> DeadAimStatus {
+    ffi_guard(|| {
+        let grid = NavGrid::from_raw(tiles_ptr, width, height, cell_size)?;
+        let xs = unsafe { slice_arg_mut(out_x, out_cap) }?;
+        let ys = unsafe { slice_arg_mut(out_y, out_cap) }?;
+        let Some(path) = grid.find_path(start_x, start_y, goal_x, goal_y) else {
+            return unsafe { write_out(out_total, -1) };
+        };
+        for ((ox, oy), &(x, y)) in xs.iter_mut().zip(ys.iter_mut()).zip(&path) {
+            *ox = x;
+            *oy = y;
+        }
+        unsafe { write_out(out_total, path.len() as i32) }
+    })
+}
@@ -0,0 +1,94 @@
+// src/player.rs
+// The world-owned player: input-driven movement that collides with the registered obstacle
+// map, and touch damage from enemies in contact.
+use crate::combat::hit_radius_of;
+use crate::obstacles::circle_blocked;
+use crate::serialize::{Reader, Writer};
+use crate::Enemy;
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Player {
+    pub x: f32,
+    pub y: f32,
+    /// Movement direction from `world_set_player_input`, length <= 1.
+    pub input_x: f32,
+    pub input_y: f32,
+    /// World units per second at full input.
+    pub speed: f32,
+    /// Collision radius against obstacles and enemy hitboxes.
+    pub radius: f32,
+    pub health: f32,
+    pub max_health: f32,
+    /// Damage per second from each enemy touching the player.
+    pub touch_dps: f32,
+}
+
+impl Default for Player {
+    fn default() -> Self {
+        Player {
+            x: 0.0,
+            y: 0.0,
+            input_x: 0.0,
+            input_y: 0.0,
+            speed: 5.0,
+            radius: 0.5,
+            health: 100.0,
+            max_health: 100.0,
+            touch_dps: 0.0,
+        }
+    }
+}
+
+impl Player {
+    /// Move by the current input for `dt` seconds. Each axis is tried separately so the player
+    /// slides along walls instead of sticking to them. A dead player doesn't move.
+    pub(crate) fn step(&mut self, dt: f32) {
+        if self.health <= 0.0 {
+            return;
+        }
+        let (mx, my) = (self.input_x * self.speed * dt, self.input_y * self.speed * dt);
+        if mx != 0.0 && !circle_blocked(self.x + mx, self.y, self.radius) {
+            self.x += mx;
+        }
+        if my != 0.0 && !circle_blocked(self.x, self.y + my, self.radius) {
+            self.y += my;
+        }
+    }
+
+    /// Take `dt` seconds of touch damage from every alive enemy overlapping the player.
+    /// Returns the number of enemies in contact.
+    pub(crate) fn take_touch_damage(&mut self, enemies: &[Enemy], dt: f32) -> usize {
+        let touching = enemies
+            .iter()
+            .filter(|e| {
+                let reach = self.radius + hit_radius_of(e);
+                e.is_alive() && (e.x - self.x).powi(2) + (e.y - self.y).powi(2) <= reach * reach
+            })
+            .count();
+        if self.touch_dps > 0.0 {
+            self.health = (self.health - self.touch_dps * dt * touching as f32).max(0.0);
+        }
+        touching
+    }
+
+    pub(crate) fn write_snapshot(&self, w: &mut Writer) {
+        let stats = [self.speed, self.radius, self.health, self.max_health, self.touch_dps];
+        for v in [self.x, self.y, self.input_x, self.input_y].into_iter().chain(stats) {
+            w.f32(v);
+        }
+    }
+
+    pub(crate) fn read_snapshot(r: &mut Reader) -> Option<Self> {
+        Some(Player {
+            x: r.f32()?,
+            y: r.f32()?,
+            input_x: r.f32()?,
+            input_y: r.f32()?,
+            speed: r.f32()?,
+            radius: r.f32()?,
+            health: r.f32()?,
+            max_health: r.f32()?,
+            touch_dps: r.f32()?,
+        })
+    }
+}
@@ -0,0 +1,233 @@
+// src/projectiles.rs
+// Simulated projectiles (grenades, arrows, slow bullets) that fly under gravity and hit enemies
+// along their path, for weapons hitscan can't model.
+use crate::ballistics::integrate_step;
+use crate::combat::{damage_enemy, first_enemy_on_segment};
+use crate::error::{ffi_guard, slice_arg_mut, write_out, DeadAimStatus};
+use crate::serialize::{Reader, Writer};
+use crate::session::{record_late_hit, record_shot};
+use crate::Enemy;
+use std::sync::Mutex;
+
+// Seconds a projectile flies before it is discarded without hitting anything.
+const PROJECTILE_LIFETIME: f32 = 10.0;
+
+#[derive(Clone, Copy, Debug)]
+struct Projectile {
+    id: i32,
+    x: f32,
+    y: f32,
+    vx: f32,
+    vy: f32,
+    gravity: f32,
+    damage: f32,
+    age: f32,
+}
+
+/// Projectiles in flight and the next id to hand out. Each world owns one (see
+/// `world_spawn_projectile`); the crate-wide set below serves the raw-pointer API.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct ProjectileSet {
+    live: Vec<Projectile>,
+    next_id: i32,
+}
+
+static PROJECTILES: Mutex<ProjectileSet> = Mutex::new(ProjectileSet::new());
+
+impl ProjectileSet {
+    pub(crate) const fn new() -> Self {
+        ProjectileSet { live: Vec::new(), next_id: 0 }
+    }
+
+    /// Launch a projectile (see `spawn_projectile`). Returns its id, or -1 for a non-finite
+    /// position, direction or speed.
+    pub(crate) fn spawn(&mut self, x: f32, y: f32, dir: f32, speed: f32, gravity: f32, damage: f32) -> i32 {
+        if !(x.is_finite() && y.is_finite() && dir.is_finite() && speed.is_finite()) {
+            return -1;
+        }
+        let id = self.next_id;
+        self.next_id = self.next_id.wrapping_add(1).max(0);
+        let gravity = if gravity.is_finite() { gravity } else { 0.0 };
+        record_shot(false);
+        self.live.push(Projectile {
+            id,
+            x,
+            y,
+            vx: dir.cos() * speed,
+            vy: dir.sin() * speed,
+            gravity,
+            damage,
+            age: 0.0,
+        });
+        id
+    }
+
+    /// `tick_projectiles` over a slice, reporting each hit to `on_hit` as it resolves.
+    pub(crate) fn step(&mut self, dt: f32, enemies: &mut [Enemy], mut on_hit: impl FnMut(ProjectileHit)) {
+        self.live.retain_mut(|p| {
+            let (x0, y0) = (p.x, p.y);
+            integrate_step(&mut p.x, &mut p.y, p.vx, &mut p.vy, p.gravity, dt);
+            p.age += dt;
+
+            let Some((i, t)) = first_enemy_on_segment(enemies, x0, y0, p.x, p.y) else {
+                return p.age < PROJECTILE_LIFETIME;
+            };
+            let remaining_health = damage_enemy(enemies, i, p.damage);
+            record_late_hit();
+            on_hit(ProjectileHit {
+                projectile_id: p.id,
+                enemy_index: i as i32,
+                x: x0 + (p.x - x0) * t,
+                y: y0 + (p.y - y0) * t,
+                remaining_health,
+            });
+            false
+        });
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.live.len()
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.live.clear();
+    }
+
+    /// The next id, then (id, [x, y, vx, vy, gravity, damage, age]) of every projectile in
+    /// flight, in launch order.
+    pub(crate) fn state(&self) -> (i32, impl Iterator<Item = (i32, [f32; 7])> + '_) {
+        let flights = self.live.iter().map(|p| (p.id, [p.x, p.y, p.vx, p.vy, p.gravity, p.damage, p.age]));
+        (self.next_id, flights)
+    }
+
+    pub(crate) fn write_snapshot(&self, w: &mut Writer) {
+        let (next_id, flights) = self.state();
+        w.i32(next_id);
+        w.u32(self.live.len() as u32);
+        for (id, values) in flights {
+            w.i32(id);
+            for v in values {
+                w.f32(v);
+            }
+        }
+    }
+
+    pub(crate) fn read_snapshot(r: &mut Reader) -> Option<Self> {
+        let next_id = r.i32()?;
+        let count = r.u32()?;
+        let live = (0..count)
+            .map(|_| {
+                let id = r.i32()?;
+                let mut values = [0.0; 7];
+                for v in &mut values {
+                    *v = r.f32()?;
+                }
+                let [x, y, vx, vy, gravity, damage, age] = values;
+                Some(Projectile { id, x, y, vx, vy, gravity, damage, age })
+            })
+            .collect::<Option<Vec<_>>>()?;
+        (next_id >= 0).then_some(ProjectileSet { live, next_id })
+    }
+}
+
+/// One resolved projectile impact reported by `tick_projectiles`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct ProjectileHit {
+    /// Id returned by `spawn_projectile`.
+    pub projectile_id: i32,
+    /// Index of the enemy that was hit.
+    pub enemy_index: i32,
+    /// Impact point.
+    pub x: f32,
+    pub y: f32,
+    /// The enemy's health after the hit (0 once killed).
+    pub remaining_health: f32,
+}
+
+/// Launch a projectile from (`x`, `y`) at angle `dir` (radians) with `speed` world units per
+/// second; `gravity` pulls it toward -y (0 flies straight). It deals `damage` to the first enemy
+/// it touches; the launch and any hit count toward the running match's accuracy. Writes the
+/// projectile's id to `out_id`; `InvalidArgument` for a non-finite position, direction or speed.
+// Safe C entry point: `out_id` is null-checked before use; the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn spawn_projectile(
+    x: f32,
+    y: f32,
+    dir: f32,
+    speed: f32,
+    gravity: f32,
+    damage: f32,
+    out_id: *mut i32,
+) -> DeadAimStatus {
+    ffi_guard(|| {
+        if out_id.is_null() {
+            return Err(DeadAimStatus::NullPointer);
+        }
+        let id = PROJECTILES.lock().unwrap_or_else(|e| e.into_inner()).spawn(x, y, dir, speed, gravity, damage);
+        if id < 0 {
+            return Err(DeadAimStatus::InvalidArgument);
+        }
+        unsafe { write_out(out_id, id) }
+    })
+}
+
+/// Advance every projectile by `dt` seconds and resolve hits against the alive enemies: a
+/// projectile hits the first hitbox (see `set_enemy_hit_radius`) its path crosses this step,
+/// damages that enemy through the regular damage path and is removed. Projectiles older than
+/// 10 seconds are discarded. Hits are written to `out_hits` (capacity `out_cap`) in the order
+/// they resolved, and the total number of hits, which may exceed `out_cap`, to `out_total`.
+/// `InvalidArgument` for a non-positive or NaN `dt`.
+// Safe C entry point: `enemies_ptr`, `out_hits`, `out_total` are null-checked before use;
+// the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn tick_projectiles(
+    dt: f32,
+    enemies_ptr: *mut Enemy,
+    count: i32,
+    out_hits: *mut ProjectileHit,
+    out_cap: i32,
+    out_total: *mut i32,
+) -> DeadAimStatus {
+    ffi_guard(|| {
+        let enemies = unsafe { slice_arg_mut(enemies_ptr, count) }?;
+        let out = unsafe { slice_arg_mut(out_hits, out_cap) }?;
+        if out_total.is_null() {
+            return Err(DeadAimStatus::NullPointer);
+        }
+        if dt.is_nan() || dt <= 0.0 {
+            return Err(DeadAimStatus::InvalidArgument);
+        }
+        let mut hits = 0usize;
+        let mut set = PROJECTILES.lock().unwrap_or_else(|e| e.into_inner());
+        set.step(dt, enemies, |hit| {
+            if let Some(o) = out.get_mut(hits) {
+                *o = hit;
+            }
+            hits += 1;
+        });
+        unsafe { write_out(out_total, hits as i32) }
+    })
+}
+
+/// Writes the number of projectiles currently in flight.
+// Safe C entry point: `out_count` is null-checked before use; the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn projectile_count(out_count: *mut i32) -> DeadAimStatus {
+    ffi_guard(|| {
+        let len = PROJECTILES.lock().unwrap_or_else(|e| e.into_inner()).len() as i32;
+        unsafe { write_out(out_count, len) }
+    })
+}
+
+/// Discard every projectile in flight (e.g. on level change).
+#[no_mangle]
+pub extern "C" fn clear_projectiles() -> DeadAimStatus {
+    ffi_guard(|| {
+        PROJECTILES.lock().unwrap_or_else(|e| e.into_inner()).clear();
+        Ok(())
+    })
+}
@@ -0,0 +1,274 @@
+// src/registry.rs
+// Worlds owned by the library and addressed by id, for servers running many matches in one
+// process. Every `world_*` call has an `_by_id` counterpart taking the id in place of the
+// handle. Each world has its own lock and its own match state (events, match session, scores,
+// safe zones, heatmap and tracked wave; see `match_state`), and the match-level calls
+// (`drain_events`, `start_match`, `score_kill`, ...) have `_by_id` counterparts acting on that
+// world's copy, so matches on different threads never wait on each other. Crate-wide settings
+// (obstacles, enemy types, flocking, scoring rules, callbacks, ...) are shared by all worlds and
+// only read during a tick. Callbacks a call triggers fire once the world's lock is released, so
+// they may call back into the same world.
+use crate::ai::AiConfig;
+use crate::callbacks::deferred;
+use crate::combat::{in_safe_zone, set_safe_zones};
+use crate::error::{check, ffi_guard, slice_arg, write_out, DeadAimStatus, FfiResult};
+use crate::events::{drain_events, pending_event_count, take_dropped_event_count, GameEvent};
+use crate::heatmap::{clear_heatmap, get_heatmap, heatmap_record_player_death, HeatmapCell};
+use crate::obstacles::Rect;
+use crate::scoring::{get_score_summary, reset_scores, score_kill, score_player_death, ScoreSummary};
+use crate::session::{end_match, match_is_running, pause_match, resume_match, start_match, MatchConfig, MatchResult};
+use crate::status::StatusKind;
+use crate::steering::Behavior;
+use crate::world::*;
+use crate::Enemy;
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+struct Registry {
+    next_id: u32,
+    worlds: BTreeMap<u32, Arc<Mutex<World>>>,
+}
+
+static REGISTRY: Mutex<Registry> = Mutex::new(Registry { next_id: 1, worlds: BTreeMap::new() });
+
+/// Register `world` and write its id to `out_id`; `InvalidArgument` once every id is spent.
+fn register(world: World, out_id: *mut u32) -> FfiResult {
+    if out_id.is_null() {
+        return Err(DeadAimStatus::NullPointer);
+    }
+    let mut registry = REGISTRY.lock().unwrap_or_else(|e| e.into_inner());
+    let id = registry.next_id;
+    // ids are never reused, so a stale id can't reach a newer world
+    if id == u32::MAX {
+        return Err(DeadAimStatus::InvalidArgument);
+    }
+    registry.next_id = id + 1;
+    registry.worlds.insert(id, Arc::new(Mutex::new(world)));
+    unsafe { write_out(out_id, id) }
+}
+
+fn lookup(id: u32) -> Option<Arc<Mutex<World>>> {
+    REGISTRY.lock().unwrap_or_else(|e| e.into_inner()).worlds.get(&id).cloned()
+}
+
+/// Run `f` on world `id` under its lock, with its match state installed, and pass on its status;
+/// `UnknownId` for an unknown id. Callbacks `f` triggers fire after the lock is released.
+fn with_world(id: u32, f: impl FnOnce(&mut World) -> DeadAimStatus) -> FfiResult {
+    let world = lookup(id).ok_or(DeadAimStatus::UnknownId)?;
+    check(deferred(|| world.lock().unwrap_or_else(|e| e.into_inner()).with_match_state(f)))
+}
+
+/// Create a registered world holding up to `capacity` enemies (see `world_create`) and write its
+/// id (never reused) to `out_id`. `InvalidArgument` for a negative capacity.
+#[no_mangle]
+pub extern "C" fn create_world(capacity: i32, out_id: *mut u32) -> DeadAimStatus {
+    ffi_guard(|| {
+        let capacity = usize::try_from(capacity).map_err(|_| DeadAimStatus::InvalidArgument)?;
+        register(new_world(capacity), out_id)
+    })
+}
+
+/// Register a world restored from a `serialize_world` snapshot and write its id to `out_id`.
+/// `InvalidData` for malformed input.
+// Safe C entry point: `in_ptr` is null-checked before use; the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn create_world_from_snapshot(in_ptr: *const u8, in_len: usize, out_id: *mut u32) -> DeadAimStatus {
+    ffi_guard(|| {
+        let bytes = unsafe { slice_arg(in_ptr, in_len) }?;
+        register(World::read_snapshot(bytes).ok_or(DeadAimStatus::InvalidData)?, out_id)
+    })
+}
+
+/// Register an independent copy of world `id` (see `world_clone`) and write the copy's id to
+/// `out_id`. `UnknownId` for an unknown id.
+#[no_mangle]
+pub extern "C" fn clone_world(id: u32, out_id: *mut u32) -> DeadAimStatus {
+    ffi_guard(|| {
+        // copied without `with_world`, whose installed match state would leave the copy's empty
+        let world = lookup(id).ok_or(DeadAimStatus::UnknownId)?;
+        let copy = world.lock().unwrap_or_else(|e| e.into_inner()).clone();
+        register(copy, out_id)
+    })
+}
+
+/// Unregister and free world `id`; a call already running on it finishes first. `UnknownId` for
+/// an unknown id.
+#[no_mangle]
+pub extern "C" fn destroy_world(id: u32) -> DeadAimStatus {
+    ffi_guard(|| {
+        let removed = REGISTRY.lock().unwrap_or_else(|e| e.into_inner()).worlds.remove(&id);
+        removed.map(drop).ok_or(DeadAimStatus::UnknownId)
+    })
+}
+
+/// Writes the number of registered worlds.
+// Safe C entry point: `out_count` is null-checked before use; the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn world_registry_count(out_count: *mut i32) -> DeadAimStatus {
+    ffi_guard(|| {
+        let count = REGISTRY.lock().unwrap_or_else(|e| e.into_inner()).worlds.len() as i32;
+        unsafe { write_out(out_count, count) }
+    })
+}
+
+/// `encode_delta` against registered world `id`.
+#[no_mangle]
+pub extern "C" fn encode_delta_by_id(
+    prev_ptr: *const u8,
+    prev_len: usize,
+    id: u32,
+    out_ptr: *mut u8,
+    out_cap: usize,
+    out_written: *mut usize,
+) -> DeadAimStatus {
+    ffi_guard(|| with_world(id, |world| encode_delta(prev_ptr, prev_len, world, out_ptr, out_cap, out_written)))
+}
+
+// `$by_id(id, args...)` locks world `id` and forwards to `$world_fn(world, args...)`.
+macro_rules! by_id {
+    ($($by_id:ident => $world_fn:ident($($arg:ident: $ty:ty),* $(,)?);)*) => {
+        $(
+            #[doc = concat!("`", stringify!($world_fn), "` on registered world `id`; `UnknownId` for an unknown id.")]
+            #[no_mangle]
+            pub extern "C" fn $by_id(id: u32, $($arg: $ty),*) -> DeadAimStatus {
+                ffi_guard(|| with_world(id, |world| $world_fn(world, $($arg),*)))
+            }
+        )*
+    };
+}
+
+// Every `world_*` entry point except the lifecycle ones above and `world_enemy_buffer_ptr`,
+// whose pointer would outlive the lock.
+by_id! {
+    world_set_grid_cell_size_by_id => world_set_grid_cell_size(cell_size: f32);
+    world_add_enemy_by_id => world_add_enemy(x: f32, y: f32, out_index: *mut i32);
+    world_enemy_count_by_id => world_enemy_count(out_count: *mut i32);
+    world_get_enemy_by_id => world_get_enemy(index: i32, out: *mut Enemy);
+    world_find_nearest_enemy_by_id => world_find_nearest_enemy(player_x: f32, player_y: f32, out_index: *mut i32);
+    world_shoot_enemy_by_id => world_shoot_enemy(index: i32);
+    world_kill_enemy_with_respawn_by_id => world_kill_enemy_with_respawn(index: i32, delay_seconds: f32);
+    world_set_respawn_points_by_id => world_set_respawn_points(points_ptr: *const f32, point_count: i32);
+    world_respawn_remaining_by_id => world_respawn_remaining(index: i32, out_seconds: *mut f32);
+    world_set_rng_seed_by_id => world_set_rng_seed(seed: u64);
+    world_move_enemies_randomly_by_id => world_move_enemies_randomly(speed: f32);
+    world_spawn_projectile_by_id => world_spawn_projectile(
+        x: f32,
+        y: f32,
+        dir: f32,
+        speed: f32,
+        gravity: f32,
+        damage: f32,
+        out_id: *mut i32,
+    );
+    world_projectile_count_by_id => world_projectile_count(out_count: *mut i32);
+    world_set_tick_step_by_id => world_set_tick_step(step_seconds: f32);
+    world_set_move_speed_by_id => world_set_move_speed(units_per_second: f32);
+    world_set_summoning_by_id => world_set_summoning(minions_per_summon: i32, summon_radius: f32);
+    world_set_enemy_collision_by_id => world_set_enemy_collision(enabled: bool);
+    world_set_nav_grid_by_id => world_set_nav_grid(tiles_ptr: *const u8, width: i32, height: i32, cell_size: f32);
+    world_enemy_path_to_by_id => world_enemy_path_to(index: i32, goal_x: f32, goal_y: f32, out_len: *mut i32);
+    world_set_patrol_route_by_id => world_set_patrol_route(
+        index: i32,
+        points_ptr: *const f32,
+        point_count: i32,
+        looping: bool,
+        tolerance: f32,
+    );
+    world_set_player_position_by_id => world_set_player_position(x: f32, y: f32);
+    world_set_player_input_by_id => world_set_player_input(dx: f32, dy: f32);
+    world_get_player_position_by_id => world_get_player_position(out_x: *mut f32, out_y: *mut f32);
+    world_configure_player_by_id => world_configure_player(speed: f32, radius: f32, max_health: f32, touch_dps: f32);
+    world_get_player_health_by_id => world_get_player_health(out_health: *mut f32);
+    world_set_ai_config_by_id => world_set_ai_config(config: *const AiConfig);
+    world_get_enemy_ai_state_by_id => world_get_enemy_ai_state(index: i32, out_state: *mut i32);
+    world_load_behavior_tree_by_id => world_load_behavior_tree(bytes_ptr: *const u8, len: usize, out_tree_id: *mut i32);
+    world_bind_behavior_tree_by_id => world_bind_behavior_tree(index: i32, tree_id: i32);
+    world_configure_wave_by_id => world_configure_wave(
+        wave_index: i32,
+        enemy_count: i32,
+        enemy_type: i32,
+        spawn_interval: f32,
+        spawn_points_ptr: *const f32,
+        point_count: i32,
+    );
+    world_start_wave_by_id => world_start_wave(wave_index: i32);
+    world_current_wave_by_id => world_current_wave(out_wave: *mut i32);
+    world_tick_spawner_by_id => world_tick_spawner(dt: f32, out_cleared: *mut i32);
+    world_apply_status_by_id => world_apply_status(index: i32, kind: StatusKind, duration: f32, magnitude: f32);
+    world_status_mask_by_id => world_status_mask(index: i32, out_mask: *mut u32);
+    world_get_status_by_id => world_get_status(
+        index: i32,
+        kind: StatusKind,
+        out_active: *mut bool,
+        out_remaining: *mut f32,
+        out_magnitude: *mut f32,
+    );
+    world_set_enemy_behavior_by_id => world_set_enemy_behavior(index: i32, behavior: Behavior);
+    world_tick_by_id => world_tick(dt_seconds: f32, out_steps: *mut i32);
+    world_lock_target_by_id => world_lock_target(criteria: *const LockCriteria, out_id: *mut i32);
+    world_unlock_target_by_id => world_unlock_target();
+    world_get_locked_target_position_by_id => world_get_locked_target_position(
+        out_locked: *mut bool,
+        out_x: *mut f32,
+        out_y: *mut f32,
+    );
+    serialized_world_size_by_id => serialized_world_size(out_size: *mut usize);
+    serialize_world_by_id => serialize_world(out_ptr: *mut u8, out_cap: usize, out_written: *mut usize);
+    world_state_hash_by_id => world_state_hash(out_hash: *mut u64);
+    apply_delta_by_id => apply_delta(in_ptr: *const u8, in_len: usize);
+    world_record_history_by_id => world_record_history(now_ms: u64);
+    world_fire_hitscan_at_time_by_id => world_fire_hitscan_at_time(
+        origin_x: f32,
+        origin_y: f32,
+        dir_x: f32,
+        dir_y: f32,
+        max_range: f32,
+        client_time_ms: u64,
+        out_index: *mut i32,
+    );
+    world_interpolation_alpha_by_id => world_interpolation_alpha(out_alpha: *mut f32);
+    world_get_interpolated_positions_by_id => world_get_interpolated_positions(
+        alpha: f32,
+        out_xy: *mut f32,
+        out_cap: i32,
+        out_total: *mut i32,
+    );
+}
+
+// `$by_id(id, args...)` runs the crate-wide `$match_fn(args...)` against world `id`'s own match
+// state.
+macro_rules! match_by_id {
+    ($($by_id:ident => $match_fn:ident($($arg:ident: $ty:ty),* $(,)?);)*) => {
+        $(
+            #[doc = concat!(
+                "`", stringify!($match_fn), "` on registered world `id`'s match state; `UnknownId` for an unknown id."
+            )]
+            #[no_mangle]
+            pub extern "C" fn $by_id(id: u32, $($arg: $ty),*) -> DeadAimStatus {
+                ffi_guard(|| with_world(id, |_| $match_fn($($arg),*)))
+            }
+        )*
+    };
+}
+
+match_by_id! {
+    drain_events_by_id => drain_events(out_ptr: *mut GameEvent, cap: i32, out_written: *mut i32);
+    pending_event_count_by_id => pending_event_count(out_count: *mut i32);
+    take_dropped_event_count_by_id => take_dropped_event_count(out_dropped: *mut u64);
+    start_match_by_id => start_match(config: *const MatchConfig, now_ms: u64);
+    pause_match_by_id => pause_match(now_ms: u64);
+    resume_match_by_id => resume_match(now_ms: u64);
+    end_match_by_id => end_match(now_ms: u64, out_result: *mut MatchResult);
+    match_is_running_by_id => match_is_running(now_ms: u64, out_running: *mut bool);
+    score_kill_by_id => score_kill(player_id: i32, type_id: i32, headshot: bool, now_ms: u64, out_points: *mut i64);
+    score_player_death_by_id => score_player_death(player_id: i32);
+    get_score_summary_by_id => get_score_summary(player_id: i32, out: *mut ScoreSummary);
+    reset_scores_by_id => reset_scores();
+    set_safe_zones_by_id => set_safe_zones(zones_ptr: *const Rect, count: i32);
+    in_safe_zone_by_id => in_safe_zone(x: f32, y: f32, out_inside: *mut bool);
+    heatmap_record_player_death_by_id => heatmap_record_player_death(x: f32, y: f32);
+    get_heatmap_by_id => get_heatmap(cell_size: f32, out_buffer: *mut HeatmapCell, out_len: i32, out_total: *mut i32);
+    clear_heatmap_by_id => clear_heatmap();
+}
@@ -0,0 +1,379 @@
+// src/replay.rs
+// Replays: the world's starting state (including its RNG seed) plus every command applied to it
+// afterwards, so a match can be re-simulated exactly for kill-cams or checked server-side.
+//
+// Saved layout (little endian): magic "DARP" | version u16 | reserved u16
+//   | start snapshot len u32 | `serialize_world` bytes | command count u32 | commands, each a
+//   tag u8 then: tick (dt f32) | player input (dx f32 | dy f32) | player position (x f32 | y f32)
+//   | shoot (index i32) | spawn projectile (x, y, dir, speed, gravity, damage f32)
+//
+// Only world state is captured: crate-wide settings (obstacles, enemy types, ...) must match
+// between recording and playback.
+use crate::error::{arg_mut, arg_ref, check, ffi_guard, slice_arg, write_opt, write_out, DeadAimStatus, FfiResult};
+use crate::serialize::{Reader, Writer};
+use crate::world::{
+    copy_out, world_set_player_input, world_set_player_position, world_set_rng_seed, world_shoot_enemy,
+    world_spawn_projectile, world_tick, World,
+};
+
+const REPLAY_MAGIC: &[u8; 4] = b"DARP";
+const REPLAY_FORMAT_VERSION: u16 = 1;
+
+#[derive(Clone, Copy, Debug)]
+enum Command {
+    Tick(f32),
+    PlayerInput(f32, f32),
+    PlayerPosition(f32, f32),
+    Shoot(i32),
+    SpawnProjectile([f32; 6]),
+}
+
+impl Command {
+    /// Apply to `world` exactly as the recording did.
+    fn apply(self, world: &mut World) -> FfiResult {
+        check(match self {
+            Command::Tick(dt) => world_tick(world, dt, std::ptr::null_mut()),
+            Command::PlayerInput(dx, dy) => world_set_player_input(world, dx, dy),
+            Command::PlayerPosition(x, y) => world_set_player_position(world, x, y),
+            Command::Shoot(index) => world_shoot_enemy(world, index),
+            Command::SpawnProjectile([x, y, dir, speed, gravity, damage]) => {
+                world_spawn_projectile(world, x, y, dir, speed, gravity, damage, std::ptr::null_mut())
+            }
+        })
+    }
+}
+
+/// A recorded command stream and its playback cursor. Create with `replay_begin` or
+/// `replay_load`; free with `replay_free`.
+pub struct Replay {
+    start: Vec<u8>,
+    commands: Vec<Command>,
+    /// Re-simulated world for `replay_step`, and how many commands it has been fed.
+    playback: Option<Box<World>>,
+    cursor: usize,
+}
+
+impl Replay {
+    /// Apply `command` and keep it if it succeeded; a failed command changed nothing, so it is
+    /// left out of the stream.
+    fn record(&mut self, world: &mut World, command: Command) -> FfiResult {
+        command.apply(world)?;
+        self.commands.push(command);
+        Ok(())
+    }
+
+    /// Run the whole stream on a fresh copy of the starting state.
+    fn resimulate(&self) -> FfiResult<Box<World>> {
+        let mut world = Box::new(World::read_snapshot(&self.start).ok_or(DeadAimStatus::InvalidData)?);
+        for &command in &self.commands {
+            command.apply(&mut world)?;
+        }
+        Ok(world)
+    }
+}
+
+/// Reseed `world` with `seed`, start recording it and write the replay to `out_replay`: the
+/// replay keeps a snapshot of the world's state now, and every `replay_record_*` call applies a
+/// command to the world and appends it.
+// Safe C entry point: `world`, `out_replay` are null-checked before use; the rest of the contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn replay_begin(world: *mut World, seed: u64, out_replay: *mut *mut Replay) -> DeadAimStatus {
+    ffi_guard(|| {
+        let world = unsafe { arg_mut(world) }?;
+        if out_replay.is_null() {
+            return Err(DeadAimStatus::NullPointer);
+        }
+        check(world_set_rng_seed(world, seed))?;
+        let replay = Replay { start: world.write_snapshot(), commands: Vec::new(), playback: None, cursor: 0 };
+        unsafe { write_out(out_replay, Box::into_raw(Box::new(replay))) }
+    })
+}
+
+/// Free a replay.
+// Safe C entry point: `replay` is null-checked before use; the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn replay_free(replay: *mut Replay) -> DeadAimStatus {
+    ffi_guard(|| {
+        if replay.is_null() {
+            return Err(DeadAimStatus::NullPointer);
+        }
+        drop(unsafe { Box::from_raw(replay) });
+        Ok(())
+    })
+}
+
+/// `world_tick(world, dt_seconds, out_steps)`, recorded.
+// Safe C entry point: `replay`, `world` are null-checked before use; the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn replay_record_tick(
+    replay: *mut Replay,
+    world: *mut World,
+    dt_seconds: f32,
+    out_steps: *mut i32,
+) -> DeadAimStatus {
+    ffi_guard(|| {
+        let replay = unsafe { arg_mut(replay) }?;
+        let world = unsafe { arg_mut(world) }?;
+        check(world_tick(world, dt_seconds, out_steps))?;
+        replay.commands.push(Command::Tick(dt_seconds));
+        Ok(())
+    })
+}
+
+/// `world_set_player_input(world, dx, dy)`, recorded.
+// Safe C entry point: `replay`, `world` are null-checked before use; the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn replay_record_player_input(
+    replay: *mut Replay,
+    world: *mut World,
+    dx: f32,
+    dy: f32,
+) -> DeadAimStatus {
+    ffi_guard(|| unsafe { arg_mut(replay) }?.record(unsafe { arg_mut(world) }?, Command::PlayerInput(dx, dy)))
+}
+
+/// `world_set_player_position(world, x, y)`, recorded.
+// Safe C entry point: `replay`, `world` are null-checked before use; the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn replay_record_player_position(
+    replay: *mut Replay,
+    world: *mut World,
+    x: f32,
+    y: f32,
+) -> DeadAimStatus {
+    ffi_guard(|| unsafe { arg_mut(replay) }?.record(unsafe { arg_mut(world) }?, Command::PlayerPosition(x, y)))
+}
+
+/// `world_shoot_enemy(world, index)`, recorded.
+// Safe C entry point: `replay`, `world` are null-checked before use; the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn replay_record_shoot(replay: *mut Replay, world: *mut World, index: i32) -> DeadAimStatus {
+    ffi_guard(|| unsafe { arg_mut(replay) }?.record(unsafe { arg_mut(world) }?, Command::Shoot(index)))
+}
+
+/// `world_spawn_projectile(world, ..., out_id)`, recorded.
+// Safe C entry point: `replay`, `world` are null-checked before use; the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn replay_record_spawn_projectile(
+    replay: *mut Replay,
+    world: *mut World,
+    x: f32,
+    y: f32,
+    dir: f32,
+    speed: f32,
+    gravity: f32,
+    damage: f32,
+    out_id: *mut i32,
+) -> DeadAimStatus {
+    ffi_guard(|| {
+        let replay = unsafe { arg_mut(replay) }?;
+        let world = unsafe { arg_mut(world) }?;
+        check(world_spawn_projectile(world, x, y, dir, speed, gravity, damage, out_id))?;
+        replay.commands.push(Command::SpawnProjectile([x, y, dir, speed, gravity, damage]));
+        Ok(())
+    })
+}
+
+/// Writes the number of recorded commands.
+// Safe C entry point: `replay`, `out_count` are null-checked before use; the rest of the contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn replay_command_count(replay: *const Replay, out_count: *mut i32) -> DeadAimStatus {
+    ffi_guard(|| {
+        let replay = unsafe { arg_ref(replay) }?;
+        unsafe { write_out(out_count, replay.commands.len() as i32) }
+    })
+}
+
+/// Advance playback by one command, starting from the recorded starting state on the first call
+/// (or after `replay_rewind`). Writes the index of the command applied, or -1 once the stream
+/// is exhausted, to `out_index` (may be null). A command that fails on playback (crate-wide
+/// settings differ from the recording) returns its status. The playback world is readable via
+/// `replay_playback_world`.
+// Safe C entry point: `replay`, `out_index` are null-checked before use; the rest of the contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn replay_step(replay: *mut Replay, out_index: *mut i32) -> DeadAimStatus {
+    ffi_guard(|| {
+        let replay = unsafe { arg_mut(replay) }?;
+        let Some(&command) = replay.commands.get(replay.cursor) else {
+            unsafe { write_opt(out_index, -1) };
+            return Ok(());
+        };
+        if replay.playback.is_none() {
+            let world = World::read_snapshot(&replay.start).ok_or(DeadAimStatus::InvalidData)?;
+            replay.playback = Some(Box::new(world));
+        }
+        replay.cursor += 1;
+        unsafe { write_opt(out_index, replay.cursor as i32 - 1) };
+        match replay.playback.as_deref_mut() {
+            Some(world) => command.apply(world),
+            None => Ok(()),
+        }
+    })
+}
+
+/// Restart playback from the beginning; the next `replay_step` rebuilds the starting state.
+// Safe C entry point: `replay` is null-checked before use; the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn replay_rewind(replay: *mut Replay) -> DeadAimStatus {
+    ffi_guard(|| {
+        let replay = unsafe { arg_mut(replay) }?;
+        replay.playback = None;
+        replay.cursor = 0;
+        Ok(())
+    })
+}
+
+/// Writes the world playback is advancing (owned by the replay; valid until the next
+/// `replay_step`, `replay_rewind` or `replay_free`), or null before the first step.
+// Safe C entry point: `replay`, `out_world` are null-checked before use; the rest of the contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn replay_playback_world(replay: *mut Replay, out_world: *mut *mut World) -> DeadAimStatus {
+    ffi_guard(|| {
+        let replay = unsafe { arg_mut(replay) }?;
+        let world = replay.playback.as_deref_mut().map_or(std::ptr::null_mut(), |w| w as *mut World);
+        unsafe { write_out(out_world, world) }
+    })
+}
+
+/// Re-simulate the whole recording from its starting state and write whether it ends in a world
+/// whose `world_state_hash` is `expected_hash` (e.g. the hash a client reported at match end) to
+/// `out_matches`. A command that fails on playback returns its status.
+// Safe C entry point: `replay`, `out_matches` are null-checked before use; the rest of the contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn replay_verify_hash(
+    replay: *const Replay,
+    expected_hash: u64,
+    out_matches: *mut bool,
+) -> DeadAimStatus {
+    ffi_guard(|| {
+        let replay = unsafe { arg_ref(replay) }?;
+        if out_matches.is_null() {
+            return Err(DeadAimStatus::NullPointer);
+        }
+        let world = replay.resimulate()?;
+        unsafe { write_out(out_matches, world.state_hash() == expected_hash) }
+    })
+}
+
+// ---------- Save / load ----------
+
+fn write_replay(replay: &Replay) -> Vec<u8> {
+    let mut w = Writer::new(false);
+    w.put(REPLAY_MAGIC);
+    w.u16(REPLAY_FORMAT_VERSION);
+    w.u16(0);
+    w.u32(replay.start.len() as u32);
+    w.put(&replay.start);
+    w.u32(replay.commands.len() as u32);
+    for &command in &replay.commands {
+        match command {
+            Command::Tick(dt) => {
+                w.u8(0);
+                w.f32(dt);
+            }
+            Command::PlayerInput(dx, dy) => {
+                w.u8(1);
+                w.f32(dx);
+                w.f32(dy);
+            }
+            Command::PlayerPosition(x, y) => {
+                w.u8(2);
+                w.f32(x);
+                w.f32(y);
+            }
+            Command::Shoot(index) => {
+                w.u8(3);
+                w.i32(index);
+            }
+            Command::SpawnProjectile(args) => {
+                w.u8(4);
+                for v in args {
+                    w.f32(v);
+                }
+            }
+        }
+    }
+    w.buf
+}
+
+fn read_replay(bytes: &[u8]) -> Option<Replay> {
+    let mut r = Reader::new(bytes, false);
+    if r.take::<4>().as_ref() != Some(REPLAY_MAGIC) || r.u16()? != REPLAY_FORMAT_VERSION {
+        return None;
+    }
+    r.u16()?;
+    let start_len = r.u32()? as usize;
+    let start = r.bytes(start_len)?.to_vec();
+    World::read_snapshot(&start)?;
+    let count = r.u32()?;
+    let commands = (0..count)
+        .map(|_| {
+            Some(match r.u8()? {
+                0 => Command::Tick(r.f32()?),
+                1 => Command::PlayerInput(r.f32()?, r.f32()?),
+                2 => Command::PlayerPosition(r.f32()?, r.f32()?),
+                3 => Command::Shoot(r.i32()?),
+                4 => Command::SpawnProjectile([r.f32()?, r.f32()?, r.f32()?, r.f32()?, r.f32()?, r.f32()?]),
+                _ => return None,
+            })
+        })
+        .collect::<Option<Vec<_>>>()?;
+    r.at_end().then_some(Replay { start, commands, playback: None, cursor: 0 })
+}
+
+/// Writes the bytes `replay_save` needs.
+// Safe C entry point: `replay`, `out_size` are null-checked before use; the rest of the contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn replay_saved_size(replay: *const Replay, out_size: *mut usize) -> DeadAimStatus {
+    ffi_guard(|| {
+        let replay = unsafe { arg_ref(replay) }?;
+        unsafe { write_out(out_size, write_replay(replay).len()) }
+    })
+}
+
+/// Write the recording (layout above) to `out_ptr`, e.g. to upload with a score for
+/// validation, and the bytes written to `out_written`. `BufferTooSmall` if `out_cap` is smaller
+/// than `replay_saved_size`.
+// Safe C entry point: `replay` is null-checked before use; the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn replay_save(
+    replay: *const Replay,
+    out_ptr: *mut u8,
+    out_cap: usize,
+    out_written: *mut usize,
+) -> DeadAimStatus {
+    ffi_guard(|| {
+        let replay = unsafe { arg_ref(replay) }?;
+        copy_out(&write_replay(replay), out_ptr, out_cap, out_written)
+    })
+}
+
+/// Load a recording written by `replay_save`, ready for playback or verification, and write it
+/// to `out_replay`. `InvalidData` for malformed input. Free with `replay_free`.
+// Safe C entry point: `in_ptr`, `out_replay` are null-checked before use; the rest of the contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn replay_load(in_ptr: *const u8, in_len: usize, out_replay: *mut *mut Replay) -> DeadAimStatus {
+    ffi_guard(|| {
+        let bytes = unsafe { slice_arg(in_ptr, in_len) }?;
+        if out_replay.is_null() {
+            return Err(DeadAimStatus::NullPointer);
+        }
+        let replay = read_replay(bytes).ok_or(DeadAimStatus::InvalidData)?;
+        unsafe { write_out(out_replay, Box::into_raw(Box::new(replay))) }
+    })
+}
@@ -0,0 +1,193 @@
+// src/rewards.rs
+// Reward hooks: forward payouts to the frontend wallet integration.
+use crate::enemy_types::enemy_type;
+use crate::error::{ffi_guard, slice_arg, slice_arg_mut, DeadAimStatus, FfiResult};
+use crate::events::{enemy_killed, reward_queued};
+use crate::session::{match_running, match_unpaused};
+use crate::spawner::check_wave_cleared;
+use crate::{js_log, js_mint_nft, js_send_token, Enemy};
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+/// Sliding-window payout counter; `max_per_window == 0` disables limiting.
+struct RateLimiter {
+    max_per_window: u32,
+    window_ms: u64,
+    dispatched: VecDeque<u64>,
+}
+
+impl RateLimiter {
+    /// Record a dispatch at `now_ms` if the window has room; false means reject.
+    fn try_acquire(&mut self, now_ms: u64) -> bool {
+        if self.max_per_window == 0 {
+            return true;
+        }
+        while let Some(&t) = self.dispatched.front() {
+            if now_ms.saturating_sub(t) >= self.window_ms {
+                self.dispatched.pop_front();
+            } else {
+                break;
+            }
+        }
+        if self.dispatched.len() >= self.max_per_window as usize {
+            return false;
+        }
+        self.dispatched.push_back(now_ms);
+        true
+    }
+}
+
+static RATE_LIMITER: Mutex<RateLimiter> = Mutex::new(RateLimiter {
+    max_per_window: 0,
+    window_ms: 0,
+    dispatched: VecDeque::new(),
+});
+
+/// Allow at most `max_per_window` payouts in any `window_ms` sliding window; excess
+/// `reward_player` calls return `RateLimited`. Pass 0 to disable. Clears recorded history.
+#[no_mangle]
+pub extern "C" fn set_reward_rate_limit(max_per_window: u32, window_ms: u64) -> DeadAimStatus {
+    ffi_guard(|| {
+        let mut limiter = RATE_LIMITER.lock().unwrap_or_else(|e| e.into_inner());
+        limiter.max_per_window = max_per_window;
+        limiter.window_ms = window_ms;
+        limiter.dispatched.clear();
+        Ok(())
+    })
+}
+
+/// Reward player with fungible token amount (smallest unit). Frontend must implement js_send_token.
+/// `wallet` is a null-terminated C string pointer expected from caller; to simplify from C++,
+/// you can call this from the WASM/js layer. For native builds this is a stub.
+/// `now_ms` is the caller's current time, used by the payout rate limiter and to check that a
+/// match is running (see `start_match`); outside one nothing is paid and `NotInitialized` is
+/// returned. `NullPointer` for a missing wallet, `Utf8Error` for a wallet that isn't UTF-8 and
+/// `RateLimited` past the rate limit.
+#[no_mangle]
+pub extern "C" fn reward_player(wallet_ptr: *const u8, wallet_len: usize, amount: u64, now_ms: u64) -> DeadAimStatus {
+    ffi_guard(|| dispatch_reward(wallet_ptr, wallet_len, amount, now_ms, -1))
+}
+
+/// `reward_player` on behalf of a kill of `enemy_id` (-1 for none), reported with the queued
+/// `RewardQueued` event.
+fn dispatch_reward(wallet_ptr: *const u8, wallet_len: usize, amount: u64, now_ms: u64, enemy_id: i32) -> FfiResult {
+    if !match_running(now_ms) {
+        js_log("reward_player: no match running, payout rejected");
+        return Err(DeadAimStatus::NotInitialized);
+    }
+    if wallet_ptr.is_null() || wallet_len == 0 {
+        js_log("reward_player: invalid wallet pointer/len");
+        return Err(DeadAimStatus::NullPointer);
+    }
+    // Convert C-style pointer+len to &str
+    let wallet_slice = unsafe { std::slice::from_raw_parts(wallet_ptr, wallet_len) };
+    if let Ok(wallet_str) = std::str::from_utf8(wallet_slice) {
+        if !RATE_LIMITER.lock().unwrap_or_else(|e| e.into_inner()).try_acquire(now_ms) {
+            js_log("reward_player: rate limit exceeded, payout rejected");
+            return Err(DeadAimStatus::RateLimited);
+        }
+        // call JS/native hook
+        js_send_token(wallet_str, amount);
+        reward_queued(enemy_id, amount);
+        js_log(&format!("reward_player: sent {} to {}", amount, wallet_str));
+        Ok(())
+    } else {
+        js_log("reward_player: wallet string not utf-8");
+        Err(DeadAimStatus::Utf8Error)
+    }
+}
+
+/// Host policy consulted before paying for a kill; `None` approves every kill.
+type KillValidator = extern "C" fn(enemy_id: i32) -> bool;
+
+static KILL_VALIDATOR: Mutex<Option<KillValidator>> = Mutex::new(None);
+
+/// Install (or clear, with null) the kill validator used by `shoot_enemy_and_reward`.
+/// Returning false from it vetoes the payout; the enemy stays dead either way.
+#[no_mangle]
+pub extern "C" fn set_kill_validator(cb: Option<KillValidator>) -> DeadAimStatus {
+    ffi_guard(|| {
+        *KILL_VALIDATOR.lock().unwrap_or_else(|e| e.into_inner()) = cb;
+        Ok(())
+    })
+}
+
+/// Kill the alive enemy at `index` and pay `amount` (scaled by its type's reward multiplier,
+/// see `register_enemy_type`) to the wallet, unless the kill validator rejects it. Nothing is
+/// killed on `InvalidIndex` (a bad index or an enemy that is already dead) or `NotInitialized`
+/// (outside a running match); `Rejected` means the kill stands but was vetoed, and any other
+/// status is the result of `reward_player`.
+// Safe C entry point: `enemies_ptr` is null-checked before use; the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn shoot_enemy_and_reward(
+    enemies_ptr: *mut Enemy,
+    count: i32,
+    index: i32,
+    wallet_ptr: *const u8,
+    wallet_len: usize,
+    amount: u64,
+    now_ms: u64,
+) -> DeadAimStatus {
+    ffi_guard(|| {
+        let enemies = unsafe { slice_arg_mut(enemies_ptr, count) }?;
+        let e = usize::try_from(index).ok().and_then(|i| enemies.get_mut(i)).filter(|e| e.is_alive());
+        let e = e.ok_or(DeadAimStatus::InvalidIndex)?;
+        if !match_running(now_ms) {
+            js_log("shoot_enemy_and_reward: no match running, shot ignored");
+            return Err(DeadAimStatus::NotInitialized);
+        }
+        e.health = 0.0;
+        e.set_alive(false);
+        enemy_killed(e);
+        let enemy_id = e.id;
+        let amount = match enemy_type(e.type_id) {
+            Some(t) => (amount as f64 * t.reward_multiplier.max(0.0) as f64).round() as u64,
+            None => amount,
+        };
+        check_wave_cleared(enemies);
+
+        // copy the callback out so it may call back into us without deadlocking
+        let validator = *KILL_VALIDATOR.lock().unwrap_or_else(|e| e.into_inner());
+        if validator.is_some_and(|cb| !cb(enemy_id)) {
+            js_log(&format!("shoot_enemy_and_reward: kill of enemy {} rejected, no payout", enemy_id));
+            return Err(DeadAimStatus::Rejected);
+        }
+        dispatch_reward(wallet_ptr, wallet_len, amount, now_ms, enemy_id)
+    })
+}
+
+/// Mint an NFT for a player: frontend must implement js_mint_nft(wallet, metadata)
+/// Returns `NotInitialized` (nothing minted) unless a match is in progress and unpaused,
+/// `NullPointer` for a missing wallet or metadata buffer and `Utf8Error` for bad strings.
+// Safe C entry point: `wallet_ptr`, `meta_ptr` are null-checked before use; the rest of the contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn mint_nft_for_player(
+    wallet_ptr: *const u8,
+    wallet_len: usize,
+    meta_ptr: *const u8,
+    meta_len: usize,
+) -> DeadAimStatus {
+    ffi_guard(|| {
+        if !match_unpaused() {
+            js_log("mint_nft_for_player: no match running, mint rejected");
+            return Err(DeadAimStatus::NotInitialized);
+        }
+        if wallet_ptr.is_null() || wallet_len == 0 || (meta_ptr.is_null() && meta_len > 0) {
+            js_log("mint_nft_for_player: invalid wallet pointer");
+            return Err(DeadAimStatus::NullPointer);
+        }
+        let wallet_slice = unsafe { slice_arg(wallet_ptr, wallet_len) }?;
+        let meta_slice = unsafe { slice_arg(meta_ptr, meta_len) }?;
+
+        if let (Ok(wallet_str), Ok(meta_str)) = (std::str::from_utf8(wallet_slice), std::str::from_utf8(meta_slice)) {
+            js_mint_nft(wallet_str, meta_str);
+            js_log(&format!("mint_nft_for_player: minted for {} metadata={}", wallet_str, meta_str));
+            Ok(())
+        } else {
+            js_log("mint_nft_for_player: utf-8 conversion failed");
+            Err(DeadAimStatus::Utf8Error)
+        }
+    })
+}
@@ -0,0 +1,372 @@
+// src/rng.rs
+// Crate-owned deterministic RNG: equal seeds reproduce identical sequences on native and wasm.
+// Both generators are implemented here (not taken from `rand`), so replays can't change under a
+// dependency bump.
+use crate::error::{arg_mut, arg_ref, ffi_guard, slice_arg, slice_arg_mut, write_out, DeadAimStatus};
+use crate::serialize::{Reader, Writer};
+use crate::{move_randomly, Enemy};
+use std::sync::atomic::{AtomicI32, Ordering};
+use std::sync::Mutex;
+
+pub(crate) const DEFAULT_SEED: u64 = 0x0DEA_DA17_5EED;
+
+/// xorshift128+ (the default).
+pub const RNG_XORSHIFT128P: i32 = 0;
+/// PCG32 (XSH-RR, 64-bit state).
+pub const RNG_PCG32: i32 = 1;
+
+// Generator used by subsequent `set_rng_seed` / `rng_new` calls.
+static RNG_ALGORITHM: AtomicI32 = AtomicI32::new(RNG_XORSHIFT128P);
+
+const PCG_MULTIPLIER: u64 = 6364136223846793005;
+
+/// Seeded generator state. For xorshift128+ `s0`/`s1` are the two state words; for PCG32 they
+/// are the state and the (odd) stream increment.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RngState {
+    algo: i32,
+    s0: u64,
+    s1: u64,
+}
+
+const fn splitmix64(state: u64) -> (u64, u64) {
+    let next = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = next;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    (next, z ^ (z >> 31))
+}
+
+impl RngState {
+    /// xorshift128+ seeded through splitmix64.
+    pub const fn from_seed(seed: u64) -> Self {
+        let (state, s0) = splitmix64(seed);
+        let (_, s1) = splitmix64(state);
+        // xorshift must never be all-zero
+        if s0 == 0 && s1 == 0 {
+            RngState { algo: RNG_XORSHIFT128P, s0: 1, s1: 0 }
+        } else {
+            RngState { algo: RNG_XORSHIFT128P, s0, s1 }
+        }
+    }
+
+    /// PCG32 initialized like the reference `pcg32_srandom(init_state, init_seq)`.
+    pub fn pcg32(init_state: u64, init_seq: u64) -> Self {
+        let mut rng = RngState { algo: RNG_PCG32, s0: 0, s1: (init_seq << 1) | 1 };
+        rng.pcg32_next();
+        rng.s0 = rng.s0.wrapping_add(init_state);
+        rng.pcg32_next();
+        rng
+    }
+
+    /// Seed the given algorithm; unknown algorithms fall back to xorshift128+.
+    pub fn from_seed_with(seed: u64, algo: i32) -> Self {
+        match algo {
+            RNG_PCG32 => {
+                let (state, init_state) = splitmix64(seed);
+                let (_, init_seq) = splitmix64(state);
+                RngState::pcg32(init_state, init_seq)
+            }
+            _ => RngState::from_seed(seed),
+        }
+    }
+
+    fn pcg32_next(&mut self) -> u32 {
+        let old = self.s0;
+        self.s0 = old.wrapping_mul(PCG_MULTIPLIER).wrapping_add(self.s1);
+        let xorshifted = (((old >> 18) ^ old) >> 27) as u32;
+        xorshifted.rotate_right((old >> 59) as u32)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        if self.algo == RNG_PCG32 {
+            let hi = self.pcg32_next() as u64;
+            return (hi << 32) | self.pcg32_next() as u64;
+        }
+        let mut x = self.s0;
+        let y = self.s1;
+        self.s0 = y;
+        x ^= x << 23;
+        self.s1 = x ^ y ^ (x >> 17) ^ (y >> 26);
+        self.s1.wrapping_add(y)
+    }
+
+    /// Uniform float in [0, 1).
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    /// Uniform float in [lo, hi); returns `lo` for an empty range.
+    pub fn range_f32(&mut self, lo: f32, hi: f32) -> f32 {
+        if lo.is_nan() || hi.is_nan() || hi <= lo {
+            return lo;
+        }
+        lo + (hi - lo) * self.next_f32()
+    }
+
+    /// Uniform integer in [0, n); 0 when `n` is 0.
+    pub fn next_below(&mut self, n: u32) -> u32 {
+        (((self.next_u64() >> 32) * n as u64) >> 32) as u32
+    }
+
+    pub fn next_bool(&mut self) -> bool {
+        self.next_u64() >> 63 == 1
+    }
+
+    /// Rebuild saved state; `None` for an unknown algorithm or a state it can't be in.
+    fn from_parts(algo: i32, s0: u64, s1: u64) -> Option<Self> {
+        let valid = match algo {
+            RNG_XORSHIFT128P => s0 != 0 || s1 != 0,
+            RNG_PCG32 => s1 & 1 == 1,
+            _ => false,
+        };
+        valid.then_some(RngState { algo, s0, s1 })
+    }
+
+    pub(crate) fn write_snapshot(&self, w: &mut Writer) {
+        w.i32(self.algo);
+        w.u64(self.s0);
+        w.u64(self.s1);
+    }
+
+    pub(crate) fn read_snapshot(r: &mut Reader) -> Option<Self> {
+        RngState::from_parts(r.i32()?, r.u64()?, r.u64()?)
+    }
+}
+
+static GLOBAL_RNG: Mutex<RngState> = Mutex::new(RngState::from_seed(DEFAULT_SEED));
+
+/// Run `f` with the module-global seeded RNG.
+pub(crate) fn with_rng<R>(f: impl FnOnce(&mut RngState) -> R) -> R {
+    let mut rng = GLOBAL_RNG.lock().unwrap_or_else(|e| e.into_inner());
+    f(&mut rng)
+}
+
+/// A generator seeded with `seed` using the algorithm selected by `set_rng_algorithm`.
+pub(crate) fn seeded(seed: u64) -> RngState {
+    RngState::from_seed_with(seed, RNG_ALGORITHM.load(Ordering::Relaxed))
+}
+
+/// Reseed the global RNG with the selected algorithm (see `set_rng_algorithm`); equal seeds
+/// and algorithms reproduce identical random decisions.
+#[no_mangle]
+pub extern "C" fn set_rng_seed(seed: u64) -> DeadAimStatus {
+    ffi_guard(|| {
+        with_rng(|rng| *rng = seeded(seed));
+        Ok(())
+    })
+}
+
+/// Pin the generator used by later `set_rng_seed` / `rng_new` calls: `RNG_XORSHIFT128P` (default)
+/// or `RNG_PCG32`. Existing generators keep their algorithm. `InvalidArgument` for an unknown
+/// value.
+#[no_mangle]
+pub extern "C" fn set_rng_algorithm(algo: i32) -> DeadAimStatus {
+    ffi_guard(|| {
+        if !matches!(algo, RNG_XORSHIFT128P | RNG_PCG32) {
+            return Err(DeadAimStatus::InvalidArgument);
+        }
+        RNG_ALGORITHM.store(algo, Ordering::Relaxed);
+        Ok(())
+    })
+}
+
+/// Replay-safe jitter for floating damage numbers: a deterministic offset in [-0.5, 0.5) world
+/// units per axis derived only from `seed` (e.g. `(enemy_id << 32) | frame`), so the same hit
+/// always places its text identically. Does not touch the global RNG.
+// Safe C entry point: `out_dx`, `out_dy` are null-checked before use; the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn damage_text_offset(seed: u64, out_dx: *mut f32, out_dy: *mut f32) -> DeadAimStatus {
+    ffi_guard(|| {
+        if out_dx.is_null() || out_dy.is_null() {
+            return Err(DeadAimStatus::NullPointer);
+        }
+        let mut rng = RngState::from_seed(seed);
+        let (dx, dy) = (rng.range_f32(-0.5, 0.5), rng.range_f32(-0.5, 0.5));
+        unsafe {
+            out_dx.write(dx);
+            write_out(out_dy, dy)
+        }
+    })
+}
+
+// ---------- Instanced RNG handles (one per world, snapshot/restore for save games) ----------
+
+// v1 (17 bytes) had no algorithm byte and is always xorshift128+.
+const SAVE_VERSION: u8 = 2;
+const SAVE_LEN_V1: usize = 17;
+/// Bytes written by `rng_save`: version byte + algorithm byte + two little-endian u64 state words.
+pub const RNG_SAVE_LEN: usize = 18;
+
+/// Create an RNG handle seeded with `seed`, using the algorithm selected by `set_rng_algorithm`,
+/// and write it to `out_rng`. Free with `rng_free`.
+// Safe C entry point: `out_rng` is null-checked before use; the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn rng_new(seed: u64, out_rng: *mut *mut RngState) -> DeadAimStatus {
+    ffi_guard(|| {
+        if out_rng.is_null() {
+            return Err(DeadAimStatus::NullPointer);
+        }
+        unsafe { write_out(out_rng, Box::into_raw(Box::new(seeded(seed)))) }
+    })
+}
+
+/// Free a handle from `rng_new`/`rng_restore`. Null is ignored.
+// Safe C entry point: `rng` is null-checked before use; the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn rng_free(rng: *mut RngState) -> DeadAimStatus {
+    ffi_guard(|| {
+        if !rng.is_null() {
+            drop(unsafe { Box::from_raw(rng) });
+        }
+        Ok(())
+    })
+}
+
+/// Snapshot the generator's exact position into `out_buf` and write the bytes written
+/// (`RNG_SAVE_LEN`) to `out_written`. `BufferTooSmall` below `RNG_SAVE_LEN` bytes of capacity.
+// Safe C entry point: `rng`, `out_buf`, `out_written` are null-checked before use;
+// the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn rng_save(
+    rng: *const RngState,
+    out_buf: *mut u8,
+    out_cap: usize,
+    out_written: *mut usize,
+) -> DeadAimStatus {
+    ffi_guard(|| {
+        let rng = unsafe { arg_ref(rng) }?;
+        if out_buf.is_null() || out_written.is_null() {
+            return Err(DeadAimStatus::NullPointer);
+        }
+        if out_cap < RNG_SAVE_LEN {
+            return Err(DeadAimStatus::BufferTooSmall);
+        }
+        let out = unsafe { slice_arg_mut(out_buf, RNG_SAVE_LEN) }?;
+        out[0] = SAVE_VERSION;
+        out[1] = rng.algo as u8;
+        out[2..10].copy_from_slice(&rng.s0.to_le_bytes());
+        out[10..18].copy_from_slice(&rng.s1.to_le_bytes());
+        unsafe { write_out(out_written, RNG_SAVE_LEN) }
+    })
+}
+
+/// Recreate a generator from an `rng_save` snapshot and write it to `out_rng`; continuing from it
+/// reproduces the exact sequence the saved generator would have produced. `InvalidData` for
+/// malformed input.
+// Safe C entry point: `in_buf`, `out_rng` are null-checked before use; the rest of the contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn rng_restore(in_buf: *const u8, in_len: usize, out_rng: *mut *mut RngState) -> DeadAimStatus {
+    ffi_guard(|| {
+        let bytes = unsafe { slice_arg(in_buf, in_len.min(RNG_SAVE_LEN)) }?;
+        if out_rng.is_null() {
+            return Err(DeadAimStatus::NullPointer);
+        }
+        if bytes.len() < SAVE_LEN_V1 {
+            return Err(DeadAimStatus::InvalidData);
+        }
+        let (algo, words) = match bytes[0] {
+            1 => (RNG_XORSHIFT128P, &bytes[1..SAVE_LEN_V1]),
+            SAVE_VERSION if bytes.len() == RNG_SAVE_LEN => (bytes[1] as i32, &bytes[2..RNG_SAVE_LEN]),
+            _ => return Err(DeadAimStatus::InvalidData),
+        };
+        let s0 = u64::from_le_bytes(words[0..8].try_into().unwrap());
+        let s1 = u64::from_le_bytes(words[8..16].try_into().unwrap());
+        let rng = RngState::from_parts(algo, s0, s1).ok_or(DeadAimStatus::InvalidData)?;
+        unsafe { write_out(out_rng, Box::into_raw(Box::new(rng))) }
+    })
+}
+
+/// Roll a uniform float in [0, 1) from the handle into `out_value`.
+// Safe C entry point: `rng`, `out_value` are null-checked before use; the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn rng_next_f32(rng: *mut RngState, out_value: *mut f32) -> DeadAimStatus {
+    ffi_guard(|| {
+        let rng = unsafe { arg_mut(rng) }?;
+        if out_value.is_null() {
+            return Err(DeadAimStatus::NullPointer);
+        }
+        unsafe { write_out(out_value, rng.next_f32()) }
+    })
+}
+
+/// Roll a uniform float in [lo, hi) from the handle into `out_value` (`lo` for an empty range).
+// Safe C entry point: `rng`, `out_value` are null-checked before use; the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn rng_range_f32(rng: *mut RngState, lo: f32, hi: f32, out_value: *mut f32) -> DeadAimStatus {
+    ffi_guard(|| {
+        let rng = unsafe { arg_mut(rng) }?;
+        if out_value.is_null() {
+            return Err(DeadAimStatus::NullPointer);
+        }
+        unsafe { write_out(out_value, rng.range_f32(lo, hi)) }
+    })
+}
+
+/// `move_enemies_randomly` (walk, flocking and arena bounds) driven by an explicit RNG handle
+/// instead of shared state. An empty array or zero speed does nothing; `InvalidArgument` for a
+/// negative/NaN speed.
+// Safe C entry point: `enemies_ptr`, `rng` are null-checked before use; the rest of the contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn move_enemies_randomly_rng(
+    enemies_ptr: *mut Enemy,
+    count: i32,
+    speed: f32,
+    rng: *mut RngState,
+) -> DeadAimStatus {
+    ffi_guard(|| {
+        let rng = unsafe { arg_mut(rng) }?;
+        let enemies = unsafe { slice_arg_mut(enemies_ptr, count) }?;
+        if speed.is_nan() || speed < 0.0 {
+            return Err(DeadAimStatus::InvalidArgument);
+        }
+        if speed > 0.0 {
+            move_randomly(enemies, speed, rng);
+        }
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pcg32_matches_the_reference_vectors() {
+        // pcg32-demo output for pcg32_srandom(42, 54)
+        let mut rng = RngState::pcg32(42, 54);
+        let words: Vec<u32> = (0..6).map(|_| rng.pcg32_next()).collect();
+        assert_eq!(words, [0xa15c02b7, 0x7b47f409, 0xba1d3330, 0x83d2f293, 0xbfa4784b, 0xcbed606e]);
+
+        let mut rng = RngState::pcg32(42, 54);
+        assert_eq!(rng.next_u64(), 0xa15c02b7_7b47f409);
+    }
+
+    #[test]
+    fn xorshift128p_output_is_pinned() {
+        let mut rng = RngState::from_seed(0);
+        let words: Vec<u64> = (0..4).map(|_| rng.next_u64()).collect();
+        assert_eq!(words, [0xff5e664aa2264ab1, 0x5cb3706844353952, 0x76f611e25a5011e3, 0xcfeccb7f0a0c7948]);
+        assert_eq!(RngState::from_seed_with(0, 99), RngState::from_seed(0));
+    }
+
+    #[test]
+    fn saved_state_continues_the_sequence() {
+        let mut rng = RngState::from_seed_with(7, RNG_PCG32);
+        rng.next_u64();
+        let mut w = Writer::new(false);
+        rng.write_snapshot(&mut w);
+        let mut restored = RngState::read_snapshot(&mut Reader::new(&w.buf, false)).unwrap();
+        for _ in 0..8 {
+            assert_eq!(restored.next_u64(), rng.next_u64());
+        }
+    }
+}
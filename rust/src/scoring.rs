@@ -0,0 +1,207 @@
+// src/scoring.rs
+// Per-player score with kill-streak multipliers, combos and headshot bonuses. Points are
+// computed here from the registered enemy types, so hosts report what happened, never how much
+// it was worth. Registered worlds keep their own scores (see `match_state`); the rules are shared.
+use crate::enemy_types::enemy_type;
+use crate::error::{arg_ref, ffi_guard, write_opt, write_out, DeadAimStatus, FfiResult};
+use crate::match_state::with_part;
+use std::sync::Mutex;
+
+// Player ids accepted by the scoring functions (0..MAX_PLAYERS).
+const MAX_PLAYERS: usize = 64;
+
+/// Scoring rules for `set_scoring_config`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct ScoringConfig {
+    /// Points for killing an enemy whose type has no `score_value` (or isn't registered).
+    pub base_points: i32,
+    /// Every `streak_step` kills without dying raises the multiplier by `streak_bonus`
+    /// (0 disables streak multipliers).
+    pub streak_step: i32,
+    pub streak_bonus: f32,
+    /// Cap on the streak multiplier.
+    pub max_multiplier: f32,
+    /// Extra factor on a headshot kill's points (1 = no bonus).
+    pub headshot_multiplier: f32,
+    /// Kills within this many ms of the previous one chain into a combo; each chained kill adds
+    /// `combo_points` times the combo length so far (0 window disables combos).
+    pub combo_window_ms: u64,
+    pub combo_points: i32,
+}
+
+static CONFIG: Mutex<ScoringConfig> = Mutex::new(ScoringConfig {
+    base_points: 100,
+    streak_step: 5,
+    streak_bonus: 0.5,
+    max_multiplier: 3.0,
+    headshot_multiplier: 1.5,
+    combo_window_ms: 2000,
+    combo_points: 25,
+});
+
+/// A player's standing, copied out by `get_score_summary`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ScoreSummary {
+    pub score: i64,
+    pub kills: i32,
+    pub headshots: i32,
+    pub deaths: i32,
+    /// Kills since the player last died, and the longest such run.
+    pub streak: i32,
+    pub best_streak: i32,
+    /// Kills in the current combo chain (0 before the first kill).
+    pub combo: i32,
+    /// Streak multiplier the next kill scores with.
+    pub multiplier: f32,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct PlayerScore {
+    summary: ScoreSummary,
+    last_kill_ms: u64,
+}
+
+impl Default for PlayerScore {
+    fn default() -> Self {
+        PlayerScore { summary: ScoreSummary { multiplier: 1.0, ..Default::default() }, last_kill_ms: 0 }
+    }
+}
+
+static SCORES: Mutex<Vec<PlayerScore>> = Mutex::new(Vec::new());
+
+/// Run `f` on the installed world's scores, else the crate-wide ones.
+fn with_scores<T>(f: impl FnOnce(&mut Vec<PlayerScore>) -> T) -> T {
+    with_part(&SCORES, |m| &mut m.scores, f)
+}
+
+impl ScoringConfig {
+    fn multiplier(&self, streak: i32) -> f32 {
+        if self.streak_step <= 0 {
+            return 1.0;
+        }
+        let bonus = (streak / self.streak_step) as f32 * self.streak_bonus;
+        (1.0 + bonus).min(self.max_multiplier).max(1.0)
+    }
+}
+
+/// Slot of `player_id`; `InvalidArgument` outside 0..64.
+fn player_index(player_id: i32) -> FfiResult<usize> {
+    usize::try_from(player_id).ok().filter(|&i| i < MAX_PLAYERS).ok_or(DeadAimStatus::InvalidArgument)
+}
+
+fn player_entry(scores: &mut Vec<PlayerScore>, index: usize) -> &mut PlayerScore {
+    if scores.len() <= index {
+        scores.resize(index + 1, PlayerScore::default());
+    }
+    &mut scores[index]
+}
+
+/// Replace the scoring rules (defaults: 100 base points, +0.5x every 5-kill streak up to 3x,
+/// 1.5x headshots, 25-point combo steps within 2 s). Affects later kills only.
+// Safe C entry point: `config` is null-checked before use; the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn set_scoring_config(config: *const ScoringConfig) -> DeadAimStatus {
+    ffi_guard(|| {
+        *CONFIG.lock().unwrap_or_else(|e| e.into_inner()) = *unsafe { arg_ref(config) }?;
+        Ok(())
+    })
+}
+
+/// Score a kill of an enemy of `type_id` by `player_id` at the caller's `now_ms`: the type's
+/// `score_value` (or the base points) times the player's streak multiplier, times the headshot
+/// multiplier for a `headshot`, plus the combo bonus when it chains off the previous kill.
+/// Timestamps earlier than the previous kill are treated as simultaneous with it.
+/// Writes the points awarded to `out_points` (may be null); `InvalidArgument` for a player id
+/// outside 0..64.
+// Safe C entry point: `out_points` is null-checked before use; the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn score_kill(
+    player_id: i32,
+    type_id: i32,
+    headshot: bool,
+    now_ms: u64,
+    out_points: *mut i64,
+) -> DeadAimStatus {
+    ffi_guard(|| {
+        let index = player_index(player_id)?;
+        let config = *CONFIG.lock().unwrap_or_else(|e| e.into_inner());
+        let base = enemy_type(type_id).map(|t| t.score_value).filter(|&v| v > 0).unwrap_or(config.base_points);
+        let awarded = with_scores(|scores| {
+            let player = player_entry(scores, index);
+            let s = &mut player.summary;
+
+            let mut points = base as f64 * config.multiplier(s.streak) as f64;
+            if headshot {
+                points *= config.headshot_multiplier.max(0.0) as f64;
+                s.headshots += 1;
+            }
+            let now_ms = now_ms.max(player.last_kill_ms);
+            let chained =
+                s.kills > 0 && config.combo_window_ms > 0 && now_ms - player.last_kill_ms <= config.combo_window_ms;
+            s.combo = if chained { s.combo + 1 } else { 1 };
+            let combo_bonus = config.combo_points as i64 * (s.combo - 1) as i64;
+            let awarded = points.round() as i64 + combo_bonus;
+
+            s.score += awarded;
+            s.kills += 1;
+            s.streak += 1;
+            s.best_streak = s.best_streak.max(s.streak);
+            s.multiplier = config.multiplier(s.streak);
+            player.last_kill_ms = now_ms;
+            awarded
+        });
+        unsafe { write_opt(out_points, awarded) };
+        Ok(())
+    })
+}
+
+/// Record that `player_id` died: ends their streak and combo. `InvalidArgument` for a bad id.
+#[no_mangle]
+pub extern "C" fn score_player_death(player_id: i32) -> DeadAimStatus {
+    ffi_guard(|| {
+        let index = player_index(player_id)?;
+        with_scores(|scores| {
+            let s = &mut player_entry(scores, index).summary;
+            s.deaths += 1;
+            s.streak = 0;
+            s.combo = 0;
+            s.multiplier = 1.0;
+        });
+        Ok(())
+    })
+}
+
+/// Copy `player_id`'s standing to `out` (all zero, multiplier 1, for a player with no kills yet).
+/// `InvalidArgument` for a bad id.
+// Safe C entry point: `out` is null-checked before use; the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn get_score_summary(player_id: i32, out: *mut ScoreSummary) -> DeadAimStatus {
+    ffi_guard(|| {
+        let index = player_index(player_id)?;
+        unsafe { write_out(out, with_scores(|scores| scores.get(index).copied().unwrap_or_default().summary)) }
+    })
+}
+
+/// `get_score_summary` for the crate: default standing for an unknown or bad id.
+pub(crate) fn score_summary(player_id: i32) -> ScoreSummary {
+    let index = player_index(player_id).unwrap_or(usize::MAX);
+    with_scores(|scores| scores.get(index).copied().unwrap_or_default().summary)
+}
+
+/// Reset every player's score (e.g. between matches).
+#[no_mangle]
+pub extern "C" fn reset_scores() -> DeadAimStatus {
+    ffi_guard(|| {
+        clear_scores();
+        Ok(())
+    })
+}
+
+pub(crate) fn clear_scores() {
+    with_scores(|scores| scores.clear());
+}
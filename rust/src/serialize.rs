@@ -0,0 +1,422 @@
+// src/serialize.rs
+// Versioned binary snapshots of enemy arrays with explicit byte order.
+//
+// Layout: magic "DAEN" | version u16 | byte-order u8 (1=LE, 2=BE) | reserved u8 | count u32
+//         then per enemy:
+//   v1: id i32 | x f32 | y f32 | alive u8
+//   v2: v1 fields | dodge_cooldown f32 | stun_frames i32 | last_seen_x f32 | last_seen_y f32
+//       | vx f32 | vy f32 | health f32 | max_health f32 | tether_id i32 | summon_cooldown f32
+//   v3: v2 fields | team i32
+//   v4: v3 fields | flinch_frames i32
+//   v5: v4 fields | wave_id i32
+//   v6: v5 fields | regen_rate f32 | frames_since_hit i32
+//   v7: v6 fields | taunt_x f32 | taunt_y f32 | taunt_frames i32
+//   v8: v7 fields | type_id i32
+//
+// Every AI-relevant `Enemy` field must be listed here, or save/load silently resets it:
+// bump FORMAT_VERSION and extend `write_enemy`/`read_enemy` whenever the struct grows.
+use crate::error::{ffi_guard, slice_arg, slice_arg_mut, write_out, DeadAimStatus, FfiResult};
+use crate::Enemy;
+
+const MAGIC: &[u8; 4] = b"DAEN";
+pub(crate) const FORMAT_VERSION: u16 = 8;
+const HEADER_LEN: usize = 12;
+const ENEMY_LEN_V1: usize = 13;
+const ENEMY_LEN_V2: usize = ENEMY_LEN_V1 + 40;
+const ENEMY_LEN_V3: usize = ENEMY_LEN_V2 + 4;
+const ENEMY_LEN_V4: usize = ENEMY_LEN_V3 + 4;
+const ENEMY_LEN_V5: usize = ENEMY_LEN_V4 + 4;
+const ENEMY_LEN_V6: usize = ENEMY_LEN_V5 + 8;
+const ENEMY_LEN_V7: usize = ENEMY_LEN_V6 + 12;
+const ENEMY_LEN: usize = ENEMY_LEN_V7 + 4;
+
+pub(crate) fn enemy_len(version: u16) -> Option<usize> {
+    match version {
+        1 => Some(ENEMY_LEN_V1),
+        2 => Some(ENEMY_LEN_V2),
+        3 => Some(ENEMY_LEN_V3),
+        4 => Some(ENEMY_LEN_V4),
+        5 => Some(ENEMY_LEN_V5),
+        6 => Some(ENEMY_LEN_V6),
+        7 => Some(ENEMY_LEN_V7),
+        8 => Some(ENEMY_LEN),
+        _ => None,
+    }
+}
+
+pub const ENDIAN_NATIVE: i32 = 0;
+pub const ENDIAN_LITTLE: i32 = 1;
+pub const ENDIAN_BIG: i32 = 2;
+
+/// Resolve an `endian` argument to "big endian?"; `InvalidArgument` for unknown values.
+fn resolve_endian(endian: i32) -> FfiResult<bool> {
+    match endian {
+        ENDIAN_NATIVE => Ok(cfg!(target_endian = "big")),
+        ENDIAN_LITTLE => Ok(false),
+        ENDIAN_BIG => Ok(true),
+        _ => Err(DeadAimStatus::InvalidArgument),
+    }
+}
+
+/// Byte sink shared by the snapshot formats (enemy arrays here, worlds in `world.rs`).
+pub(crate) struct Writer {
+    pub(crate) buf: Vec<u8>,
+    big: bool,
+}
+
+impl Writer {
+    pub(crate) fn new(big: bool) -> Self {
+        Writer { buf: Vec::new(), big }
+    }
+    pub(crate) fn put(&mut self, bytes: &[u8]) {
+        self.buf.extend_from_slice(bytes);
+    }
+    pub(crate) fn u8(&mut self, v: u8) {
+        self.put(&[v]);
+    }
+    pub(crate) fn u16(&mut self, v: u16) {
+        self.put(&if self.big { v.to_be_bytes() } else { v.to_le_bytes() });
+    }
+    pub(crate) fn u32(&mut self, v: u32) {
+        self.put(&if self.big { v.to_be_bytes() } else { v.to_le_bytes() });
+    }
+    pub(crate) fn u64(&mut self, v: u64) {
+        self.put(&if self.big { v.to_be_bytes() } else { v.to_le_bytes() });
+    }
+    pub(crate) fn i32(&mut self, v: i32) {
+        self.u32(v as u32);
+    }
+    pub(crate) fn f32(&mut self, v: f32) {
+        self.u32(v.to_bits());
+    }
+    /// A length-prefixed (u32) list of interleaved x, y pairs.
+    pub(crate) fn points(&mut self, points: &[(f32, f32)]) {
+        self.u32(points.len() as u32);
+        for &(x, y) in points {
+            self.f32(x);
+            self.f32(y);
+        }
+    }
+}
+
+/// Byte source matching `Writer`; every read is `None` once the input runs out.
+pub(crate) struct Reader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+    big: bool,
+}
+
+impl<'a> Reader<'a> {
+    pub(crate) fn new(buf: &'a [u8], big: bool) -> Self {
+        Reader { buf, pos: 0, big }
+    }
+    pub(crate) fn at_end(&self) -> bool {
+        self.pos == self.buf.len()
+    }
+    pub(crate) fn bytes(&mut self, len: usize) -> Option<&'a [u8]> {
+        let bytes = self.buf.get(self.pos..self.pos.checked_add(len)?)?;
+        self.pos += len;
+        Some(bytes)
+    }
+    pub(crate) fn take<const N: usize>(&mut self) -> Option<[u8; N]> {
+        self.bytes(N)?.try_into().ok()
+    }
+    pub(crate) fn u8(&mut self) -> Option<u8> {
+        self.take::<1>().map(|b| b[0])
+    }
+    pub(crate) fn u16(&mut self) -> Option<u16> {
+        let b = self.take::<2>()?;
+        Some(if self.big { u16::from_be_bytes(b) } else { u16::from_le_bytes(b) })
+    }
+    pub(crate) fn u32(&mut self) -> Option<u32> {
+        let b = self.take::<4>()?;
+        Some(if self.big { u32::from_be_bytes(b) } else { u32::from_le_bytes(b) })
+    }
+    pub(crate) fn u64(&mut self) -> Option<u64> {
+        let b = self.take::<8>()?;
+        Some(if self.big { u64::from_be_bytes(b) } else { u64::from_le_bytes(b) })
+    }
+    pub(crate) fn i32(&mut self) -> Option<i32> {
+        self.u32().map(|v| v as i32)
+    }
+    pub(crate) fn f32(&mut self) -> Option<f32> {
+        self.u32().map(f32::from_bits)
+    }
+    pub(crate) fn bool(&mut self) -> Option<bool> {
+        self.u8().map(|b| b != 0)
+    }
+    /// Read a list written by `Writer::points`.
+    pub(crate) fn points(&mut self) -> Option<Vec<(f32, f32)>> {
+        let count = self.u32()?;
+        (0..count).map(|_| Some((self.f32()?, self.f32()?))).collect()
+    }
+}
+
+/// Writes the bytes needed to serialize `count` enemies. `InvalidArgument` for a negative count.
+// Safe C entry point: `out_size` is null-checked before use; the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn serialized_enemies_size(count: i32, out_size: *mut usize) -> DeadAimStatus {
+    ffi_guard(|| {
+        let count = usize::try_from(count).map_err(|_| DeadAimStatus::InvalidArgument)?;
+        unsafe { write_out(out_size, enemies_size(count)) }
+    })
+}
+
+fn enemies_size(count: usize) -> usize {
+    HEADER_LEN + count * ENEMY_LEN
+}
+
+/// Serialize `count` enemies into `out_buf` using the requested byte order
+/// (0 = native, 1 = little endian, 2 = big endian) and write the bytes written to
+/// `out_written`. `InvalidArgument` for an unknown byte order, `BufferTooSmall` if `out_cap` is
+/// too small.
+// Safe C entry point: `enemies_ptr`, `out_buf`, `out_written` are null-checked before use;
+// the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn serialize_enemies(
+    enemies_ptr: *const Enemy,
+    count: i32,
+    endian: i32,
+    out_buf: *mut u8,
+    out_cap: usize,
+    out_written: *mut usize,
+) -> DeadAimStatus {
+    ffi_guard(|| {
+        let big = resolve_endian(endian)?;
+        let enemies = unsafe { slice_arg(enemies_ptr, count) }?;
+        if out_buf.is_null() || out_written.is_null() {
+            return Err(DeadAimStatus::NullPointer);
+        }
+        if out_cap < enemies_size(enemies.len()) {
+            return Err(DeadAimStatus::BufferTooSmall);
+        }
+
+        let mut w = Writer::new(big);
+        w.put(MAGIC);
+        w.u16(FORMAT_VERSION);
+        w.u8(if big { ENDIAN_BIG as u8 } else { ENDIAN_LITTLE as u8 });
+        w.u8(0);
+        w.u32(enemies.len() as u32);
+        for e in enemies {
+            write_enemy(&mut w, e);
+        }
+        unsafe { std::ptr::copy_nonoverlapping(w.buf.as_ptr(), out_buf, w.buf.len()) };
+        unsafe { write_out(out_written, w.buf.len()) }
+    })
+}
+
+/// Encode `e` in the current (`FORMAT_VERSION`) record layout.
+pub(crate) fn write_enemy(w: &mut Writer, e: &Enemy) {
+    w.i32(e.id);
+    w.f32(e.x);
+    w.f32(e.y);
+    w.u8(e.is_alive() as u8);
+    w.f32(e.dodge_cooldown);
+    w.i32(e.stun_frames);
+    w.f32(e.last_seen_x);
+    w.f32(e.last_seen_y);
+    w.f32(e.vx);
+    w.f32(e.vy);
+    w.f32(e.health);
+    w.f32(e.max_health);
+    w.i32(e.tether_id);
+    w.f32(e.summon_cooldown);
+    w.i32(e.team);
+    w.i32(e.flinch_frames);
+    w.i32(e.wave_id);
+    w.f32(e.regen_rate);
+    w.i32(e.frames_since_hit);
+    w.f32(e.taunt_x);
+    w.f32(e.taunt_y);
+    w.i32(e.taunt_frames);
+    w.i32(e.type_id);
+}
+
+/// Decode one enemy; fields a `version` doesn't carry keep their `Enemy::new` defaults.
+pub(crate) fn read_enemy(r: &mut Reader, version: u16) -> Option<Enemy> {
+    let (id, x, y) = (r.i32()?, r.f32()?, r.f32()?);
+    let mut e = Enemy::new(id, x, y);
+    e.set_alive(r.u8()? != 0);
+    if version >= 2 {
+        e.dodge_cooldown = r.f32()?;
+        e.stun_frames = r.i32()?;
+        e.last_seen_x = r.f32()?;
+        e.last_seen_y = r.f32()?;
+        e.vx = r.f32()?;
+        e.vy = r.f32()?;
+        e.health = r.f32()?;
+        e.max_health = r.f32()?;
+        e.tether_id = r.i32()?;
+        e.summon_cooldown = r.f32()?;
+    }
+    if version >= 3 {
+        e.team = r.i32()?;
+    }
+    if version >= 4 {
+        e.flinch_frames = r.i32()?;
+    }
+    if version >= 5 {
+        e.wave_id = r.i32()?;
+    }
+    if version >= 6 {
+        e.regen_rate = r.f32()?;
+        e.frames_since_hit = r.i32()?;
+    }
+    if version >= 7 {
+        e.taunt_x = r.f32()?;
+        e.taunt_y = r.f32()?;
+        e.taunt_frames = r.i32()?;
+    }
+    if version >= 8 {
+        e.type_id = r.i32()?;
+    }
+    Some(e)
+}
+
+/// Decode a snapshot written by `serialize_enemies` into `out_enemies` (capacity `out_cap`) and
+/// write the number of enemies decoded to `out_count`. Older snapshots load with the state their
+/// version lacks at defaults. `endian` must match the byte order the snapshot was written with
+/// (0 = native). `InvalidData` for malformed input or a byte-order mismatch, `BufferTooSmall` if
+/// `out_cap` is too small.
+// Safe C entry point: `in_buf`, `out_enemies`, `out_count` are null-checked before use;
+// the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn deserialize_enemies(
+    in_buf: *const u8,
+    in_len: usize,
+    endian: i32,
+    out_enemies: *mut Enemy,
+    out_cap: i32,
+    out_count: *mut i32,
+) -> DeadAimStatus {
+    ffi_guard(|| {
+        let big = resolve_endian(endian)?;
+        let buf = unsafe { slice_arg(in_buf, in_len) }?;
+        let out = unsafe { slice_arg_mut(out_enemies, out_cap) }?;
+        if out_count.is_null() {
+            return Err(DeadAimStatus::NullPointer);
+        }
+        let mut r = Reader::new(buf, big);
+
+        if r.take::<4>().as_ref() != Some(MAGIC) {
+            return Err(DeadAimStatus::InvalidData);
+        }
+        let (Some(version), Some(order), Some(_), Some(count)) = (r.u16(), r.u8(), r.u8(), r.u32()) else {
+            return Err(DeadAimStatus::InvalidData);
+        };
+        let expected_order = if big { ENDIAN_BIG } else { ENDIAN_LITTLE };
+        let record_len = enemy_len(version).ok_or(DeadAimStatus::InvalidData)?;
+        if order as i32 != expected_order {
+            return Err(DeadAimStatus::InvalidData);
+        }
+        let count = count as usize;
+        if in_len < HEADER_LEN + count * record_len {
+            return Err(DeadAimStatus::InvalidData);
+        }
+        let Some(out) = out.get_mut(..count) else {
+            return Err(DeadAimStatus::BufferTooSmall);
+        };
+
+        for slot in out.iter_mut() {
+            *slot = read_enemy(&mut r, version).ok_or(DeadAimStatus::InvalidData)?;
+        }
+        unsafe { write_out(out_count, count as i32) }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode(enemies: &[Enemy], endian: i32) -> Vec<u8> {
+        let mut size = 0;
+        assert_eq!(serialized_enemies_size(enemies.len() as i32, &mut size), DeadAimStatus::Ok);
+        let (mut buf, mut written) = (vec![0; size], 0);
+        let status =
+            serialize_enemies(enemies.as_ptr(), enemies.len() as i32, endian, buf.as_mut_ptr(), size, &mut written);
+        assert_eq!(status, DeadAimStatus::Ok);
+        assert_eq!(written, size);
+        buf
+    }
+
+    fn decode(buf: &[u8], endian: i32) -> Result<Vec<Enemy>, DeadAimStatus> {
+        let mut out = vec![Enemy::new(0, 0.0, 0.0); 8];
+        let mut count = 0;
+        match deserialize_enemies(buf.as_ptr(), buf.len(), endian, out.as_mut_ptr(), 8, &mut count) {
+            DeadAimStatus::Ok => Ok(out[..count as usize].to_vec()),
+            status => Err(status),
+        }
+    }
+
+    #[test]
+    fn big_endian_round_trip() {
+        let mut e = Enemy::new(0x0102_0304, 1.5, -2.25);
+        e.health = 0.75;
+        let buf = encode(&[e, Enemy::new(7, 3.0, 4.0)], ENDIAN_BIG);
+
+        assert_eq!(&buf[..8], &[b'D', b'A', b'E', b'N', 0, FORMAT_VERSION as u8, ENDIAN_BIG as u8, 0]);
+        assert_eq!(&buf[8..12], &2u32.to_be_bytes());
+        assert_eq!(&buf[12..16], &[1, 2, 3, 4]);
+        assert_eq!(&buf[16..20], &1.5f32.to_be_bytes());
+
+        let back = decode(&buf, ENDIAN_BIG).unwrap();
+        assert_eq!(format!("{:?}", back), format!("{:?}", [e, Enemy::new(7, 3.0, 4.0)]));
+        assert_eq!(decode(&buf, ENDIAN_LITTLE).unwrap_err(), DeadAimStatus::InvalidData);
+    }
+
+    #[test]
+    fn byte_orders_differ_only_in_encoding() {
+        let enemies = [Enemy::new(3, 10.0, 20.0)];
+        let (le, be) = (encode(&enemies, ENDIAN_LITTLE), encode(&enemies, ENDIAN_BIG));
+        assert_ne!(le, be);
+        assert_eq!(&le[12..16], &3i32.to_le_bytes());
+        assert_eq!(format!("{:?}", decode(&le, ENDIAN_LITTLE)), format!("{:?}", decode(&be, ENDIAN_BIG)));
+        assert_eq!(decode(&le, 3).unwrap_err(), DeadAimStatus::InvalidArgument);
+    }
+
+    #[test]
+    fn mid_fight_save_keeps_ai_state() {
+        let mut e = Enemy::new(4, 12.0, -3.0);
+        e.dodge_cooldown = 0.4;
+        e.stun_frames = 3;
+        e.last_seen_x = 9.5;
+        e.last_seen_y = 1.25;
+        e.vx = -0.5;
+        e.vy = 2.0;
+        e.health = 0.3;
+        e.max_health = 2.0;
+        e.tether_id = 5;
+        e.summon_cooldown = 1.5;
+        e.team = 2;
+        e.flinch_frames = 6;
+        e.wave_id = 1;
+        e.regen_rate = 0.05;
+        e.frames_since_hit = 17;
+        e.taunt_x = 3.0;
+        e.taunt_y = 4.0;
+        e.taunt_frames = 8;
+        e.type_id = 2;
+        let mut dead = Enemy::new(5, 0.0, 0.0);
+        dead.set_alive(false);
+
+        for endian in [ENDIAN_LITTLE, ENDIAN_BIG] {
+            let back = decode(&encode(&[e, dead], endian), endian).unwrap();
+            assert_eq!(format!("{:?}", back), format!("{:?}", [e, dead]));
+        }
+    }
+
+    #[test]
+    fn old_versions_load_with_defaults() {
+        let mut e = Enemy::new(1, 2.0, 3.0);
+        e.type_id = 4;
+        e.taunt_frames = 9;
+        let mut buf = encode(&[e], ENDIAN_LITTLE);
+        buf[4..6].copy_from_slice(&7u16.to_le_bytes());
+        buf.truncate(HEADER_LEN + ENEMY_LEN_V7);
+        let back = decode(&buf, ENDIAN_LITTLE).unwrap();
+        assert_eq!((back[0].taunt_frames, back[0].type_id), (9, 0));
+    }
+}
@@ -0,0 +1,180 @@
+// src/session.rs
+// Match lifecycle: start, pause/resume and end, with the per-match stats the result reports.
+// Payouts are only honored while a match is running, so rewards can't be triggered from a
+// console outside gameplay. Registered worlds run their own match (see `match_state`).
+use crate::error::{arg_ref, ffi_guard, write_out, DeadAimStatus};
+use crate::match_state::with_part;
+use crate::scoring::{clear_scores, score_summary};
+use std::sync::Mutex;
+
+/// Settings for `start_match`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct MatchConfig {
+    /// Scoring player whose kills and score the result reports (see `score_kill`).
+    pub player_id: i32,
+    /// Match length in ms of unpaused play; 0 = no limit. Once it runs out rewards are refused
+    /// until the next `start_match`.
+    pub time_limit_ms: u64,
+}
+
+/// Summary returned by `end_match`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MatchResult {
+    pub kills: i32,
+    pub shots_fired: i32,
+    pub shots_hit: i32,
+    /// `shots_hit / shots_fired` (0 when nothing was fired).
+    pub accuracy: f32,
+    /// Unpaused play time, in ms (capped at the time limit).
+    pub duration_ms: u64,
+    pub score: i64,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Match {
+    config: MatchConfig,
+    started_ms: u64,
+    /// When the current pause began, if paused.
+    paused_at_ms: Option<u64>,
+    paused_total_ms: u64,
+    shots_fired: i32,
+    shots_hit: i32,
+}
+
+impl Match {
+    fn elapsed_ms(&self, now_ms: u64) -> u64 {
+        let until = self.paused_at_ms.unwrap_or(now_ms);
+        let elapsed = until.saturating_sub(self.started_ms).saturating_sub(self.paused_total_ms);
+        match self.config.time_limit_ms {
+            0 => elapsed,
+            limit => elapsed.min(limit),
+        }
+    }
+
+    fn running(&self, now_ms: u64) -> bool {
+        self.paused_at_ms.is_none()
+            && (self.config.time_limit_ms == 0 || self.elapsed_ms(now_ms) < self.config.time_limit_ms)
+    }
+}
+
+static MATCH: Mutex<Option<Match>> = Mutex::new(None);
+
+/// Run `f` on the installed world's match, else the crate-wide one.
+fn with_match<T>(f: impl FnOnce(&mut Option<Match>) -> T) -> T {
+    with_part(&MATCH, |m| &mut m.session, f)
+}
+
+/// Start a match at the caller's `now_ms`, replacing any match in progress and resetting every
+/// player's score.
+// Safe C entry point: `config` is null-checked before use; the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn start_match(config: *const MatchConfig, now_ms: u64) -> DeadAimStatus {
+    ffi_guard(|| {
+        let config = *unsafe { arg_ref(config) }?;
+        clear_scores();
+        let started = Match {
+            config,
+            started_ms: now_ms,
+            paused_at_ms: None,
+            paused_total_ms: 0,
+            shots_fired: 0,
+            shots_hit: 0,
+        };
+        with_match(|current| *current = Some(started));
+        Ok(())
+    })
+}
+
+/// Pause the running match at `now_ms`: the clock stops and rewards are refused until
+/// `resume_match`. `NotInitialized` if no match is in progress or it is already paused.
+#[no_mangle]
+pub extern "C" fn pause_match(now_ms: u64) -> DeadAimStatus {
+    ffi_guard(|| {
+        with_match(|current| match current.as_mut() {
+            Some(m) if m.paused_at_ms.is_none() => {
+                m.paused_at_ms = Some(now_ms.max(m.started_ms));
+                Ok(())
+            }
+            _ => Err(DeadAimStatus::NotInitialized),
+        })
+    })
+}
+
+/// Resume a paused match at `now_ms`. `NotInitialized` if no match is paused.
+#[no_mangle]
+pub extern "C" fn resume_match(now_ms: u64) -> DeadAimStatus {
+    ffi_guard(|| {
+        with_match(|current| {
+            let paused_at = current.as_mut().and_then(|m| m.paused_at_ms.take().map(|at| (m, at)));
+            let (m, paused_at) = paused_at.ok_or(DeadAimStatus::NotInitialized)?;
+            m.paused_total_ms += now_ms.saturating_sub(paused_at);
+            Ok(())
+        })
+    })
+}
+
+/// End the match at `now_ms` and write its result to `out_result`. `NotInitialized` (nothing
+/// written) if no match was in progress.
+// Safe C entry point: `out_result` is null-checked before use; the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn end_match(now_ms: u64, out_result: *mut MatchResult) -> DeadAimStatus {
+    ffi_guard(|| {
+        if out_result.is_null() {
+            return Err(DeadAimStatus::NullPointer);
+        }
+        let m = with_match(Option::take).ok_or(DeadAimStatus::NotInitialized)?;
+        let summary = score_summary(m.config.player_id);
+        let accuracy = if m.shots_fired > 0 { m.shots_hit as f32 / m.shots_fired as f32 } else { 0.0 };
+        let result = MatchResult {
+            kills: summary.kills,
+            shots_fired: m.shots_fired,
+            shots_hit: m.shots_hit,
+            accuracy,
+            duration_ms: m.elapsed_ms(now_ms),
+            score: summary.score,
+        };
+        unsafe { write_out(out_result, result) }
+    })
+}
+
+/// Writes whether a match is started, unpaused and within its time limit at `now_ms`.
+// Safe C entry point: `out_running` is null-checked before use; the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn match_is_running(now_ms: u64, out_running: *mut bool) -> DeadAimStatus {
+    ffi_guard(|| unsafe { write_out(out_running, match_running(now_ms)) })
+}
+
+/// True while a match is started, unpaused and within its time limit at `now_ms`.
+pub(crate) fn match_running(now_ms: u64) -> bool {
+    with_match(|current| current.is_some_and(|m| m.running(now_ms)))
+}
+
+/// True while a match is started and unpaused, ignoring its time limit (for callers without a
+/// clock).
+pub(crate) fn match_unpaused() -> bool {
+    with_match(|current| current.is_some_and(|m| m.paused_at_ms.is_none()))
+}
+
+/// Count a shot toward the running match's accuracy (ignored outside a match or while paused).
+pub(crate) fn record_shot(hit: bool) {
+    with_match(|current| {
+        if let Some(m) = current.as_mut().filter(|m| m.paused_at_ms.is_none()) {
+            m.shots_fired += 1;
+            m.shots_hit += hit as i32;
+        }
+    })
+}
+
+/// Count a hit by a shot already recorded with `record_shot(false)` (e.g. a projectile landing).
+pub(crate) fn record_late_hit() {
+    with_match(|current| {
+        if let Some(m) = current.as_mut().filter(|m| m.paused_at_ms.is_none()) {
+            m.shots_hit += 1;
+        }
+    })
+}
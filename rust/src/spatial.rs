@@ -0,0 +1,404 @@
+// src/spatial.rs
+// Spatial acceleration structures for enemy proximity queries.
+use crate::error::{arg_mut, arg_ref, ffi_guard, slice_arg, slice_arg_mut, write_out, DeadAimStatus};
+use crate::{nearest_alive_index, Enemy};
+use std::collections::HashMap;
+
+/// One bucketed enemy: array index plus a copy of its position at rebuild time.
+type Entry = (u32, f32, f32);
+
+/// Sparse spatial hash: cell coordinate -> alive enemies in that cell (index + position copy).
+/// Cheap to rebuild every frame for moving enemies, and memory stays proportional to occupied
+/// cells, so it suits large, sparse worlds.
+pub struct SpatialHash {
+    cell_size: f32,
+    cells: HashMap<(i32, i32), Vec<Entry>>,
+}
+
+impl SpatialHash {
+    fn cell_of(&self, x: f32, y: f32) -> (i32, i32) {
+        ((x / self.cell_size).floor() as i32, (y / self.cell_size).floor() as i32)
+    }
+
+    fn rebuild(&mut self, enemies: &[Enemy], cell_size: f32) {
+        self.cell_size = cell_size;
+        // keep the per-cell allocations around between frames
+        for bucket in self.cells.values_mut() {
+            bucket.clear();
+        }
+        for (i, e) in enemies.iter().enumerate().filter(|(_, e)| e.is_alive()) {
+            let cell = self.cell_of(e.x, e.y);
+            self.cells.entry(cell).or_default().push((i as u32, e.x, e.y));
+        }
+        self.cells.retain(|_, bucket| !bucket.is_empty());
+    }
+
+    /// Visit every bucketed enemy within `radius` of (x, y) as `(index, dist2)`, in no set order.
+    fn for_each_within(&self, x: f32, y: f32, radius: f32, mut visit: impl FnMut(u32, f32)) {
+        if radius.is_nan() || radius < 0.0 || self.cells.is_empty() {
+            return;
+        }
+        let r2 = radius * radius;
+        let mut collect = |bucket: &Vec<Entry>| {
+            for &(i, ex, ey) in bucket {
+                let d2 = (ex - x).powi(2) + (ey - y).powi(2);
+                if d2 <= r2 {
+                    visit(i, d2);
+                }
+            }
+        };
+        let (lo_x, lo_y) = self.cell_of(x - radius, y - radius);
+        let (hi_x, hi_y) = self.cell_of(x + radius, y + radius);
+        let span = (hi_x as i64 - lo_x as i64 + 1) * (hi_y as i64 - lo_y as i64 + 1);
+        if span > self.cells.len() as i64 {
+            // the query covers more cells than are occupied: just scan the occupied ones
+            self.cells.values().for_each(&mut collect);
+        } else {
+            for cx in lo_x..=hi_x {
+                for cy in lo_y..=hi_y {
+                    if let Some(bucket) = self.cells.get(&(cx, cy)) {
+                        collect(bucket);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Indices of alive enemies within `radius` of (x, y), ascending.
+    fn query_radius(&self, x: f32, y: f32, radius: f32) -> Vec<u32> {
+        let mut found = Vec::new();
+        self.for_each_within(x, y, radius, |i, _| found.push(i));
+        found.sort_unstable();
+        found
+    }
+
+    /// Nearest bucketed enemy within `radius` of (x, y); ties go to the lower index.
+    fn nearest_within(&self, x: f32, y: f32, radius: f32) -> Option<u32> {
+        let mut best: Option<(f32, u32)> = None;
+        self.for_each_within(x, y, radius, |i, d2| {
+            if best.is_none_or(|(b2, bi)| d2 < b2 || (d2 == b2 && i < bi)) {
+                best = Some((d2, i));
+            }
+        });
+        best.map(|(_, i)| i)
+    }
+}
+
+/// Radii probed by an expanding search: `initial_radius`, then growing by `growth` each step,
+/// with the last probe clamped to `max_radius`. A non-positive `growth` jumps straight to the max.
+fn expanding_radii(initial_radius: f32, max_radius: f32, growth: f32) -> impl Iterator<Item = f32> {
+    let start = initial_radius.max(0.0).min(max_radius);
+    let mut next = Some(start);
+    std::iter::from_fn(move || {
+        let r = next?;
+        next = if r >= max_radius {
+            None
+        } else if growth > 0.0 {
+            Some((r + growth).min(max_radius))
+        } else {
+            Some(max_radius)
+        };
+        Some(r)
+    })
+}
+
+/// Expanding nearest: probe the hash at `initial_radius` around (x, y), growing the radius by
+/// `growth` until some enemy is found or `max_radius` is exceeded. The result is the true
+/// nearest within the radius that first found something (so the true nearest overall, when it
+/// lies within `max_radius`). Writes its index as of the last rebuild, or -1. `InvalidArgument`
+/// for a negative/NaN `max_radius`.
+// Safe C entry point: `hash`, `out_index` are null-checked before use; the rest of the contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn spatial_hash_find_nearest_expanding(
+    hash: *const SpatialHash,
+    x: f32,
+    y: f32,
+    initial_radius: f32,
+    max_radius: f32,
+    growth: f32,
+    out_index: *mut i32,
+) -> DeadAimStatus {
+    ffi_guard(|| {
+        let hash = unsafe { arg_ref(hash) }?;
+        if max_radius.is_nan() || max_radius < 0.0 {
+            return Err(DeadAimStatus::InvalidArgument);
+        }
+        let index = expanding_radii(initial_radius, max_radius, growth)
+            .find_map(|r| hash.nearest_within(x, y, r))
+            .map_or(-1, |i| i as i32);
+        unsafe { write_out(out_index, index) }
+    })
+}
+
+/// Array counterpart of `spatial_hash_find_nearest_expanding` for callers without a hash.
+/// Any ring that contains an enemy contains the overall nearest, so this resolves to the nearest
+/// alive enemy if it is within `max_radius`, computed in a single pass (the ring parameters only
+/// change which probe would hit first). Writes its index, or -1.
+// Safe C entry point: `enemies_ptr`, `out_index` are null-checked before use; the rest of the contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn find_nearest_enemy_expanding(
+    player_x: f32,
+    player_y: f32,
+    enemies_ptr: *const Enemy,
+    count: i32,
+    _initial_radius: f32,
+    max_radius: f32,
+    _growth: f32,
+    out_index: *mut i32,
+) -> DeadAimStatus {
+    ffi_guard(|| {
+        let enemies = unsafe { slice_arg(enemies_ptr, count) }?;
+        if max_radius.is_nan() || max_radius < 0.0 {
+            return Err(DeadAimStatus::InvalidArgument);
+        }
+        let nearest = nearest_alive_index(player_x, player_y, enemies);
+        let within = usize::try_from(nearest).ok().map(|i| &enemies[i]).filter(|e| {
+            (e.x - player_x).powi(2) + (e.y - player_y).powi(2) <= max_radius * max_radius
+        });
+        unsafe { write_out(out_index, within.map_or(-1, |_| nearest)) }
+    })
+}
+
+/// Create an empty spatial hash, written to `out_hash`. Free with `spatial_hash_free`.
+// Safe C entry point: `out_hash` is null-checked before use; the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn spatial_hash_new(out_hash: *mut *mut SpatialHash) -> DeadAimStatus {
+    ffi_guard(|| {
+        if out_hash.is_null() {
+            return Err(DeadAimStatus::NullPointer);
+        }
+        let hash = Box::into_raw(Box::new(SpatialHash { cell_size: 1.0, cells: HashMap::new() }));
+        unsafe { write_out(out_hash, hash) }
+    })
+}
+
+/// Free a hash created by `spatial_hash_new`. Null is ignored.
+// Safe C entry point: `hash` is null-checked before use; the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn spatial_hash_free(hash: *mut SpatialHash) -> DeadAimStatus {
+    ffi_guard(|| {
+        if !hash.is_null() {
+            drop(unsafe { Box::from_raw(hash) });
+        }
+        Ok(())
+    })
+}
+
+/// Re-bucket all alive enemies with the given `cell_size` (call after movement each frame).
+/// `InvalidArgument` for a non-positive or non-finite cell size.
+// Safe C entry point: `hash`, `enemies_ptr` are null-checked before use; the rest of the contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn spatial_hash_rebuild(
+    hash: *mut SpatialHash,
+    enemies_ptr: *const Enemy,
+    count: i32,
+    cell_size: f32,
+) -> DeadAimStatus {
+    ffi_guard(|| {
+        let hash = unsafe { arg_mut(hash) }?;
+        let enemies = unsafe { slice_arg(enemies_ptr, count) }?;
+        if !(cell_size.is_finite() && cell_size > 0.0) {
+            return Err(DeadAimStatus::InvalidArgument);
+        }
+        hash.rebuild(enemies, cell_size);
+        Ok(())
+    })
+}
+
+/// Write the indices (ascending) of alive enemies within `radius` of (x, y) as of the last
+/// rebuild into `out_indices` (capacity `out_len`), and the total number found to `out_found`;
+/// the total may exceed `out_len` (only the first `out_len` are written).
+// Safe C entry point: `hash`, `out_indices`, `out_found` are null-checked before use;
+// the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn spatial_hash_query_radius(
+    hash: *const SpatialHash,
+    x: f32,
+    y: f32,
+    radius: f32,
+    out_indices: *mut i32,
+    out_len: i32,
+    out_found: *mut i32,
+) -> DeadAimStatus {
+    ffi_guard(|| {
+        let hash = unsafe { arg_ref(hash) }?;
+        let out = unsafe { slice_arg_mut(out_indices, out_len) }?;
+        let found = hash.query_radius(x, y, radius);
+        for (o, &i) in out.iter_mut().zip(&found) {
+            *o = i as i32;
+        }
+        unsafe { write_out(out_found, found.len() as i32) }
+    })
+}
+
+/// Uniform grid kept in sync incrementally by its owner (see `World`): each alive enemy index is
+/// bucketed by cell and only re-bucketed when it crosses a cell boundary, so per-frame upkeep is
+/// proportional to the enemies that changed cells rather than a full rebuild.
+#[derive(Clone)]
+pub(crate) struct UniformGrid {
+    cell_size: f32,
+    cells: HashMap<(i32, i32), Vec<u32>>,
+    /// Cell each enemy index is bucketed in (`None` = not in the grid, e.g. dead).
+    slots: Vec<Option<(i32, i32)>>,
+    len: usize,
+}
+
+impl UniformGrid {
+    pub(crate) fn new(cell_size: f32) -> Self {
+        UniformGrid { cell_size, cells: HashMap::new(), slots: Vec::new(), len: 0 }
+    }
+
+    fn cell_of(&self, x: f32, y: f32) -> (i32, i32) {
+        ((x / self.cell_size).floor() as i32, (y / self.cell_size).floor() as i32)
+    }
+
+    pub(crate) fn cell_size(&self) -> f32 {
+        self.cell_size
+    }
+
+    /// Re-bucket everything from scratch (e.g. after a cell size change).
+    pub(crate) fn rebuild(&mut self, enemies: &[Enemy], cell_size: f32) {
+        self.cell_size = cell_size;
+        self.cells.clear();
+        self.slots.clear();
+        self.len = 0;
+        for (i, e) in enemies.iter().enumerate() {
+            self.update(i, e);
+        }
+    }
+
+    /// Sync enemy `i`: bucket it if alive (moving it only when its cell changed), drop it if dead.
+    pub(crate) fn update(&mut self, i: usize, e: &Enemy) {
+        if self.slots.len() <= i {
+            self.slots.resize(i + 1, None);
+        }
+        let want = e.is_alive().then(|| self.cell_of(e.x, e.y));
+        let have = self.slots[i];
+        if want == have {
+            return;
+        }
+        if let Some(cell) = have {
+            if let Some(bucket) = self.cells.get_mut(&cell) {
+                bucket.retain(|&j| j as usize != i);
+                if bucket.is_empty() {
+                    self.cells.remove(&cell);
+                }
+            }
+            self.len -= 1;
+        }
+        if let Some(cell) = want {
+            self.cells.entry(cell).or_default().push(i as u32);
+            self.len += 1;
+        }
+        self.slots[i] = want;
+    }
+
+    /// Nearest bucketed enemy to (x, y), searching rings of cells outward and stopping once no
+    /// unvisited cell can hold anything closer. Ties go to the lower index, like the linear scan.
+    pub(crate) fn nearest(&self, x: f32, y: f32, enemies: &[Enemy]) -> i32 {
+        let mut best: Option<(f32, u32)> = None;
+        let consider = |best: &mut Option<(f32, u32)>, i: u32| {
+            let e = &enemies[i as usize];
+            let d2 = (e.x - x).powi(2) + (e.y - y).powi(2);
+            if best.is_none_or(|(b2, bi)| d2 < b2 || (d2 == b2 && i < bi)) {
+                *best = Some((d2, i));
+            }
+        };
+        if self.len == 0 {
+            return -1;
+        }
+        let (cx, cy) = self.cell_of(x, y);
+        let mut visited = 0;
+        for r in 0i32.. {
+            if (8 * r as usize) > self.cells.len() {
+                // the ring is bigger than the occupied set: just scan every occupied cell
+                self.cells.values().flatten().for_each(|&i| consider(&mut best, i));
+                break;
+            }
+            for gx in cx.saturating_sub(r)..=cx.saturating_add(r) {
+                for gy in cy.saturating_sub(r)..=cy.saturating_add(r) {
+                    if (gx - cx).abs() != r && (gy - cy).abs() != r {
+                        continue;
+                    }
+                    if let Some(bucket) = self.cells.get(&(gx, gy)) {
+                        visited += bucket.len();
+                        bucket.iter().for_each(|&i| consider(&mut best, i));
+                    }
+                }
+            }
+            let reach = r as f32 * self.cell_size;
+            if visited == self.len || best.is_some_and(|(d2, _)| d2 <= reach * reach) {
+                break;
+            }
+        }
+        best.map_or(-1, |(_, i)| i as i32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::RngState;
+
+    fn brute_force(enemies: &[Enemy], x: f32, y: f32, radius: f32) -> Vec<i32> {
+        let r2 = radius * radius;
+        (0..enemies.len() as i32)
+            .filter(|&i| {
+                let e = &enemies[i as usize];
+                e.is_alive() && (e.x - x).powi(2) + (e.y - y).powi(2) <= r2
+            })
+            .collect()
+    }
+
+    #[test]
+    fn radius_query_matches_brute_force() {
+        let mut rng = RngState::from_seed(11);
+        let mut enemies: Vec<Enemy> =
+            (0..300).map(|i| Enemy::new(i, rng.range_f32(-500.0, 500.0), rng.range_f32(-500.0, 500.0))).collect();
+        for e in enemies.iter_mut().step_by(7) {
+            e.set_alive(false);
+        }
+        // on the query circle exactly, and stacked in one spot
+        enemies[1].x = 40.0;
+        enemies[1].y = 0.0;
+        enemies[2].x = enemies[3].x;
+        enemies[2].y = enemies[3].y;
+
+        let mut hash = std::ptr::null_mut();
+        assert_eq!(spatial_hash_new(&mut hash), DeadAimStatus::Ok);
+        let mut out = vec![0; enemies.len()];
+        for cell_size in [3.0, 25.0, 400.0] {
+            assert_eq!(spatial_hash_rebuild(hash, enemies.as_ptr(), 300, cell_size), DeadAimStatus::Ok);
+            let mut queries = vec![(0.0, 0.0, 40.0), (0.0, 0.0, 0.0), (enemies[3].x, enemies[3].y, 0.0)];
+            queries.push((-499.0, 499.0, 2000.0));
+            for _ in 0..40 {
+                queries.push((rng.range_f32(-600.0, 600.0), rng.range_f32(-600.0, 600.0), rng.range_f32(0.0, 150.0)));
+            }
+            for (x, y, radius) in queries {
+                let mut found = -1;
+                let status = spatial_hash_query_radius(hash, x, y, radius, out.as_mut_ptr(), 300, &mut found);
+                assert_eq!(status, DeadAimStatus::Ok);
+                assert_eq!(out[..found as usize], brute_force(&enemies, x, y, radius), "({x}, {y}) r={radius}");
+            }
+        }
+        assert_eq!(spatial_hash_free(hash), DeadAimStatus::Ok);
+    }
+
+    #[test]
+    fn truncated_output_still_reports_the_total() {
+        let enemies: Vec<Enemy> = (0..5).map(|i| Enemy::new(i, i as f32, 0.0)).collect();
+        let mut hash = std::ptr::null_mut();
+        spatial_hash_new(&mut hash);
+        spatial_hash_rebuild(hash, enemies.as_ptr(), 5, 1.0);
+        let (mut out, mut found) = ([-1; 2], 0);
+        assert_eq!(spatial_hash_query_radius(hash, 0.0, 0.0, 10.0, out.as_mut_ptr(), 2, &mut found), DeadAimStatus::Ok);
+        assert_eq!((out, found), ([0, 1], 5));
+        spatial_hash_free(hash);
+    }
+}
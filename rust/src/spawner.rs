@@ -0,0 +1,440 @@
+// src/spawner.rs
+// Enemy spawning into preallocated enemy arrays (dead slots are reused).
+use crate::callbacks::dispatch;
+use crate::error::{arg_mut, ffi_guard, slice_arg, slice_arg_mut, write_opt, write_out, DeadAimStatus};
+use crate::events::enemy_spawned;
+use crate::match_state::with_part;
+use crate::rng::{with_rng, RngState};
+use crate::serialize::{Reader, Writer};
+use crate::Enemy;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Mutex;
+
+// f32 bits of the seconds between a summoner's casts; defaults to 5.0.
+static SUMMON_INTERVAL: AtomicU32 = AtomicU32::new(0x40a0_0000);
+
+/// Keep the live population near `target_alive`: revive dead slots as fresh enemies at seeded
+/// random positions inside the given bounds, until the alive count reaches `target_alive`,
+/// spawning at most `max_spawn_per_call`. New ids count up from `start_id`.
+/// Writes how many enemies were spawned to `out_spawned` (may be null).
+// Safe C entry point: `enemies_ptr`, `out_spawned` are null-checked before use;
+// the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn adaptive_spawn(
+    enemies_ptr: *mut Enemy,
+    count: i32,
+    target_alive: i32,
+    max_spawn_per_call: i32,
+    min_x: f32,
+    min_y: f32,
+    max_x: f32,
+    max_y: f32,
+    start_id: i32,
+    out_spawned: *mut i32,
+) -> DeadAimStatus {
+    ffi_guard(|| {
+        let enemies = unsafe { slice_arg_mut(enemies_ptr, count) }?;
+        let alive = enemies.iter().filter(|e| e.is_alive()).count() as i32;
+        let budget = (target_alive - alive).min(max_spawn_per_call).max(0);
+
+        let mut spawned = 0;
+        with_rng(|rng| {
+            for slot in enemies.iter_mut().filter(|e| !e.is_alive()) {
+                if spawned == budget {
+                    break;
+                }
+                let x = rng.range_f32(min_x, max_x);
+                let y = rng.range_f32(min_y, max_y);
+                *slot = Enemy::new(start_id.wrapping_add(spawned), x, y);
+                spawned += 1;
+            }
+        });
+        unsafe { write_opt(out_spawned, spawned) };
+        Ok(())
+    })
+}
+
+/// Set the seconds between summons (the cooldown a summoner resets to after casting).
+/// `InvalidArgument` for a negative or non-finite value.
+#[no_mangle]
+pub extern "C" fn set_summon_interval(seconds: f32) -> DeadAimStatus {
+    ffi_guard(|| {
+        if !(seconds.is_finite() && seconds >= 0.0) {
+            return Err(DeadAimStatus::InvalidArgument);
+        }
+        SUMMON_INTERVAL.store(seconds.to_bits(), Ordering::Relaxed);
+        Ok(())
+    })
+}
+
+/// Advance summoner timers by `dt` seconds. An alive enemy is a summoner when its
+/// `summon_cooldown` is >= 0; once it elapses the summoner revives up to `minions_per_summon`
+/// dead slots as minions at seeded random spots within `summon_radius` of itself (ids counting up
+/// from `start_id`) and its cooldown resets to the summon interval. A summoner with no free slot
+/// stays ready and retries next tick. Writes the number of summoners that fired to `out_fired`
+/// and the total minions spawned to `out_spawned`; either may be null.
+// Safe C entry point: `enemies_ptr`, `out_fired`, `out_spawned` are null-checked before use;
+// the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn tick_summoners(
+    enemies_ptr: *mut Enemy,
+    count: i32,
+    dt: f32,
+    minions_per_summon: i32,
+    summon_radius: f32,
+    start_id: i32,
+    out_fired: *mut i32,
+    out_spawned: *mut i32,
+) -> DeadAimStatus {
+    ffi_guard(|| {
+        let enemies = unsafe { slice_arg_mut(enemies_ptr, count) }?;
+        let mut spawned = 0;
+        let fired = with_rng(|rng| {
+            step_summoners(enemies, dt, minions_per_summon, summon_radius, rng, |_| true, || {
+                let id = start_id.wrapping_add(spawned);
+                spawned += 1;
+                id
+            })
+        });
+        unsafe {
+            write_opt(out_fired, fired);
+            write_opt(out_spawned, spawned);
+        }
+        Ok(())
+    })
+}
+
+/// `tick_summoners` over a slice with an explicit RNG; minions only take dead slots for which
+/// `reusable` is true, and `next_id()` hands out each minion's id. Returns the number of
+/// summoners that fired.
+pub(crate) fn step_summoners(
+    enemies: &mut [Enemy],
+    dt: f32,
+    minions_per_summon: i32,
+    summon_radius: f32,
+    rng: &mut RngState,
+    reusable: impl Fn(&Enemy) -> bool,
+    mut next_id: impl FnMut() -> i32,
+) -> i32 {
+    let interval = f32::from_bits(SUMMON_INTERVAL.load(Ordering::Relaxed));
+    let mut fired = 0;
+    for i in 0..enemies.len() {
+        let summoner = enemies[i];
+        if !summoner.is_alive() || summoner.summon_cooldown < 0.0 {
+            continue;
+        }
+        let cooldown = (summoner.summon_cooldown - dt).max(0.0);
+        enemies[i].summon_cooldown = cooldown;
+        if cooldown > 0.0 || minions_per_summon <= 0 {
+            continue;
+        }
+
+        let mut cast = 0;
+        for slot in enemies.iter_mut().filter(|e| !e.is_alive() && reusable(e)) {
+            if cast == minions_per_summon {
+                break;
+            }
+            let angle = rng.range_f32(0.0, std::f32::consts::TAU);
+            let dist = summon_radius.max(0.0) * rng.next_f32().sqrt();
+            let (x, y) = (summoner.x + angle.cos() * dist, summoner.y + angle.sin() * dist);
+            *slot = Enemy::new(next_id(), x, y);
+            enemy_spawned(slot);
+            cast += 1;
+        }
+        if cast > 0 {
+            enemies[i].summon_cooldown = interval;
+            fired += 1;
+        }
+    }
+    fired
+}
+
+/// Shuffle-bag over enemy types: each cycle draws every type exactly as many times as its
+/// configured count, in seeded random order, so long same-type runs can't happen.
+pub struct ShuffleBag {
+    counts: Vec<i32>,
+    remaining: Vec<i32>,
+}
+
+impl ShuffleBag {
+    fn refill(&mut self) {
+        self.remaining.clear();
+        for (t, &n) in self.counts.iter().enumerate() {
+            self.remaining.extend(std::iter::repeat_n(t as i32, n.max(0) as usize));
+        }
+        with_rng(|rng| {
+            // Fisher-Yates
+            for i in (1..self.remaining.len()).rev() {
+                let j = rng.next_below(i as u32 + 1) as usize;
+                self.remaining.swap(i, j);
+            }
+        });
+    }
+}
+
+/// Create a shuffle bag where type `t` appears `type_counts[t]` times per cycle, written to
+/// `out_bag`. `InvalidArgument` when no type has a positive count. Free with `shuffle_bag_free`.
+// Safe C entry point: `type_counts_ptr`, `out_bag` are null-checked before use;
+// the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn shuffle_bag_new(
+    type_counts_ptr: *const i32,
+    type_count: i32,
+    out_bag: *mut *mut ShuffleBag,
+) -> DeadAimStatus {
+    ffi_guard(|| {
+        let counts = unsafe { slice_arg(type_counts_ptr, type_count) }?;
+        if out_bag.is_null() {
+            return Err(DeadAimStatus::NullPointer);
+        }
+        if !counts.iter().any(|&n| n > 0) {
+            return Err(DeadAimStatus::InvalidArgument);
+        }
+        let bag = Box::into_raw(Box::new(ShuffleBag { counts: counts.to_vec(), remaining: Vec::new() }));
+        unsafe { write_out(out_bag, bag) }
+    })
+}
+
+/// Draw the next enemy type from the bag into `out_type`, reshuffling a full cycle when it runs
+/// empty.
+// Safe C entry point: `bag`, `out_type` are null-checked before use; the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn shuffle_bag_next(bag: *mut ShuffleBag, out_type: *mut i32) -> DeadAimStatus {
+    ffi_guard(|| {
+        let bag = unsafe { arg_mut(bag) }?;
+        if out_type.is_null() {
+            return Err(DeadAimStatus::NullPointer);
+        }
+        if bag.remaining.is_empty() {
+            bag.refill();
+        }
+        unsafe { write_out(out_type, bag.remaining.pop().unwrap_or(-1)) }
+    })
+}
+
+/// Free a bag created by `shuffle_bag_new`. Null is ignored.
+// Safe C entry point: `bag` is null-checked before use; the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn shuffle_bag_free(bag: *mut ShuffleBag) -> DeadAimStatus {
+    ffi_guard(|| {
+        if !bag.is_null() {
+            drop(unsafe { Box::from_raw(bag) });
+        }
+        Ok(())
+    })
+}
+
+/// Host notification that every enemy of `wave_id` is dead.
+type WaveClearedCallback = extern "C" fn(wave_id: i32);
+
+/// The wave being tracked for completion; `current < 0` means none. Registered worlds track
+/// their own (see `match_state`).
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct WaveTracker {
+    current: i32,
+    cleared: bool,
+}
+
+impl WaveTracker {
+    const fn new() -> Self {
+        WaveTracker { current: -1, cleared: false }
+    }
+}
+
+impl Default for WaveTracker {
+    fn default() -> Self {
+        WaveTracker::new()
+    }
+}
+
+static WAVE_TRACKER: Mutex<WaveTracker> = Mutex::new(WaveTracker::new());
+static ON_WAVE_CLEARED: Mutex<Option<WaveClearedCallback>> = Mutex::new(None);
+
+fn with_tracker<T>(f: impl FnOnce(&mut WaveTracker) -> T) -> T {
+    with_part(&WAVE_TRACKER, |m| &mut m.wave, f)
+}
+
+/// Install (or clear, with null) the callback fired once when the current wave is cleared.
+#[no_mangle]
+pub extern "C" fn set_on_wave_cleared(cb: Option<WaveClearedCallback>) -> DeadAimStatus {
+    ffi_guard(|| {
+        *ON_WAVE_CLEARED.lock().unwrap_or_else(|e| e.into_inner()) = cb;
+        Ok(())
+    })
+}
+
+/// Start tracking `wave_id` (enemies tagged via their `wave_id` field). A wave with no alive
+/// members clears, and fires the callback, right away. Negative ids stop tracking.
+// Safe C entry point: `enemies_ptr` is null-checked before use; the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn start_wave(enemies_ptr: *const Enemy, count: i32, wave_id: i32) -> DeadAimStatus {
+    ffi_guard(|| {
+        let enemies = unsafe { slice_arg(enemies_ptr, count) }?;
+        with_tracker(|tracker| *tracker = WaveTracker { current: wave_id, cleared: false });
+        check_wave_cleared(enemies);
+        Ok(())
+    })
+}
+
+/// Called by the kill paths after enemies die: fires the wave-cleared callback the first time
+/// no alive enemy of the current wave remains.
+pub(crate) fn check_wave_cleared(enemies: &[Enemy]) {
+    let cleared = with_tracker(|tracker| {
+        let wave = tracker.current;
+        if wave < 0 || tracker.cleared || enemies.iter().any(|e| e.is_alive() && e.wave_id == wave) {
+            return None;
+        }
+        tracker.cleared = true;
+        Some(wave)
+    });
+    // the tracker is released first so the callback may start the next wave
+    if let Some(wave) = cleared {
+        notify_wave_cleared(wave);
+    }
+}
+
+/// Fire the wave-cleared callback, if one is installed, for `wave_id` (see `dispatch`).
+pub(crate) fn notify_wave_cleared(wave_id: i32) {
+    let cb = *ON_WAVE_CLEARED.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(cb) = cb {
+        dispatch(move || cb(wave_id));
+    }
+}
+
+// ---------- Scripted wave spawning (world-owned) ----------
+
+/// One designer-configured wave: `enemy_count` enemies of `enemy_type`, one every
+/// `spawn_interval` seconds at spawn points picked by the world's RNG.
+#[derive(Clone, Debug)]
+pub(crate) struct WaveConfig {
+    pub enemy_count: i32,
+    pub enemy_type: i32,
+    pub spawn_interval: f32,
+    pub spawn_points: Vec<(f32, f32)>,
+}
+
+/// Runs configured waves in index order: spawns the active wave over time, and once all of
+/// it has spawned and died, reports it cleared and starts the next configured index.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct WaveSpawner {
+    waves: Vec<Option<WaveConfig>>,
+    /// Index of the running wave, or `None` when idle.
+    active: Option<usize>,
+    spawned: i32,
+    /// Seconds until the next spawn.
+    timer: f32,
+}
+
+impl WaveSpawner {
+    pub(crate) fn configure(&mut self, index: usize, config: WaveConfig) {
+        if self.waves.len() <= index {
+            self.waves.resize(index + 1, None);
+        }
+        self.waves[index] = Some(config);
+    }
+
+    /// Begin wave `index`; false if it isn't configured.
+    pub(crate) fn start(&mut self, index: usize) -> bool {
+        if !matches!(self.waves.get(index), Some(Some(_))) {
+            return false;
+        }
+        self.active = Some(index);
+        self.spawned = 0;
+        self.timer = 0.0;
+        true
+    }
+
+    pub(crate) fn active(&self) -> Option<usize> {
+        self.active
+    }
+
+    /// (active wave, enemies spawned from it, seconds to the next spawn).
+    pub(crate) fn progress(&self) -> (Option<usize>, i32, f32) {
+        (self.active, self.spawned, self.timer)
+    }
+
+    /// Advance the spawn timer by `dt` and call `spawn(x, y, enemy_type, wave_index)` for each
+    /// enemy due; a `false` return (no room) stops spawning until the next call.
+    pub(crate) fn step_spawns(
+        &mut self,
+        dt: f32,
+        rng: &mut RngState,
+        mut spawn: impl FnMut(f32, f32, i32, i32) -> bool,
+    ) {
+        let Some(index) = self.active else {
+            return;
+        };
+        let Some(wave) = &self.waves[index] else {
+            return;
+        };
+        self.timer -= dt;
+        while self.spawned < wave.enemy_count && self.timer <= 0.0 {
+            let (x, y) = match wave.spawn_points.len() {
+                0 => (0.0, 0.0),
+                n => wave.spawn_points[rng.next_below(n as u32) as usize],
+            };
+            if !spawn(x, y, wave.enemy_type, index as i32) {
+                break;
+            }
+            self.spawned += 1;
+            self.timer += wave.spawn_interval;
+        }
+    }
+
+    /// If the active wave has fully spawned and none of it is alive, finish it (starting the
+    /// next index when configured), fire the wave-cleared callback and return its index.
+    pub(crate) fn check_cleared(&mut self, enemies: &[Enemy]) -> Option<i32> {
+        let index = self.active?;
+        let total = self.waves[index].as_ref().map_or(0, |w| w.enemy_count);
+        let wave_id = index as i32;
+        if self.spawned < total || enemies.iter().any(|e| e.is_alive() && e.wave_id == wave_id) {
+            return None;
+        }
+        if !self.start(index + 1) {
+            self.active = None;
+        }
+        notify_wave_cleared(wave_id);
+        Some(wave_id)
+    }
+
+    pub(crate) fn write_snapshot(&self, w: &mut Writer) {
+        w.u32(self.waves.len() as u32);
+        for wave in &self.waves {
+            w.u8(wave.is_some() as u8);
+            if let Some(wave) = wave {
+                w.i32(wave.enemy_count);
+                w.i32(wave.enemy_type);
+                w.f32(wave.spawn_interval);
+                w.points(&wave.spawn_points);
+            }
+        }
+        w.i32(self.active.map_or(-1, |i| i as i32));
+        w.i32(self.spawned);
+        w.f32(self.timer);
+    }
+
+    /// `None` for truncated input or an active index that isn't a configured wave.
+    pub(crate) fn read_snapshot(r: &mut Reader) -> Option<Self> {
+        let count = r.u32()?;
+        let waves = (0..count)
+            .map(|_| {
+                if !r.bool()? {
+                    return Some(None);
+                }
+                let (enemy_count, enemy_type, spawn_interval) = (r.i32()?, r.i32()?, r.f32()?);
+                Some(Some(WaveConfig { enemy_count, enemy_type, spawn_interval, spawn_points: r.points()? }))
+            })
+            .collect::<Option<Vec<_>>>()?;
+        let active = usize::try_from(r.i32()?).ok();
+        if active.is_some_and(|i| !matches!(waves.get(i), Some(Some(_)))) {
+            return None;
+        }
+        Some(WaveSpawner { waves, active, spawned: r.i32()?, timer: r.f32()? })
+    }
+}
@@ -0,0 +1,145 @@
+// src/stats.rs
+// Aim-trainer analytics: a log of every shot the host reports, with accuracy, reaction-time and
+// shots-per-kill aggregates over it.
+use crate::error::{ffi_guard, slice_arg_mut, write_out, DeadAimStatus};
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+/// One recorded shot, as dumped by `stats_get_events`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct ShotEvent {
+    /// Caller's clock when the shot was fired.
+    pub time_ms: u64,
+    /// Enemy aimed at, or -1 for a shot at nothing in particular.
+    pub enemy_id: i32,
+    pub hit: bool,
+    pub killed: bool,
+    /// Shooter-to-target distance in world units.
+    pub distance: f32,
+    /// Time since the target appeared (see `stats_target_appeared`), or -1 if it never did.
+    pub since_appeared_ms: i64,
+}
+
+/// Aggregates over every recorded shot, from `stats_get_summary`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ShotStats {
+    pub shots: i32,
+    pub hits: i32,
+    pub kills: i32,
+    /// Hits per shot, in percent (0 with no shots).
+    pub accuracy_percent: f32,
+    /// Median time from a target appearing to the first shot at it, in ms (-1 with no data).
+    pub median_reaction_ms: f32,
+    /// Shots fired per kill (0 with no kills).
+    pub shots_per_kill: f32,
+}
+
+struct StatsLog {
+    events: Vec<ShotEvent>,
+    /// Enemy id -> when it appeared and the reaction time to it, once shot at.
+    targets: BTreeMap<i32, (u64, Option<u64>)>,
+}
+
+static STATS: Mutex<StatsLog> = Mutex::new(StatsLog { events: Vec::new(), targets: BTreeMap::new() });
+
+/// Note that enemy `enemy_id` became visible to the player at `now_ms`; its reaction time is
+/// measured to the first shot at it. Calling again (e.g. on respawn) starts a new measurement.
+#[no_mangle]
+pub extern "C" fn stats_target_appeared(enemy_id: i32, now_ms: u64) -> DeadAimStatus {
+    ffi_guard(|| {
+        STATS.lock().unwrap_or_else(|e| e.into_inner()).targets.insert(enemy_id, (now_ms, None));
+        Ok(())
+    })
+}
+
+/// Record a shot at `enemy_id` (-1 for none) at `now_ms` from `distance` away: whether it `hit`
+/// and whether the hit `killed`.
+#[no_mangle]
+pub extern "C" fn stats_record_shot(
+    enemy_id: i32,
+    hit: bool,
+    killed: bool,
+    distance: f32,
+    now_ms: u64,
+) -> DeadAimStatus {
+    ffi_guard(|| {
+        let mut stats = STATS.lock().unwrap_or_else(|e| e.into_inner());
+        let since_appeared_ms = match stats.targets.get_mut(&enemy_id) {
+            Some((appeared, reaction)) => {
+                let since = now_ms.saturating_sub(*appeared);
+                reaction.get_or_insert(since);
+                since as i64
+            }
+            None => -1,
+        };
+        stats.events.push(ShotEvent {
+            time_ms: now_ms,
+            enemy_id,
+            hit,
+            killed: hit && killed,
+            distance,
+            since_appeared_ms,
+        });
+        Ok(())
+    })
+}
+
+/// Write the aggregates over every recorded shot to `out`.
+// Safe C entry point: `out` is null-checked before use; the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn stats_get_summary(out: *mut ShotStats) -> DeadAimStatus {
+    ffi_guard(|| {
+        let stats = STATS.lock().unwrap_or_else(|e| e.into_inner());
+        let shots = stats.events.len() as i32;
+        let hits = stats.events.iter().filter(|e| e.hit).count() as i32;
+        let kills = stats.events.iter().filter(|e| e.killed).count() as i32;
+
+        let mut reactions: Vec<u64> = stats.targets.values().filter_map(|&(_, r)| r).collect();
+        reactions.sort_unstable();
+        let mid = reactions.len() / 2;
+        let median_reaction_ms = match reactions.len() {
+            0 => -1.0,
+            n if n % 2 == 1 => reactions[mid] as f32,
+            _ => (reactions[mid - 1] as f32 + reactions[mid] as f32) / 2.0,
+        };
+        let summary = ShotStats {
+            shots,
+            hits,
+            kills,
+            accuracy_percent: if shots > 0 { hits as f32 * 100.0 / shots as f32 } else { 0.0 },
+            median_reaction_ms,
+            shots_per_kill: if kills > 0 { shots as f32 / kills as f32 } else { 0.0 },
+        };
+        unsafe { write_out(out, summary) }
+    })
+}
+
+/// Copy the recorded shots, oldest first, to `out` (up to `out_cap`) and write the total number
+/// recorded to `out_total`.
+// Safe C entry point: `out`, `out_total` are null-checked before use; the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn stats_get_events(out: *mut ShotEvent, out_cap: i32, out_total: *mut i32) -> DeadAimStatus {
+    ffi_guard(|| {
+        let out = unsafe { slice_arg_mut(out, out_cap) }?;
+        let stats = STATS.lock().unwrap_or_else(|e| e.into_inner());
+        for (o, event) in out.iter_mut().zip(&stats.events) {
+            *o = *event;
+        }
+        unsafe { write_out(out_total, stats.events.len() as i32) }
+    })
+}
+
+/// Forget every recorded shot and target (e.g. at the start of a training session).
+#[no_mangle]
+pub extern "C" fn stats_reset() -> DeadAimStatus {
+    ffi_guard(|| {
+        let mut stats = STATS.lock().unwrap_or_else(|e| e.into_inner());
+        stats.events.clear();
+        stats.targets.clear();
+        Ok(())
+    })
+}
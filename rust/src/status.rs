@@ -0,0 +1,92 @@
+// src/status.rs
+// Timed status effects (slow, stun, burn, poison) tracked per enemy by the world.
+use crate::serialize::{Reader, Writer};
+
+/// Kinds of status effect; the discriminant is also the effect's bit in `world_status_mask`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StatusKind {
+    /// Movement speed reduced by `magnitude` (0..=1 fraction).
+    Slow,
+    /// Cannot move.
+    Stun,
+    /// `magnitude` damage per second; reapplying refreshes the timer and keeps the stronger burn.
+    Burn,
+    /// `magnitude` damage per second; reapplying stacks the damage and refreshes the timer.
+    Poison,
+}
+
+const KIND_COUNT: usize = 4;
+
+/// One enemy's active effects as (seconds remaining, magnitude) per kind.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct StatusSet {
+    effects: [(f32, f32); KIND_COUNT],
+}
+
+impl StatusSet {
+    pub(crate) fn apply(&mut self, kind: StatusKind, duration: f32, magnitude: f32) {
+        let (remaining, current) = &mut self.effects[kind as usize];
+        let active = *remaining > 0.0;
+        *current = match kind {
+            StatusKind::Poison if active => *current + magnitude,
+            StatusKind::Burn | StatusKind::Slow if active => current.max(magnitude),
+            _ => magnitude,
+        };
+        *remaining = remaining.max(duration);
+    }
+
+    /// (seconds remaining, magnitude) of `kind`, if active.
+    pub(crate) fn get(&self, kind: StatusKind) -> Option<(f32, f32)> {
+        let effect = self.effects[kind as usize];
+        (effect.0 > 0.0).then_some(effect)
+    }
+
+    /// (seconds remaining, magnitude) of every kind, active or not, in discriminant order.
+    pub(crate) fn effects(&self) -> &[(f32, f32); KIND_COUNT] {
+        &self.effects
+    }
+
+    /// Bit `1 << kind` set for every active effect.
+    pub(crate) fn mask(&self) -> u32 {
+        self.effects.iter().enumerate().filter(|(_, e)| e.0 > 0.0).fold(0, |m, (i, _)| m | 1 << i)
+    }
+
+    /// Movement multiplier: 0 while stunned, else reduced by the slow.
+    pub(crate) fn speed_factor(&self) -> f32 {
+        if self.get(StatusKind::Stun).is_some() {
+            return 0.0;
+        }
+        self.get(StatusKind::Slow).map_or(1.0, |(_, m)| (1.0 - m).clamp(0.0, 1.0))
+    }
+
+    /// Damage over time owed for the next `dt` seconds (effects expiring sooner count only
+    /// their remaining time).
+    pub(crate) fn dot_damage(&self, dt: f32) -> f32 {
+        let owed = |kind| self.get(kind).map_or(0.0, |(left, m): (f32, f32)| m.max(0.0) * left.min(dt));
+        owed(StatusKind::Burn) + owed(StatusKind::Poison)
+    }
+
+    /// Count every timer down by `dt`; returns false once nothing is active.
+    pub(crate) fn tick(&mut self, dt: f32) -> bool {
+        for effect in self.effects.iter_mut() {
+            effect.0 = (effect.0 - dt).max(0.0);
+        }
+        self.mask() != 0
+    }
+
+    pub(crate) fn write_snapshot(&self, w: &mut Writer) {
+        for &(remaining, magnitude) in &self.effects {
+            w.f32(remaining);
+            w.f32(magnitude);
+        }
+    }
+
+    pub(crate) fn read_snapshot(r: &mut Reader) -> Option<Self> {
+        let mut set = StatusSet::default();
+        for effect in &mut set.effects {
+            *effect = (r.f32()?, r.f32()?);
+        }
+        Some(set)
+    }
+}
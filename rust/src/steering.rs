@@ -0,0 +1,446 @@
+// src/steering.rs
+// Goal-directed enemy movement (as opposed to the random walk in lib.rs).
+use crate::arena::confine_enemies;
+use crate::error::{ffi_guard, slice_arg, slice_arg_mut, DeadAimStatus};
+use crate::obstacles::{segment_clear, Rect};
+use crate::rng::{with_rng, RngState};
+use crate::serialize::{Reader, Writer};
+use std::sync::Mutex;
+use crate::{consume_stun, random_walk, speed_scale, tick_flinch, Enemy};
+
+/// Refresh each alive enemy's last-seen player position, but only when it has line of sight
+/// to the player through the given obstacles. Enemies without sight keep their old memory.
+// Safe C entry point: `enemies_ptr`, `obstacles_ptr` are null-checked before use;
+// the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn update_target_memory(
+    enemies_ptr: *mut Enemy,
+    count: i32,
+    player_x: f32,
+    player_y: f32,
+    obstacles_ptr: *const Rect,
+    obstacle_count: i32,
+) -> DeadAimStatus {
+    ffi_guard(|| {
+        let enemies = unsafe { slice_arg_mut(enemies_ptr, count) }?;
+        let obstacles = unsafe { slice_arg(obstacles_ptr, obstacle_count) }?;
+        for e in enemies.iter_mut().filter(|e| e.is_alive()) {
+            if segment_clear(e.x, e.y, player_x, player_y, obstacles) {
+                e.last_seen_x = player_x;
+                e.last_seen_y = player_y;
+            }
+        }
+        Ok(())
+    })
+}
+
+/// Seek: move each alive enemy up to `speed` toward its last-seen player position
+/// (see `update_target_memory`), stopping on arrival. Taunted enemies pursue the taunt point
+/// instead, using up one taunt frame per tick. Stunned enemies hold still; flinching ones move
+/// at half speed. `InvalidArgument` for a negative/NaN speed.
+// Safe C entry point: `enemies_ptr` is null-checked before use; the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn move_enemies_seek(enemies_ptr: *mut Enemy, count: i32, speed: f32) -> DeadAimStatus {
+    ffi_guard(|| {
+        let enemies = unsafe { slice_arg_mut(enemies_ptr, count) }?;
+        if speed.is_nan() || speed < 0.0 {
+            return Err(DeadAimStatus::InvalidArgument);
+        }
+        if speed == 0.0 {
+            return Ok(());
+        }
+        for e in enemies.iter_mut().filter(|e| e.is_alive()) {
+            if consume_stun(e) {
+                continue;
+            }
+            let (goal_x, goal_y) = if e.taunt_frames > 0 {
+                e.taunt_frames -= 1;
+                (e.taunt_x, e.taunt_y)
+            } else {
+                (e.last_seen_x, e.last_seen_y)
+            };
+            let dx = goal_x - e.x;
+            let dy = goal_y - e.y;
+            let dist = (dx * dx + dy * dy).sqrt();
+            let speed = speed * speed_scale(e) * tick_flinch(e);
+            let step = if dist <= speed { 1.0 } else { speed / dist };
+            e.vx = dx * step;
+            e.vy = dy * step;
+            e.x += e.vx;
+            e.y += e.vy;
+        }
+        confine_enemies(enemies);
+        Ok(())
+    })
+}
+
+/// Circle-strafe: move each alive enemy `speed` per tick around the player, keeping
+/// `orbit_radius` distance. Off-radius enemies spiral in or out (the radial correction takes
+/// priority over the tangential part, total speed stays `speed`). Stunned enemies hold still;
+/// flinching ones move at half speed. `InvalidArgument` for a negative/NaN speed or radius.
+// Safe C entry point: `enemies_ptr` is null-checked before use; the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn move_enemies_orbit(
+    enemies_ptr: *mut Enemy,
+    count: i32,
+    player_x: f32,
+    player_y: f32,
+    orbit_radius: f32,
+    speed: f32,
+    clockwise: bool,
+) -> DeadAimStatus {
+    ffi_guard(|| {
+        let enemies = unsafe { slice_arg_mut(enemies_ptr, count) }?;
+        if speed.is_nan() || speed < 0.0 || orbit_radius.is_nan() || orbit_radius < 0.0 {
+            return Err(DeadAimStatus::InvalidArgument);
+        }
+        if speed == 0.0 {
+            return Ok(());
+        }
+        for e in enemies.iter_mut().filter(|e| e.is_alive()) {
+            if consume_stun(e) {
+                continue;
+            }
+            let speed = speed * speed_scale(e) * tick_flinch(e);
+            let (rx, ry) = (e.x - player_x, e.y - player_y);
+            let dist = (rx * rx + ry * ry).sqrt();
+            // radial unit (outward); an enemy on top of the player is pushed out along +x
+            let (ux, uy) = if dist > f32::EPSILON { (rx / dist, ry / dist) } else { (1.0, 0.0) };
+            let (tx, ty) = if clockwise { (uy, -ux) } else { (-uy, ux) };
+
+            let radial = (orbit_radius - dist).clamp(-speed, speed);
+            let tangential = (speed * speed - radial * radial).max(0.0).sqrt();
+            e.vx = ux * radial + tx * tangential;
+            e.vy = uy * radial + ty * tangential;
+            e.x += e.vx;
+            e.y += e.vy;
+        }
+        confine_enemies(enemies);
+        Ok(())
+    })
+}
+
+/// Crowd variation: nudge each alive, moving enemy's `vx/vy` by a seeded random offset of up to
+/// `max_jitter` per axis, then rescale to the original speed so only the heading wanders.
+/// Positions are untouched; resting enemies (zero velocity) stay at rest. `InvalidArgument` for a
+/// non-finite jitter.
+// Safe C entry point: `enemies_ptr` is null-checked before use; the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn jitter_velocities(enemies_ptr: *mut Enemy, count: i32, max_jitter: f32) -> DeadAimStatus {
+    ffi_guard(|| {
+        let enemies = unsafe { slice_arg_mut(enemies_ptr, count) }?;
+        let jitter = max_jitter.abs();
+        if !jitter.is_finite() {
+            return Err(DeadAimStatus::InvalidArgument);
+        }
+        if jitter == 0.0 {
+            return Ok(());
+        }
+        with_rng(|rng| {
+            for e in enemies.iter_mut().filter(|e| e.is_alive()) {
+                let speed = (e.vx * e.vx + e.vy * e.vy).sqrt();
+                if speed <= f32::EPSILON {
+                    continue;
+                }
+                let vx = e.vx + rng.range_f32(-jitter, jitter);
+                let vy = e.vy + rng.range_f32(-jitter, jitter);
+                let len = (vx * vx + vy * vy).sqrt();
+                if len > f32::EPSILON {
+                    e.vx = vx / len * speed;
+                    e.vy = vy / len * speed;
+                }
+            }
+        });
+        Ok(())
+    })
+}
+
+// Gap kept between a cover spot and the obstacle edge, in world units.
+const COVER_MARGIN: f32 = 0.5;
+
+/// Spot directly behind `r` as seen from the player, just clear of its far corner; `None` when it
+/// would still be in the open (e.g. the player stands inside the box).
+fn cover_spot(r: &Rect, player_x: f32, player_y: f32, obstacles: &[Rect]) -> Option<(f32, f32)> {
+    let (cx, cy) = ((r.min_x + r.max_x) * 0.5, (r.min_y + r.max_y) * 0.5);
+    let (dx, dy) = (cx - player_x, cy - player_y);
+    let dist = (dx * dx + dy * dy).sqrt();
+    if dist <= f32::EPSILON {
+        return None;
+    }
+    let half_diag = 0.5 * ((r.max_x - r.min_x).powi(2) + (r.max_y - r.min_y).powi(2)).sqrt();
+    let reach = half_diag + COVER_MARGIN;
+    let spot = (cx + dx / dist * reach, cy + dy / dist * reach);
+    (!segment_clear(player_x, player_y, spot.0, spot.1, obstacles)).then_some(spot)
+}
+
+/// Take cover: each alive enemy the player can see moves up to `speed` toward the nearest spot
+/// behind an obstacle that breaks the player's line of sight to it. Enemies already hidden (or
+/// with no usable cover) hold position. Stunned enemies hold still; flinching ones move at half
+/// speed. `InvalidArgument` for a negative/NaN speed.
+// Safe C entry point: `enemies_ptr`, `obstacles_ptr` are null-checked before use;
+// the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn move_enemies_to_cover(
+    enemies_ptr: *mut Enemy,
+    count: i32,
+    player_x: f32,
+    player_y: f32,
+    speed: f32,
+    obstacles_ptr: *const Rect,
+    obstacle_count: i32,
+) -> DeadAimStatus {
+    ffi_guard(|| {
+        let enemies = unsafe { slice_arg_mut(enemies_ptr, count) }?;
+        let obstacles = unsafe { slice_arg(obstacles_ptr, obstacle_count) }?;
+        if speed.is_nan() || speed < 0.0 {
+            return Err(DeadAimStatus::InvalidArgument);
+        }
+        if speed == 0.0 || obstacles.is_empty() {
+            return Ok(());
+        }
+        let spots: Vec<(f32, f32)> = obstacles
+            .iter()
+            .filter_map(|r| cover_spot(r, player_x, player_y, obstacles))
+            .collect();
+
+        for e in enemies.iter_mut().filter(|e| e.is_alive()) {
+            if consume_stun(e) {
+                continue;
+            }
+            let target = spots
+                .iter()
+                .map(|&(sx, sy)| (sx, sy, (sx - e.x).powi(2) + (sy - e.y).powi(2)))
+                .min_by(|a, b| a.2.total_cmp(&b.2));
+            let hidden = !segment_clear(e.x, e.y, player_x, player_y, obstacles);
+            let Some((sx, sy, dist2)) = target.filter(|_| !hidden) else {
+                e.vx = 0.0;
+                e.vy = 0.0;
+                continue;
+            };
+            let speed = speed * speed_scale(e) * tick_flinch(e);
+            let dist = dist2.sqrt();
+            let step = if dist <= speed { 1.0 } else { speed / dist };
+            e.vx = (sx - e.x) * step;
+            e.vy = (sy - e.y) * step;
+            e.x += e.vx;
+            e.y += e.vy;
+        }
+        confine_enemies(enemies);
+        Ok(())
+    })
+}
+
+// ---------- Per-enemy steering behaviors ----------
+
+/// Movement behavior for one enemy, applied once per movement tick at up to `speed`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Behavior {
+    /// The random walk of `move_enemies_randomly` (the default).
+    Wander,
+    /// Head straight for the target, stopping on it.
+    Seek { target_x: f32, target_y: f32 },
+    /// Run directly away from the point.
+    Flee { from_x: f32, from_y: f32 },
+    /// Seek, but ease off linearly inside `slow_radius` so the enemy settles on the target.
+    Arrive { target_x: f32, target_y: f32, slow_radius: f32 },
+}
+
+impl Behavior {
+    /// Move `e` one tick. Stunned enemies hold still; flinching ones move at half speed.
+    pub(crate) fn apply(&self, e: &mut Enemy, speed: f32, rng: &mut RngState) {
+        // heading, speed factor, and whether the move stops on the target instead of passing it
+        let (dx, dy, ease, stops) = match *self {
+            Behavior::Wander => {
+                random_walk(std::slice::from_mut(e), speed, |step| rng.range_f32(-step, step));
+                return;
+            }
+            Behavior::Seek { target_x, target_y } => (target_x - e.x, target_y - e.y, 1.0, true),
+            Behavior::Flee { from_x, from_y } => {
+                let (dx, dy) = (e.x - from_x, e.y - from_y);
+                // standing on the threat: pick +x rather than freezing
+                if dx * dx + dy * dy <= f32::EPSILON { (1.0, 0.0, 1.0, false) } else { (dx, dy, 1.0, false) }
+            }
+            Behavior::Arrive { target_x, target_y, slow_radius } => {
+                let (dx, dy) = (target_x - e.x, target_y - e.y);
+                let ease = if slow_radius > 0.0 { ((dx * dx + dy * dy).sqrt() / slow_radius).min(1.0) } else { 1.0 };
+                (dx, dy, ease, true)
+            }
+        };
+        if consume_stun(e) {
+            return;
+        }
+        let speed = speed * ease * speed_scale(e) * tick_flinch(e);
+        let dist = (dx * dx + dy * dy).sqrt();
+        let step = if stops && dist <= speed { 1.0 } else { speed / dist.max(f32::EPSILON) };
+        e.vx = dx * step;
+        e.vy = dy * step;
+        e.x += e.vx;
+        e.y += e.vy;
+    }
+
+    pub(crate) fn write_snapshot(&self, w: &mut Writer) {
+        let (tag, params) = match *self {
+            Behavior::Wander => (0, [0.0; 3]),
+            Behavior::Seek { target_x, target_y } => (1, [target_x, target_y, 0.0]),
+            Behavior::Flee { from_x, from_y } => (2, [from_x, from_y, 0.0]),
+            Behavior::Arrive { target_x, target_y, slow_radius } => (3, [target_x, target_y, slow_radius]),
+        };
+        w.u8(tag);
+        params.into_iter().for_each(|v| w.f32(v));
+    }
+
+    pub(crate) fn read_snapshot(r: &mut Reader) -> Option<Self> {
+        let (tag, a, b, c) = (r.u8()?, r.f32()?, r.f32()?, r.f32()?);
+        Some(match tag {
+            0 => Behavior::Wander,
+            1 => Behavior::Seek { target_x: a, target_y: b },
+            2 => Behavior::Flee { from_x: a, from_y: b },
+            3 => Behavior::Arrive { target_x: a, target_y: b, slow_radius: c },
+            _ => return None,
+        })
+    }
+}
+
+/// Move every alive enemy one tick with the same `behavior` at up to `speed` (wandering draws
+/// from the seeded global RNG). `InvalidArgument` for a negative/NaN speed.
+// Safe C entry point: `enemies_ptr` is null-checked before use; the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn move_enemies_behavior(
+    enemies_ptr: *mut Enemy,
+    count: i32,
+    behavior: Behavior,
+    speed: f32,
+) -> DeadAimStatus {
+    ffi_guard(|| {
+        let enemies = unsafe { slice_arg_mut(enemies_ptr, count) }?;
+        if speed.is_nan() || speed < 0.0 {
+            return Err(DeadAimStatus::InvalidArgument);
+        }
+        if speed == 0.0 {
+            return Ok(());
+        }
+        with_rng(|rng| {
+            for e in enemies.iter_mut().filter(|e| e.is_alive()) {
+                behavior.apply(e, speed, rng);
+            }
+        });
+        confine_enemies(enemies);
+        Ok(())
+    })
+}
+
+// ---------- Flocking ----------
+
+/// Boids tuning for swarm enemies: each rule looks at flock-mates within its radius, and the
+/// weighted sum becomes an extra displacement (capped at the tick's move speed).
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct FlockingParams {
+    /// Enemies with this `type_id` flock; -1 disables flocking.
+    pub swarm_type_id: i32,
+    /// Push away from mates closer than this (stronger the closer they are).
+    pub separation_radius: f32,
+    pub separation_weight: f32,
+    /// Match the average velocity of mates within this radius.
+    pub alignment_radius: f32,
+    pub alignment_weight: f32,
+    /// Drift toward the center of mates within this radius.
+    pub cohesion_radius: f32,
+    pub cohesion_weight: f32,
+}
+
+static FLOCKING: Mutex<FlockingParams> = Mutex::new(FlockingParams {
+    swarm_type_id: -1,
+    separation_radius: 0.0,
+    separation_weight: 0.0,
+    alignment_radius: 0.0,
+    alignment_weight: 0.0,
+    cohesion_radius: 0.0,
+    cohesion_weight: 0.0,
+});
+
+/// Configure flocking for `move_enemies_randomly` and `world_tick` (null disables it).
+// Safe C entry point: `params` is null-checked before use; the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn set_flocking(params: *const FlockingParams) -> DeadAimStatus {
+    ffi_guard(|| {
+        let mut flocking = FLOCKING.lock().unwrap_or_else(|e| e.into_inner());
+        match unsafe { params.as_ref() } {
+            Some(p) => *flocking = *p,
+            None => flocking.swarm_type_id = -1,
+        }
+        Ok(())
+    })
+}
+
+/// Apply one tick of flocking to the alive swarm enemies, moving each at most `speed`.
+/// Forces are computed from the positions before anyone moves, so order doesn't matter.
+pub(crate) fn apply_flocking(enemies: &mut [Enemy], speed: f32) {
+    let p = *FLOCKING.lock().unwrap_or_else(|e| e.into_inner());
+    if p.swarm_type_id < 0 || speed <= 0.0 {
+        return;
+    }
+    let is_mate = |e: &Enemy| e.is_alive() && e.type_id == p.swarm_type_id;
+    let moves: Vec<(usize, f32, f32)> = enemies
+        .iter()
+        .enumerate()
+        .filter(|(_, e)| is_mate(e) && e.stun_frames <= 0)
+        .map(|(i, e)| {
+            let (mut sep_x, mut sep_y) = (0.0, 0.0);
+            let (mut vel_x, mut vel_y, mut vel_n) = (0.0, 0.0, 0);
+            let (mut sum_x, mut sum_y, mut sum_n) = (0.0, 0.0, 0);
+            for (j, o) in enemies.iter().enumerate() {
+                if j == i || !is_mate(o) {
+                    continue;
+                }
+                let (dx, dy) = (e.x - o.x, e.y - o.y);
+                let dist2 = dx * dx + dy * dy;
+                if dist2 < p.separation_radius * p.separation_radius && dist2 > f32::EPSILON {
+                    sep_x += dx / dist2;
+                    sep_y += dy / dist2;
+                }
+                if dist2 <= p.alignment_radius * p.alignment_radius {
+                    vel_x += o.vx;
+                    vel_y += o.vy;
+                    vel_n += 1;
+                }
+                if dist2 <= p.cohesion_radius * p.cohesion_radius {
+                    sum_x += o.x;
+                    sum_y += o.y;
+                    sum_n += 1;
+                }
+            }
+            let mut fx = p.separation_weight * sep_x;
+            let mut fy = p.separation_weight * sep_y;
+            if vel_n > 0 {
+                fx += p.alignment_weight * (vel_x / vel_n as f32 - e.vx);
+                fy += p.alignment_weight * (vel_y / vel_n as f32 - e.vy);
+            }
+            if sum_n > 0 {
+                fx += p.cohesion_weight * (sum_x / sum_n as f32 - e.x);
+                fy += p.cohesion_weight * (sum_y / sum_n as f32 - e.y);
+            }
+            let len = (fx * fx + fy * fy).sqrt();
+            if len > speed {
+                fx *= speed / len;
+                fy *= speed / len;
+            }
+            (i, fx, fy)
+        })
+        .collect();
+    for (i, fx, fy) in moves {
+        let e = &mut enemies[i];
+        e.x += fx;
+        e.y += fy;
+        e.vx += fx;
+        e.vy += fy;
+    }
+}
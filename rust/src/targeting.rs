@@ -0,0 +1,89 @@
+// src/targeting.rs
+// Weighted target prioritization beyond plain nearest-enemy selection.
+use crate::error::{arg_ref, ffi_guard, slice_arg, write_out, DeadAimStatus};
+use crate::Enemy;
+
+/// Weights for `find_highest_threat_enemy`; each term is normalized to 0..=1 before weighting,
+/// so weights read as relative importance (negative weights invert a preference).
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct TargetingConfig {
+    /// Closeness: 1 at the player, falling linearly to 0 at `max_range`.
+    pub distance_weight: f32,
+    /// How directly the enemy is moving at the player (1 = straight at them, 0 = not approaching).
+    pub facing_weight: f32,
+    /// Remaining health fraction (enemies without a health model count as full).
+    pub health_weight: f32,
+    /// Per-type bonus: `type_weights[type_id]`, 0 for types outside the table. Nullable.
+    pub type_weights: *const f32,
+    pub type_weight_count: i32,
+    /// Enemies farther than this are never picked; non-positive means unlimited (the distance
+    /// term then uses 1 / (1 + distance)).
+    pub max_range: f32,
+}
+
+impl TargetingConfig {
+    fn type_weight(&self, type_id: i32) -> f32 {
+        if self.type_weights.is_null() || type_id < 0 || type_id >= self.type_weight_count {
+            return 0.0;
+        }
+        unsafe { *self.type_weights.add(type_id as usize) }
+    }
+
+    /// Threat score of `e` for a player at (`px`, `py`), or `None` when out of range.
+    fn score(&self, px: f32, py: f32, e: &Enemy) -> Option<f32> {
+        let (dx, dy) = (px - e.x, py - e.y);
+        let dist = (dx * dx + dy * dy).sqrt();
+        let closeness = if self.max_range > 0.0 {
+            if dist > self.max_range {
+                return None;
+            }
+            1.0 - dist / self.max_range
+        } else {
+            1.0 / (1.0 + dist)
+        };
+        let speed = (e.vx * e.vx + e.vy * e.vy).sqrt();
+        let facing = if speed > f32::EPSILON && dist > f32::EPSILON {
+            ((e.vx * dx + e.vy * dy) / (speed * dist)).max(0.0)
+        } else {
+            0.0
+        };
+        let health = if e.max_health > 0.0 { (e.health / e.max_health).clamp(0.0, 1.0) } else { 1.0 };
+        Some(
+            self.distance_weight * closeness
+                + self.facing_weight * facing
+                + self.health_weight * health
+                + self.type_weight(e.type_id),
+        )
+    }
+}
+
+/// Threat-based targeting: score every alive enemy by the weighted terms in `config` and write
+/// the highest scorer's index (ties keep the lower index), or -1 if none qualifies.
+// Safe C entry point: `enemies_ptr`, `config`, `out_index` are null-checked before use;
+// the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn find_highest_threat_enemy(
+    player_x: f32,
+    player_y: f32,
+    enemies_ptr: *const Enemy,
+    count: i32,
+    config: *const TargetingConfig,
+    out_index: *mut i32,
+) -> DeadAimStatus {
+    ffi_guard(|| {
+        let enemies = unsafe { slice_arg(enemies_ptr, count) }?;
+        let config = unsafe { arg_ref(config) }?;
+        let mut best: Option<(usize, f32)> = None;
+        for (i, e) in enemies.iter().enumerate().filter(|(_, e)| e.is_alive()) {
+            let Some(score) = config.score(player_x, player_y, e) else {
+                continue;
+            };
+            if best.is_none_or(|(_, s)| score > s) {
+                best = Some((i, score));
+            }
+        }
+        unsafe { write_out(out_index, best.map_or(-1, |(i, _)| i as i32)) }
+    })
+}
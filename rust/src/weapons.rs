@@ -0,0 +1,206 @@
+// src/weapons.rs
+// Weapon stats plus the fire-rate / magazine / reload state machine, timed in host milliseconds.
+use crate::combat::first_enemy_on_segment;
+use crate::error::{arg_mut, arg_ref, ffi_guard, slice_arg, write_opt, write_out, DeadAimStatus};
+use crate::rng::with_rng;
+use crate::session::record_shot;
+use crate::Enemy;
+
+/// A weapon's tuning and its live firing state. Initialize with `weapon_init`; the state fields
+/// are advanced by `weapon_fire` / `weapon_reload` and may be read (not written) by the host.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct Weapon {
+    /// Damage dealt per shot.
+    pub damage: f32,
+    /// Shots per second; non-positive means no cooldown between shots.
+    pub fire_rate: f32,
+    /// Half-angle of the random spread cone in radians (0 = perfectly accurate).
+    pub spread: f32,
+    /// Rounds per magazine; non-positive means unlimited ammo.
+    pub magazine_size: i32,
+    /// Seconds a reload takes.
+    pub reload_time: f32,
+    /// Projectile speed in world units per second (0 = hitscan).
+    pub projectile_speed: f32,
+    /// Rounds left in the magazine.
+    pub ammo: i32,
+    /// Earliest time the next shot may leave the barrel.
+    pub next_fire_ms: u64,
+    /// When the reload in progress completes, or 0 when not reloading.
+    pub reload_end_ms: u64,
+}
+
+impl Weapon {
+    fn reloading(&self, now_ms: u64) -> bool {
+        self.reload_end_ms != 0 && now_ms < self.reload_end_ms
+    }
+
+    /// Ammo at `now_ms`, counting a reload that has completed but not yet been applied.
+    fn ammo_at(&self, now_ms: u64) -> i32 {
+        if self.reload_end_ms != 0 && now_ms >= self.reload_end_ms {
+            self.magazine_size
+        } else {
+            self.ammo
+        }
+    }
+
+    fn finish_reload(&mut self, now_ms: u64) {
+        self.ammo = self.ammo_at(now_ms);
+        if !self.reloading(now_ms) {
+            self.reload_end_ms = 0;
+        }
+    }
+
+    fn can_fire(&self, now_ms: u64) -> bool {
+        !self.reloading(now_ms)
+            && now_ms >= self.next_fire_ms
+            && (self.magazine_size <= 0 || self.ammo_at(now_ms) > 0)
+    }
+}
+
+/// Seconds to milliseconds, clamping negatives and NaN to 0.
+fn secs_to_ms(secs: f32) -> u64 {
+    if secs.is_nan() || secs <= 0.0 {
+        0
+    } else {
+        (secs as f64 * 1000.0).round() as u64
+    }
+}
+
+/// Initialize `weapon` with the given tuning, a full magazine and no cooldown.
+// Safe C entry point: `weapon` is null-checked before use; the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn weapon_init(
+    weapon: *mut Weapon,
+    damage: f32,
+    fire_rate: f32,
+    spread: f32,
+    magazine_size: i32,
+    reload_time: f32,
+    projectile_speed: f32,
+) -> DeadAimStatus {
+    ffi_guard(|| {
+        let w = Weapon {
+            damage,
+            fire_rate,
+            spread: spread.abs(),
+            magazine_size,
+            reload_time,
+            projectile_speed,
+            ammo: magazine_size.max(0),
+            next_fire_ms: 0,
+            reload_end_ms: 0,
+        };
+        unsafe { write_out(weapon, w) }
+    })
+}
+
+/// Writes whether `weapon` could fire at `now_ms`: not mid-reload, off cooldown and with a round
+/// loaded.
+// Safe C entry point: `weapon`, `out_ready` are null-checked before use; the rest of the contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn weapon_can_fire(weapon: *const Weapon, now_ms: u64, out_ready: *mut bool) -> DeadAimStatus {
+    ffi_guard(|| {
+        let w = unsafe { arg_ref(weapon) }?;
+        unsafe { write_out(out_ready, w.can_fire(now_ms)) }
+    })
+}
+
+/// Pull the trigger at `now_ms` aiming at `aim_angle` (radians). If the weapon can fire this
+/// consumes a round, starts the fire-rate cooldown, writes the shot's angle with random spread
+/// applied (from the seeded RNG) to `out_angle` (may be null) and writes true to `out_fired`;
+/// otherwise it writes false without side effects.
+// Safe C entry point: `weapon`, `out_fired`, `out_angle` are null-checked before use;
+// the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn weapon_fire(
+    weapon: *mut Weapon,
+    now_ms: u64,
+    aim_angle: f32,
+    out_fired: *mut bool,
+    out_angle: *mut f32,
+) -> DeadAimStatus {
+    ffi_guard(|| {
+        let w = unsafe { arg_mut(weapon) }?;
+        if out_fired.is_null() {
+            return Err(DeadAimStatus::NullPointer);
+        }
+        if !w.can_fire(now_ms) {
+            return unsafe { write_out(out_fired, false) };
+        }
+        w.finish_reload(now_ms);
+        if w.magazine_size > 0 {
+            w.ammo -= 1;
+        }
+        let cooldown_ms = if w.fire_rate > 0.0 { secs_to_ms(1.0 / w.fire_rate) } else { 0 };
+        w.next_fire_ms = now_ms.saturating_add(cooldown_ms);
+        let angle = aim_angle + with_rng(|rng| rng.range_f32(-w.spread, w.spread));
+        unsafe { write_opt(out_angle, angle) };
+        unsafe { write_out(out_fired, true) }
+    })
+}
+
+/// Start reloading at `now_ms`; the magazine refills once `reload_time` has elapsed and the
+/// weapon can't fire in the meantime. Writes whether a reload started to `out_started` (may be
+/// null): false if already reloading, the magazine is full, or the weapon has unlimited ammo.
+// Safe C entry point: `weapon`, `out_started` are null-checked before use; the rest of the contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn weapon_reload(weapon: *mut Weapon, now_ms: u64, out_started: *mut bool) -> DeadAimStatus {
+    ffi_guard(|| {
+        let w = unsafe { arg_mut(weapon) }?;
+        w.finish_reload(now_ms);
+        let started = w.magazine_size > 0 && !w.reloading(now_ms) && w.ammo < w.magazine_size;
+        if started {
+            let reload_ms = secs_to_ms(w.reload_time);
+            if reload_ms == 0 {
+                w.ammo = w.magazine_size;
+            } else {
+                w.reload_end_ms = now_ms.saturating_add(reload_ms);
+            }
+        }
+        unsafe { write_opt(out_started, started) };
+        Ok(())
+    })
+}
+
+/// Resolve a hitscan shot: rotate the aim direction (`dir_x`, `dir_y`) by a random angle in
+/// `±spread_rad` (from the seeded RNG), cast a ray of `max_range` from the origin and write the
+/// index of the first alive enemy whose hitbox (see `set_enemy_hit_radius`) it crosses, or -1 on
+/// a miss. `InvalidArgument` for a zero direction or a non-positive range. Deals no damage.
+/// Counts toward the running match's accuracy.
+// Safe C entry point: `enemies_ptr`, `out_index` are null-checked before use; the rest of the contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn fire_hitscan(
+    origin_x: f32,
+    origin_y: f32,
+    dir_x: f32,
+    dir_y: f32,
+    spread_rad: f32,
+    max_range: f32,
+    enemies_ptr: *const Enemy,
+    count: i32,
+    out_index: *mut i32,
+) -> DeadAimStatus {
+    ffi_guard(|| {
+        let enemies = unsafe { slice_arg(enemies_ptr, count) }?;
+        let len = (dir_x * dir_x + dir_y * dir_y).sqrt();
+        if len <= f32::EPSILON || max_range.is_nan() || max_range <= 0.0 {
+            return Err(DeadAimStatus::InvalidArgument);
+        }
+        if out_index.is_null() {
+            return Err(DeadAimStatus::NullPointer);
+        }
+        let spread = spread_rad.abs();
+        let angle = dir_y.atan2(dir_x) + with_rng(|rng| rng.range_f32(-spread, spread));
+        let (end_x, end_y) = (origin_x + angle.cos() * max_range, origin_y + angle.sin() * max_range);
+        let hit = first_enemy_on_segment(enemies, origin_x, origin_y, end_x, end_y);
+        record_shot(hit.is_some());
+        unsafe { write_out(out_index, hit.map_or(-1, |(i, _)| i as i32)) }
+    })
+}
@@ -0,0 +1,2173 @@
+// src/world.rs
+// Rust-owned enemy storage behind an opaque handle, so hosts don't have to mirror the `Enemy`
+// layout or manage the array themselves.
+use crate::ai::{AiConfig, AiState};
+use crate::arena::confine_enemies;
+use crate::behavior_tree::{
+    BehaviorTree, BtContext, BT_ACT_FLEE_PLAYER, BT_ACT_FOLLOW_ROUTE, BT_ACT_HOLD, BT_ACT_SEEK_PLAYER,
+};
+use crate::collision::separate_enemies;
+use crate::combat::{damage_enemy, first_enemy_on_segment, safe_at, step_dodge_cooldowns};
+use crate::enemy_types::new_typed;
+use crate::error::{
+    arg_mut, arg_ref, ffi_guard, slice_arg, slice_arg_mut, write_opt, write_out, DeadAimStatus, FfiResult,
+};
+use crate::events::{enemy_killed, enemy_spawned, player_damaged};
+use crate::heatmap::record_player_death;
+use crate::match_state::MatchState;
+use crate::pathfinding::NavGrid;
+use crate::player::Player;
+use crate::projectiles::ProjectileSet;
+use crate::rng::{seeded, RngState, DEFAULT_SEED};
+use crate::serialize::{
+    enemy_len, read_enemy, write_enemy, Reader, Writer, FORMAT_VERSION as ENEMY_FORMAT_VERSION,
+};
+use crate::session::record_shot;
+use crate::spatial::UniformGrid;
+use crate::spawner::{step_summoners, WaveConfig, WaveSpawner};
+use crate::status::{StatusKind, StatusSet};
+use crate::steering::{apply_flocking, Behavior};
+use crate::{consume_stun, nearest_matching, random_walk, speed_scale, tick_flinch, Enemy};
+use std::collections::{HashMap, VecDeque};
+
+// Grid cell edge, in world units, for new worlds.
+const DEFAULT_CELL_SIZE: f32 = 8.0;
+// Fixed simulation step for new worlds, in seconds (60 Hz).
+const DEFAULT_TICK_STEP: f32 = 1.0 / 60.0;
+// Most fixed steps one `world_tick` runs; time beyond that is dropped so a long stall can't
+// snowball into ever-longer catch-up frames.
+const MAX_STEPS_PER_TICK: u32 = 8;
+
+/// A game world owning up to `capacity` enemies plus a uniform grid over the alive ones.
+/// Create with `world_create`.
+#[derive(Clone)]
+pub struct World {
+    enemies: Vec<Enemy>,
+    capacity: usize,
+    next_id: i32,
+    grid: UniformGrid,
+    /// Id of the enemy held by `world_lock_target`, or -1.
+    locked_id: i32,
+    /// This world's own random stream; reseed with `world_set_rng_seed`.
+    rng: RngState,
+    /// Fixed simulation step (seconds) and the unsimulated time carried between `world_tick`s.
+    step: f32,
+    accumulator: f32,
+    /// Random-walk speed in world units per second used by `world_tick` (0 = no wandering).
+    move_speed: f32,
+    /// Summoning parameters used by `world_tick` (see `tick_summoners`).
+    summon_minions: i32,
+    summon_radius: f32,
+    /// Whether movement updates push overlapping enemies apart (see `world_set_enemy_collision`).
+    enemy_collision: bool,
+    /// Walls for `world_enemy_path_to`; set with `world_set_nav_grid`.
+    nav: Option<NavGrid>,
+    /// Waypoint routes being followed during `world_tick`, keyed by enemy id.
+    routes: HashMap<i32, Route>,
+    /// Steering behaviors set with `world_set_enemy_behavior`, keyed by enemy id (default wander).
+    behaviors: HashMap<i32, Behavior>,
+    /// The player, moved by `world_set_player_input`; AI and behavior trees react to it.
+    player: Player,
+    /// AI thresholds; `None` leaves every enemy idle (plain behavior/route movement).
+    ai: Option<AiConfig>,
+    /// Current AI state per enemy id (absent = idle).
+    ai_states: HashMap<i32, AiState>,
+    /// Trees loaded with `world_load_behavior_tree` (indexed by tree id) and which enemy ids
+    /// run which tree; a bound tree overrides the AI state machine.
+    trees: Vec<BehaviorTree>,
+    tree_bindings: HashMap<i32, usize>,
+    /// Scripted waves (see `world_configure_wave`).
+    waves: WaveSpawner,
+    /// Active status effects per enemy id (see `world_apply_status`).
+    statuses: HashMap<i32, StatusSet>,
+    /// Where killed enemies come back (see `world_set_respawn_points`), and the seconds left
+    /// before each pending respawn, keyed by enemy id.
+    respawn_points: Vec<(f32, f32)>,
+    respawns: HashMap<i32, f32>,
+    /// Projectiles in flight, launched with `world_spawn_projectile`.
+    projectiles: ProjectileSet,
+    /// Events, match, scores, safe zones, heatmap and tracked wave used while a registry call
+    /// runs on this world (see `match_state`; not snapshotted).
+    match_state: MatchState,
+    /// Recent enemy positions for `world_fire_hitscan_at_time`, oldest first (not snapshotted).
+    history: VecDeque<HistoryFrame>,
+    /// Each slot's position and alive flag before the last simulated step or applied delta, for
+    /// `world_get_interpolated_positions` (not snapshotted).
+    prev_positions: Vec<(f32, f32, bool)>,
+}
+
+/// Waypoints an enemy walks through in order instead of wandering.
+#[derive(Clone, Debug)]
+struct Route {
+    points: Vec<(f32, f32)>,
+    next: usize,
+    /// Start over from the first waypoint after the last (patrols) instead of finishing.
+    looping: bool,
+    /// A waypoint counts as reached once the enemy is this close.
+    tolerance: f32,
+}
+
+impl Route {
+    /// Move on to the next waypoint; false once a non-looping route is finished.
+    fn advance(&mut self) -> bool {
+        self.next += 1;
+        if self.looping && self.next == self.points.len() {
+            self.next = 0;
+        }
+        self.next < self.points.len()
+    }
+
+    /// Walk `e` up to `speed` toward the next waypoint, advancing on arrival. Returns false once
+    /// the last waypoint of a non-looping route is reached.
+    fn follow(&mut self, e: &mut Enemy, speed: f32) -> bool {
+        if consume_stun(e) {
+            return true;
+        }
+        let (tx, ty) = self.points[self.next];
+        if (tx - e.x).powi(2) + (ty - e.y).powi(2) <= self.tolerance * self.tolerance && !self.advance() {
+            return false;
+        }
+        let step = speed * speed_scale(e) * tick_flinch(e);
+        let (tx, ty) = self.points[self.next];
+        let (dx, dy) = (tx - e.x, ty - e.y);
+        let dist = (dx * dx + dy * dy).sqrt();
+        let arrived = dist <= step;
+        let (mx, my) = if arrived { (dx, dy) } else { (dx / dist * step, dy / dist * step) };
+        e.x += mx;
+        e.y += my;
+        e.vx = mx;
+        e.vy = my;
+        !arrived || self.advance()
+    }
+}
+
+/// Copy `count` interleaved x, y pairs; `InvalidArgument` for a negative count.
+unsafe fn points_from_raw(points_ptr: *const f32, count: i32) -> FfiResult<Vec<(f32, f32)>> {
+    let count = usize::try_from(count).map_err(|_| DeadAimStatus::InvalidArgument)?;
+    let coords = slice_arg(points_ptr, count * 2)?;
+    Ok(coords.chunks_exact(2).map(|p| (p[0], p[1])).collect())
+}
+
+impl World {
+    /// Mutate the enemies through `f`, then re-sync the grid for every enemy.
+    pub(crate) fn with_enemies_mut<R>(&mut self, f: impl FnOnce(&mut [Enemy]) -> R) -> R {
+        let result = f(&mut self.enemies);
+        self.resync_grid();
+        result
+    }
+
+    /// Add a fresh enemy of `type_id` at (x, y), reusing a dead slot once the world is full.
+    /// Returns its index, or `None` when every slot holds a living enemy.
+    fn spawn_enemy(&mut self, x: f32, y: f32, type_id: i32) -> Option<usize> {
+        let index = if self.enemies.len() < self.capacity {
+            self.enemies.push(Enemy::new(0, 0.0, 0.0));
+            self.enemies.len() - 1
+        } else {
+            // slots waiting to respawn stay reserved
+            self.enemies.iter().position(|e| !e.is_alive() && !self.respawns.contains_key(&e.id))?
+        };
+        self.enemies[index] = new_typed(self.next_id, x, y, type_id);
+        self.next_id = self.next_id.wrapping_add(1);
+        self.grid.update(index, &self.enemies[index]);
+        enemy_spawned(&self.enemies[index]);
+        Some(index)
+    }
+
+    /// Count down pending respawns by `dt` seconds and bring back every enemy whose timer ran
+    /// out, fresh (full health, no statuses, idle) at the spawn point farthest from the player.
+    fn step_respawns(&mut self, dt: f32) {
+        let mut due: Vec<i32> = Vec::new();
+        self.respawns.retain(|&id, remaining| {
+            *remaining -= dt;
+            if *remaining > 0.0 {
+                return true;
+            }
+            due.push(id);
+            false
+        });
+        due.sort_unstable();
+        let (px, py) = (self.player.x, self.player.y);
+        let farthest = self
+            .respawn_points
+            .iter()
+            .copied()
+            .reduce(|best, p| {
+                let d2 = |(x, y): (f32, f32)| (x - px).powi(2) + (y - py).powi(2);
+                if d2(p) > d2(best) { p } else { best }
+            });
+        for id in due {
+            let Some(e) = self.enemies.iter_mut().find(|e| e.id == id && !e.is_alive()) else {
+                continue;
+            };
+            let (x, y) = farthest.unwrap_or((e.x, e.y));
+            *e = new_typed(id, x, y, e.type_id);
+            enemy_spawned(e);
+            self.statuses.remove(&id);
+            self.ai_states.remove(&id);
+        }
+    }
+
+    /// Run the wave spawner for `dt` seconds; returns the wave index cleared, if any.
+    fn step_waves(&mut self, dt: f32) -> Option<i32> {
+        let mut waves = std::mem::take(&mut self.waves);
+        let mut rng = self.rng;
+        waves.step_spawns(dt, &mut rng, |x, y, enemy_type, wave| {
+            let Some(i) = self.spawn_enemy(x, y, enemy_type) else {
+                return false;
+            };
+            self.enemies[i].wave_id = wave;
+            true
+        });
+        let cleared = waves.check_cleared(&self.enemies);
+        self.rng = rng;
+        self.waves = waves;
+        cleared
+    }
+
+    /// Run the wave-cleared check after a kill outside `world_tick`, so the callback fires on
+    /// the kill itself rather than on the next spawner step.
+    fn check_waves_after_kill(&mut self) {
+        self.waves.check_cleared(&self.enemies);
+    }
+
+    fn resync_grid(&mut self) {
+        for (i, e) in self.enemies.iter().enumerate() {
+            self.grid.update(i, e);
+        }
+    }
+
+    /// Nearest alive enemy to (x, y) via the grid, or -1.
+    pub(crate) fn nearest(&self, x: f32, y: f32) -> i32 {
+        self.grid.nearest(x, y, &self.enemies)
+    }
+
+    /// Run `f` with the world's own match state installed in place of the crate-wide one.
+    pub(crate) fn with_match_state<T>(&mut self, f: impl FnOnce(&mut World) -> T) -> T {
+        let mut state = std::mem::take(&mut self.match_state);
+        let out = state.install(|| f(self));
+        self.match_state = state;
+        out
+    }
+}
+
+/// Create an empty world that can hold `capacity` enemies and write it to `out_world`.
+/// `InvalidArgument` for a negative capacity. Free with `world_destroy`.
+// Safe C entry point: `out_world` is null-checked before use; the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn world_create(capacity: i32, out_world: *mut *mut World) -> DeadAimStatus {
+    ffi_guard(|| {
+        if out_world.is_null() {
+            return Err(DeadAimStatus::NullPointer);
+        }
+        let world = new_world(usize::try_from(capacity).map_err(|_| DeadAimStatus::InvalidArgument)?);
+        unsafe { write_out(out_world, Box::into_raw(Box::new(world))) }
+    })
+}
+
+/// An empty world that can hold `capacity` enemies. `capacity` is only a limit: the enemy buffer
+/// grows as enemies spawn, so a huge capacity costs nothing up front.
+pub(crate) fn new_world(capacity: usize) -> World {
+    World {
+        enemies: Vec::new(),
+        capacity,
+        next_id: 0,
+        grid: UniformGrid::new(DEFAULT_CELL_SIZE),
+        locked_id: -1,
+        rng: seeded(DEFAULT_SEED),
+        step: DEFAULT_TICK_STEP,
+        accumulator: 0.0,
+        move_speed: 0.0,
+        summon_minions: 0,
+        summon_radius: 0.0,
+        enemy_collision: true,
+        nav: None,
+        routes: HashMap::new(),
+        behaviors: HashMap::new(),
+        player: Player::default(),
+        ai: None,
+        ai_states: HashMap::new(),
+        trees: Vec::new(),
+        tree_bindings: HashMap::new(),
+        waves: WaveSpawner::default(),
+        statuses: HashMap::new(),
+        respawn_points: Vec::new(),
+        respawns: HashMap::new(),
+        projectiles: ProjectileSet::new(),
+        match_state: MatchState::default(),
+        history: VecDeque::new(),
+        prev_positions: Vec::new(),
+    }
+}
+
+/// Set the spatial grid's cell edge (world units; about the typical query distance works well)
+/// and re-bucket all enemies. `InvalidArgument` for a non-positive size.
+// Safe C entry point: `world` is null-checked before use; the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn world_set_grid_cell_size(world: *mut World, cell_size: f32) -> DeadAimStatus {
+    ffi_guard(|| {
+        let world = unsafe { arg_mut(world) }?;
+        if !(cell_size.is_finite() && cell_size > 0.0) {
+            return Err(DeadAimStatus::InvalidArgument);
+        }
+        world.grid.rebuild(&world.enemies, cell_size);
+        Ok(())
+    })
+}
+
+/// Free a world created by `world_create`. Null is ignored.
+// Safe C entry point: `world` is null-checked before use; the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn world_destroy(world: *mut World) -> DeadAimStatus {
+    ffi_guard(|| {
+        if !world.is_null() {
+            drop(unsafe { Box::from_raw(world) });
+        }
+        Ok(())
+    })
+}
+
+/// Copy `world` into a new, independent world: enemies, player, RNG stream, fixed-step
+/// accumulator, projectiles in flight, match state and every per-enemy table, so ticking the
+/// copy gives exactly what ticking the original would. Enemies are plain data, so this is a
+/// handful of flat copies; cheap enough to keep one per frame for rollback and resimulate from
+/// it. Callbacks fire again while resimulating, and crate-wide side effects (events and scores
+/// of unregistered worlds) are not rolled back. Writes the copy to `out_world`; free it with
+/// `world_destroy`.
+// Safe C entry point: `world`, `out_world` are null-checked before use; the rest of the contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn world_clone(world: *const World, out_world: *mut *mut World) -> DeadAimStatus {
+    ffi_guard(|| {
+        let world = unsafe { arg_ref(world) }?;
+        if out_world.is_null() {
+            return Err(DeadAimStatus::NullPointer);
+        }
+        unsafe { write_out(out_world, Box::into_raw(Box::new(world.clone()))) }
+    })
+}
+
+/// Spawn an enemy at (`x`, `y`) with the next free id and write its index to `out_index` (may be
+/// null). `InvalidArgument` for a full world.
+// Safe C entry point: `world`, `out_index` are null-checked before use; the rest of the contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn world_add_enemy(world: *mut World, x: f32, y: f32, out_index: *mut i32) -> DeadAimStatus {
+    ffi_guard(|| {
+        let world = unsafe { arg_mut(world) }?;
+        if world.enemies.len() >= world.capacity {
+            return Err(DeadAimStatus::InvalidArgument);
+        }
+        let index = world.enemies.len();
+        world.enemies.push(Enemy::new(world.next_id, x, y));
+        world.next_id = world.next_id.wrapping_add(1);
+        world.grid.update(index, &world.enemies[index]);
+        enemy_spawned(&world.enemies[index]);
+        unsafe { write_opt(out_index, index as i32) };
+        Ok(())
+    })
+}
+
+/// Writes the number of enemies in the world (alive or dead).
+// Safe C entry point: `world`, `out_count` are null-checked before use; the rest of the contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn world_enemy_count(world: *const World, out_count: *mut i32) -> DeadAimStatus {
+    ffi_guard(|| {
+        let world = unsafe { arg_ref(world) }?;
+        unsafe { write_out(out_count, world.enemies.len() as i32) }
+    })
+}
+
+/// Writes the world's enemy array itself (`world_enemy_count` `Enemy` records, `repr(C)`), so
+/// hosts can read every enemy in place: in wasm, a view over linear memory at this offset. Valid
+/// until the next call that adds enemies or frees the world; treat it as read-only to keep the
+/// spatial grid in sync.
+// Safe C entry point: `world`, `out_ptr` are null-checked before use; the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn world_enemy_buffer_ptr(world: *const World, out_ptr: *mut *const Enemy) -> DeadAimStatus {
+    ffi_guard(|| {
+        let world = unsafe { arg_ref(world) }?;
+        unsafe { write_out(out_ptr, world.enemies.as_ptr()) }
+    })
+}
+
+/// Copy the enemy at `index` into `out`. `InvalidIndex` for a bad index.
+// Safe C entry point: `world`, `out` are null-checked before use; the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn world_get_enemy(world: *const World, index: i32, out: *mut Enemy) -> DeadAimStatus {
+    ffi_guard(|| {
+        let world = unsafe { arg_ref(world) }?;
+        let e = world.enemy_at(index)?;
+        unsafe { write_out(out, *e) }
+    })
+}
+
+impl World {
+    /// The enemy at `index`; `InvalidIndex` outside the world.
+    fn enemy_at(&self, index: i32) -> FfiResult<&Enemy> {
+        usize::try_from(index).ok().and_then(|i| self.enemies.get(i)).ok_or(DeadAimStatus::InvalidIndex)
+    }
+}
+
+/// `find_nearest_enemy` over the world's enemies, answered from the spatial grid instead of a
+/// full scan (same result, including ties). Writes -1 when none are alive.
+// Safe C entry point: `world`, `out_index` are null-checked before use; the rest of the contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn world_find_nearest_enemy(
+    world: *const World,
+    player_x: f32,
+    player_y: f32,
+    out_index: *mut i32,
+) -> DeadAimStatus {
+    ffi_guard(|| {
+        let world = unsafe { arg_ref(world) }?;
+        unsafe { write_out(out_index, world.nearest(player_x, player_y)) }
+    })
+}
+
+/// Kill the enemy at `index` (a no-op for one that is already dead). Clearing the running
+/// wave fires the `set_on_wave_cleared` callback before this returns. `InvalidIndex` for a bad
+/// index.
+// Safe C entry point: `world` is null-checked before use; the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn world_shoot_enemy(world: *mut World, index: i32) -> DeadAimStatus {
+    ffi_guard(|| {
+        let world = unsafe { arg_mut(world) }?;
+        let Some(i) = usize::try_from(index).ok().filter(|&i| i < world.enemies.len()) else {
+            return Err(DeadAimStatus::InvalidIndex);
+        };
+        let e = &mut world.enemies[i];
+        if e.is_alive() {
+            enemy_killed(e);
+        }
+        e.health = 0.0;
+        e.set_alive(false);
+        world.grid.update(i, &world.enemies[i]);
+        world.check_waves_after_kill();
+        Ok(())
+    })
+}
+
+/// Kill the alive enemy at `index` and bring it back (same id and type, full health) after
+/// `delay_seconds` of `world_tick` time, at the respawn point farthest from the player (see
+/// `world_set_respawn_points`; where it died if none are set). Its slot is not reused by other
+/// spawns meanwhile. `InvalidIndex` for a bad index or an enemy that is already dead.
+// Safe C entry point: `world` is null-checked before use; the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn world_kill_enemy_with_respawn(world: *mut World, index: i32, delay_seconds: f32) -> DeadAimStatus {
+    ffi_guard(|| {
+        let world = unsafe { arg_mut(world) }?;
+        let Some(i) = usize::try_from(index).ok().filter(|&i| world.enemies.get(i).is_some_and(|e| e.is_alive())) else {
+            return Err(DeadAimStatus::InvalidIndex);
+        };
+        let e = &mut world.enemies[i];
+        e.health = 0.0;
+        e.set_alive(false);
+        enemy_killed(e);
+        world.grid.update(i, &world.enemies[i]);
+        let delay = if delay_seconds.is_finite() { delay_seconds.max(0.0) } else { 0.0 };
+        world.respawns.insert(world.enemies[i].id, delay);
+        world.check_waves_after_kill();
+        Ok(())
+    })
+}
+
+/// Set the points respawning enemies choose from (`point_count` interleaved x, y pairs; the
+/// data is copied). 0 points respawns enemies where they died.
+// Safe C entry point: `world`, `points_ptr` are null-checked before use; the rest of the contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn world_set_respawn_points(
+    world: *mut World,
+    points_ptr: *const f32,
+    point_count: i32,
+) -> DeadAimStatus {
+    ffi_guard(|| {
+        let world = unsafe { arg_mut(world) }?;
+        world.respawn_points = unsafe { points_from_raw(points_ptr, point_count) }?;
+        Ok(())
+    })
+}
+
+/// Writes the seconds until the enemy at `index` respawns, or -1.0 if it has no respawn pending.
+/// `InvalidIndex` for a bad index.
+// Safe C entry point: `world`, `out_seconds` are null-checked before use; the rest of the contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn world_respawn_remaining(world: *const World, index: i32, out_seconds: *mut f32) -> DeadAimStatus {
+    ffi_guard(|| {
+        let world = unsafe { arg_ref(world) }?;
+        let e = world.enemy_at(index)?;
+        let remaining = world.respawns.get(&e.id).map_or(-1.0, |&remaining| remaining.max(0.0));
+        unsafe { write_out(out_seconds, remaining) }
+    })
+}
+
+/// Reseed the world's random stream (with the algorithm selected by `set_rng_algorithm`); two
+/// worlds with equal seeds and inputs simulate identically, independent of the global RNG.
+// Safe C entry point: `world` is null-checked before use; the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn world_set_rng_seed(world: *mut World, seed: u64) -> DeadAimStatus {
+    ffi_guard(|| {
+        unsafe { arg_mut(world) }?.rng = seeded(seed);
+        Ok(())
+    })
+}
+
+/// `move_enemies_randomly` over the world's enemies, drawing from the world's own RNG; the grid
+/// is updated incrementally. Zero speed does nothing; `InvalidArgument` for a negative/NaN speed.
+// Safe C entry point: `world` is null-checked before use; the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn world_move_enemies_randomly(world: *mut World, speed: f32) -> DeadAimStatus {
+    ffi_guard(|| {
+        let world = unsafe { arg_mut(world) }?;
+        if speed.is_nan() || speed < 0.0 {
+            return Err(DeadAimStatus::InvalidArgument);
+        }
+        if speed == 0.0 {
+            return Ok(());
+        }
+        let (mut rng, collide) = (world.rng, world.enemy_collision);
+        world.with_enemies_mut(|enemies| {
+            random_walk(enemies, speed, |step| rng.range_f32(-step, step));
+            apply_flocking(enemies, speed);
+            if collide {
+                separate_enemies(enemies);
+            }
+            confine_enemies(enemies);
+        });
+        world.rng = rng;
+        Ok(())
+    })
+}
+
+/// `spawn_projectile` into the world: the projectile flies during `world_tick` and hits only this
+/// world's enemies. Writes its id to `out_id`; `InvalidArgument` for a non-finite position,
+/// direction or speed.
+// Safe C entry point: `world`, `out_id` are null-checked before use; the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn world_spawn_projectile(
+    world: *mut World,
+    x: f32,
+    y: f32,
+    dir: f32,
+    speed: f32,
+    gravity: f32,
+    damage: f32,
+    out_id: *mut i32,
+) -> DeadAimStatus {
+    ffi_guard(|| {
+        let world = unsafe { arg_mut(world) }?;
+        if out_id.is_null() {
+            return Err(DeadAimStatus::NullPointer);
+        }
+        let id = world.projectiles.spawn(x, y, dir, speed, gravity, damage);
+        if id < 0 {
+            return Err(DeadAimStatus::InvalidArgument);
+        }
+        unsafe { write_out(out_id, id) }
+    })
+}
+
+/// Writes the number of projectiles in flight in the world.
+// Safe C entry point: `world`, `out_count` are null-checked before use; the rest of the contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn world_projectile_count(world: *const World, out_count: *mut i32) -> DeadAimStatus {
+    ffi_guard(|| {
+        let world = unsafe { arg_ref(world) }?;
+        unsafe { write_out(out_count, world.projectiles.len() as i32) }
+    })
+}
+
+// ---------- Fixed-timestep simulation ----------
+
+impl World {
+    /// One fixed simulation step of `dt` seconds.
+    fn step_once(&mut self, dt: f32) {
+        self.remember_positions();
+        let speed = self.move_speed * dt;
+        self.player.step(dt);
+        let (px, py) = (self.player.x, self.player.y);
+        let World { enemies, rng, next_id, routes, behaviors, ai, ai_states, trees, tree_bindings, statuses, .. } =
+            self;
+
+        for i in 0..enemies.len() {
+            let owed = statuses.get(&enemies[i].id).map_or(0.0, |s| s.dot_damage(dt));
+            if enemies[i].is_alive() && owed > 0.0 {
+                damage_enemy(enemies, i, owed);
+            }
+        }
+
+        if let Some(ai) = ai {
+            for e in enemies.iter_mut().filter(|e| e.is_alive()) {
+                let current = ai_states.get(&e.id).copied().unwrap_or(AiState::Idle);
+                let patrolling = routes.get(&e.id).is_some_and(|r| r.looping);
+                ai_states.insert(e.id, ai.next_state(current, e, px, py, patrolling));
+            }
+        }
+        if speed > 0.0 {
+            for e in enemies.iter_mut().filter(|e| e.is_alive()) {
+                let speed = speed * statuses.get(&e.id).map_or(1.0, |s| s.speed_factor());
+                if speed <= 0.0 {
+                    e.vx = 0.0;
+                    e.vy = 0.0;
+                    continue;
+                }
+                if let Some(tree) = tree_bindings.get(&e.id).map(|&t| &trees[t]) {
+                    let has_route = routes.contains_key(&e.id);
+                    let mut ctx = BtContext { player_x: px, player_y: py, has_route, rng: &mut *rng };
+                    let behavior = match tree.decide(e, &mut ctx) {
+                        Some(BT_ACT_SEEK_PLAYER) => Behavior::Seek { target_x: px, target_y: py },
+                        Some(BT_ACT_FLEE_PLAYER) => Behavior::Flee { from_x: px, from_y: py },
+                        Some(BT_ACT_FOLLOW_ROUTE) => {
+                            if !routes.get_mut(&e.id).is_some_and(|route| route.follow(e, speed)) {
+                                routes.remove(&e.id);
+                            }
+                            continue;
+                        }
+                        Some(BT_ACT_HOLD) | None => {
+                            e.vx = 0.0;
+                            e.vy = 0.0;
+                            continue;
+                        }
+                        Some(_) => Behavior::Wander,
+                    };
+                    behavior.apply(e, speed, rng);
+                    continue;
+                }
+                match ai_states.get(&e.id).copied().unwrap_or(AiState::Idle) {
+                    AiState::Chase => {
+                        let (target_x, target_y) = (e.last_seen_x, e.last_seen_y);
+                        Behavior::Seek { target_x, target_y }.apply(e, speed, rng)
+                    }
+                    AiState::Flee => Behavior::Flee { from_x: px, from_y: py }.apply(e, speed, rng),
+                    AiState::Attack => {
+                        e.vx = 0.0;
+                        e.vy = 0.0;
+                    }
+                    AiState::Idle | AiState::Patrol => match routes.get_mut(&e.id) {
+                        Some(route) => {
+                            if !route.follow(e, speed) {
+                                routes.remove(&e.id);
+                            }
+                        }
+                        None => behaviors.get(&e.id).unwrap_or(&Behavior::Wander).apply(e, speed, rng),
+                    },
+                }
+            }
+            apply_flocking(enemies, speed);
+            if self.enemy_collision {
+                separate_enemies(enemies);
+            }
+            confine_enemies(enemies);
+        }
+        statuses.retain(|_, s| s.tick(dt));
+        let health_before = self.player.health;
+        if !safe_at(self.player.x, self.player.y) {
+            self.player.take_touch_damage(enemies, dt);
+        }
+        if self.player.health < health_before {
+            player_damaged(self.player.x, self.player.y, health_before - self.player.health);
+        }
+        if health_before > 0.0 && self.player.health <= 0.0 {
+            record_player_death(self.player.x, self.player.y);
+        }
+        self.projectiles.step(dt, enemies, |_| {});
+        step_dodge_cooldowns(enemies, dt);
+        // slots held for a pending respawn stay reserved, as in `spawn_enemy`
+        let reusable = |e: &Enemy| !self.respawns.contains_key(&e.id);
+        step_summoners(enemies, dt, self.summon_minions, self.summon_radius, rng, reusable, || {
+            let id = *next_id;
+            *next_id = next_id.wrapping_add(1);
+            id
+        });
+        self.step_respawns(dt);
+        self.resync_grid();
+        self.step_waves(dt);
+    }
+}
+
+/// Set the fixed simulation step `world_tick` advances in, in seconds (default 1/60).
+/// `InvalidArgument` for a non-positive step.
+// Safe C entry point: `world` is null-checked before use; the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn world_set_tick_step(world: *mut World, step_seconds: f32) -> DeadAimStatus {
+    ffi_guard(|| {
+        let world = unsafe { arg_mut(world) }?;
+        if !(step_seconds.is_finite() && step_seconds > 0.0) {
+            return Err(DeadAimStatus::InvalidArgument);
+        }
+        world.step = step_seconds;
+        Ok(())
+    })
+}
+
+/// Set how far enemies wander per second during `world_tick` (0 disables the random walk).
+/// `InvalidArgument` for a negative/NaN speed.
+// Safe C entry point: `world` is null-checked before use; the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn world_set_move_speed(world: *mut World, units_per_second: f32) -> DeadAimStatus {
+    ffi_guard(|| {
+        let world = unsafe { arg_mut(world) }?;
+        if units_per_second.is_nan() || units_per_second < 0.0 {
+            return Err(DeadAimStatus::InvalidArgument);
+        }
+        world.move_speed = units_per_second;
+        Ok(())
+    })
+}
+
+/// Set how many minions each summoner raises and how far away during `world_tick`
+/// (0 minions disables summoning). Minions take the world's next free ids.
+// Safe C entry point: `world` is null-checked before use; the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn world_set_summoning(world: *mut World, minions_per_summon: i32, summon_radius: f32) -> DeadAimStatus {
+    ffi_guard(|| {
+        let world = unsafe { arg_mut(world) }?;
+        world.summon_minions = minions_per_summon.max(0);
+        world.summon_radius = summon_radius.max(0.0);
+        Ok(())
+    })
+}
+
+/// Turn enemy-vs-enemy collision on or off (on by default). While on, every movement update
+/// (`world_tick`, `world_move_enemies_randomly`) pushes apart enemies whose hitboxes overlap
+/// (see `resolve_enemy_collisions`).
+// Safe C entry point: `world` is null-checked before use; the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn world_set_enemy_collision(world: *mut World, enabled: bool) -> DeadAimStatus {
+    ffi_guard(|| {
+        unsafe { arg_mut(world) }?.enemy_collision = enabled;
+        Ok(())
+    })
+}
+
+/// Set the walls `world_enemy_path_to` routes around: a row-major `width` x `height` tile grid
+/// (non-zero byte = wall, the `set_obstacle_map` encoding) of `cell_size` tiles starting at the
+/// origin. The data is copied. A 0 x 0 grid clears it; `InvalidArgument` for other bad
+/// dimensions or cell size.
+// Safe C entry point: `world` is null-checked before use; the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn world_set_nav_grid(
+    world: *mut World,
+    tiles_ptr: *const u8,
+    width: i32,
+    height: i32,
+    cell_size: f32,
+) -> DeadAimStatus {
+    ffi_guard(|| {
+        let world = unsafe { arg_mut(world) }?;
+        world.nav = match (width, height) {
+            (0, 0) => None,
+            _ => Some(NavGrid::from_raw(tiles_ptr, width, height, cell_size)?),
+        };
+        Ok(())
+    })
+}
+
+/// Plan an A* path (see `find_path`) from the alive enemy at `index` to (`goal_x`, `goal_y`)
+/// over the world's nav grid; during `world_tick` the enemy then walks it at the world move
+/// speed instead of wandering, replacing any route it had. Writes the number of waypoints, or -1
+/// when there is no path, to `out_len`. `InvalidIndex` for a bad index or a dead enemy,
+/// `NotInitialized` without a nav grid.
+// Safe C entry point: `world`, `out_len` are null-checked before use; the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn world_enemy_path_to(
+    world: *mut World,
+    index: i32,
+    goal_x: f32,
+    goal_y: f32,
+    out_len: *mut i32,
+) -> DeadAimStatus {
+    ffi_guard(|| {
+        let world = unsafe { arg_mut(world) }?;
+        let e = *world.enemy_at(index)?;
+        if !e.is_alive() {
+            return Err(DeadAimStatus::InvalidIndex);
+        }
+        let nav = world.nav.as_ref().ok_or(DeadAimStatus::NotInitialized)?;
+        if out_len.is_null() {
+            return Err(DeadAimStatus::NullPointer);
+        }
+        let Some(points) = nav.find_path(e.x, e.y, goal_x, goal_y) else {
+            return unsafe { write_out(out_len, -1) };
+        };
+        let len = points.len() as i32;
+        world.routes.insert(e.id, Route { points, next: 0, looping: false, tolerance: 0.0 });
+        unsafe { write_out(out_len, len) }
+    })
+}
+
+/// Give the enemy at `index` a designer-authored patrol: `point_count` waypoints as interleaved
+/// x, y pairs at `points_ptr`, walked in order at the world move speed during `world_tick`,
+/// moving on once within `tolerance` of each. A `looping` route starts over after the last
+/// waypoint; otherwise the enemy falls back to its steering behavior at the end. Replaces any
+/// route or path it had; 0 points clears it. `InvalidIndex` for a bad index.
+// Safe C entry point: `world`, `points_ptr` are null-checked before use; the rest of the contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn world_set_patrol_route(
+    world: *mut World,
+    index: i32,
+    points_ptr: *const f32,
+    point_count: i32,
+    looping: bool,
+    tolerance: f32,
+) -> DeadAimStatus {
+    ffi_guard(|| {
+        let world = unsafe { arg_mut(world) }?;
+        let id = world.enemy_at(index)?.id;
+        let points = unsafe { points_from_raw(points_ptr, point_count) }?;
+        if points.is_empty() {
+            world.routes.remove(&id);
+            return Ok(());
+        }
+        world.routes.insert(id, Route { points, next: 0, looping, tolerance: tolerance.max(0.0) });
+        Ok(())
+    })
+}
+
+/// Teleport the player to (`x`, `y`) (spawns, respawns, or hosts that move the player
+/// themselves). AI and behavior trees react to the player's position.
+// Safe C entry point: `world` is null-checked before use; the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn world_set_player_position(world: *mut World, x: f32, y: f32) -> DeadAimStatus {
+    ffi_guard(|| {
+        let world = unsafe { arg_mut(world) }?;
+        world.player.x = x;
+        world.player.y = y;
+        Ok(())
+    })
+}
+
+/// Set the player's movement input for the following `world_tick`s; vectors longer than 1 are
+/// normalized. Each step moves the player `speed * dt` along it, sliding along obstacles.
+/// `InvalidArgument` for a non-finite input.
+// Safe C entry point: `world` is null-checked before use; the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn world_set_player_input(world: *mut World, dx: f32, dy: f32) -> DeadAimStatus {
+    ffi_guard(|| {
+        let world = unsafe { arg_mut(world) }?;
+        if !(dx.is_finite() && dy.is_finite()) {
+            return Err(DeadAimStatus::InvalidArgument);
+        }
+        let len = (dx * dx + dy * dy).sqrt();
+        let scale = if len > 1.0 { 1.0 / len } else { 1.0 };
+        world.player.input_x = dx * scale;
+        world.player.input_y = dy * scale;
+        Ok(())
+    })
+}
+
+/// Write the player's position to `out_x`/`out_y`.
+// Safe C entry point: `world`, `out_x`, `out_y` are null-checked before use; the rest of the contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn world_get_player_position(world: *const World, out_x: *mut f32, out_y: *mut f32) -> DeadAimStatus {
+    ffi_guard(|| {
+        let world = unsafe { arg_ref(world) }?;
+        if out_x.is_null() || out_y.is_null() {
+            return Err(DeadAimStatus::NullPointer);
+        }
+        unsafe {
+            out_x.write(world.player.x);
+            out_y.write(world.player.y);
+        }
+        Ok(())
+    })
+}
+
+/// Tune the player: movement `speed` (units per second), collision `radius`, `max_health`
+/// (health refills to it), and `touch_dps` damage per second from each enemy in contact (none
+/// while the player stands in a safe zone, see `set_safe_zones`).
+/// Negative/NaN values leave that stat unchanged.
+// Safe C entry point: `world` is null-checked before use; the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn world_configure_player(
+    world: *mut World,
+    speed: f32,
+    radius: f32,
+    max_health: f32,
+    touch_dps: f32,
+) -> DeadAimStatus {
+    ffi_guard(|| {
+        let world = unsafe { arg_mut(world) }?;
+        let p = &mut world.player;
+        for (stat, value) in [(&mut p.speed, speed), (&mut p.radius, radius), (&mut p.max_health, max_health)] {
+            if value >= 0.0 {
+                *stat = value;
+            }
+        }
+        if touch_dps >= 0.0 {
+            p.touch_dps = touch_dps;
+        }
+        p.health = p.max_health;
+        Ok(())
+    })
+}
+
+/// Writes the player's remaining health (0 once dead).
+// Safe C entry point: `world`, `out_health` are null-checked before use; the rest of the contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn world_get_player_health(world: *const World, out_health: *mut f32) -> DeadAimStatus {
+    ffi_guard(|| {
+        let world = unsafe { arg_ref(world) }?;
+        unsafe { write_out(out_health, world.player.health) }
+    })
+}
+
+/// Turn on the AI state machine with the given thresholds (null turns it off and resets every
+/// enemy to idle). While on, each `world_tick` step re-evaluates every alive enemy's state and
+/// moves it accordingly: chasers seek the last-seen player position, attackers hold still,
+/// fleers run from the player, and idle/patrolling enemies keep their route or behavior.
+// Safe C entry point: `world`, `config` are null-checked before use; the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn world_set_ai_config(world: *mut World, config: *const AiConfig) -> DeadAimStatus {
+    ffi_guard(|| {
+        let world = unsafe { arg_mut(world) }?;
+        world.ai = unsafe { config.as_ref() }.copied();
+        if world.ai.is_none() {
+            world.ai_states.clear();
+        }
+        Ok(())
+    })
+}
+
+/// Writes the AI state of the enemy at `index` as an `AiState` discriminant (0 = idle,
+/// 1 = patrol, 2 = chase, 3 = attack, 4 = flee), for picking animations. `InvalidIndex` for a
+/// bad index.
+// Safe C entry point: `world`, `out_state` are null-checked before use; the rest of the contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn world_get_enemy_ai_state(world: *const World, index: i32, out_state: *mut i32) -> DeadAimStatus {
+    ffi_guard(|| {
+        let world = unsafe { arg_ref(world) }?;
+        let e = world.enemy_at(index)?;
+        let state = world.ai_states.get(&e.id).copied().unwrap_or(AiState::Idle);
+        unsafe { write_out(out_state, state as i32) }
+    })
+}
+
+/// Load an encoded behavior tree (format in `behavior_tree.rs`) into the world and write its tree
+/// id for `world_bind_behavior_tree` to `out_tree_id`. `InvalidData` for malformed input.
+// Safe C entry point: `world`, `bytes_ptr`, `out_tree_id` are null-checked before use;
+// the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn world_load_behavior_tree(
+    world: *mut World,
+    bytes_ptr: *const u8,
+    len: usize,
+    out_tree_id: *mut i32,
+) -> DeadAimStatus {
+    ffi_guard(|| {
+        let world = unsafe { arg_mut(world) }?;
+        let bytes = unsafe { slice_arg(bytes_ptr, len) }?;
+        if out_tree_id.is_null() {
+            return Err(DeadAimStatus::NullPointer);
+        }
+        let tree = BehaviorTree::parse(bytes).ok_or(DeadAimStatus::InvalidData)?;
+        world.trees.push(tree);
+        unsafe { write_out(out_tree_id, world.trees.len() as i32 - 1) }
+    })
+}
+
+/// Make the enemy at `index` run tree `tree_id` each `world_tick` step (overriding the AI state
+/// machine); -1 unbinds it. A tree that reaches no action holds the enemy still. `InvalidIndex`
+/// for a bad index, `InvalidArgument` for an unknown tree id.
+// Safe C entry point: `world` is null-checked before use; the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn world_bind_behavior_tree(world: *mut World, index: i32, tree_id: i32) -> DeadAimStatus {
+    ffi_guard(|| {
+        let world = unsafe { arg_mut(world) }?;
+        let id = world.enemy_at(index)?.id;
+        if tree_id == -1 {
+            world.tree_bindings.remove(&id);
+            return Ok(());
+        }
+        let t = usize::try_from(tree_id).ok().filter(|&t| t < world.trees.len());
+        world.tree_bindings.insert(id, t.ok_or(DeadAimStatus::InvalidArgument)?);
+        Ok(())
+    })
+}
+
+/// Configure wave `wave_index`: `enemy_count` enemies with `type_id` `enemy_type`, the
+/// first right away and then one every `spawn_interval` seconds (0 = all at once), each at a
+/// random one of `point_count` spawn points (interleaved x, y pairs; none = the origin).
+/// Spawned enemies carry `wave_id = wave_index`. Reconfiguring a wave replaces it.
+/// `InvalidArgument` for a negative index or count.
+// Safe C entry point: `world`, `spawn_points_ptr` are null-checked before use;
+// the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn world_configure_wave(
+    world: *mut World,
+    wave_index: i32,
+    enemy_count: i32,
+    enemy_type: i32,
+    spawn_interval: f32,
+    spawn_points_ptr: *const f32,
+    point_count: i32,
+) -> DeadAimStatus {
+    ffi_guard(|| {
+        let world = unsafe { arg_mut(world) }?;
+        let Ok(index) = usize::try_from(wave_index) else {
+            return Err(DeadAimStatus::InvalidArgument);
+        };
+        if enemy_count < 0 {
+            return Err(DeadAimStatus::InvalidArgument);
+        }
+        let spawn_points = unsafe { points_from_raw(spawn_points_ptr, point_count) }?;
+        let spawn_interval = if spawn_interval.is_finite() { spawn_interval.max(0.0) } else { 0.0 };
+        world.waves.configure(index, WaveConfig { enemy_count, enemy_type, spawn_interval, spawn_points });
+        Ok(())
+    })
+}
+
+/// Start running configured wave `wave_index`. When a wave has fully spawned and all of it is
+/// dead it counts as cleared (firing the `set_on_wave_cleared` callback) and the next wave
+/// index starts if configured. `InvalidArgument` for an unconfigured wave.
+// Safe C entry point: `world` is null-checked before use; the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn world_start_wave(world: *mut World, wave_index: i32) -> DeadAimStatus {
+    ffi_guard(|| {
+        let world = unsafe { arg_mut(world) }?;
+        if !usize::try_from(wave_index).is_ok_and(|i| world.waves.start(i)) {
+            return Err(DeadAimStatus::InvalidArgument);
+        }
+        Ok(())
+    })
+}
+
+/// Writes the index of the wave currently spawning or being fought, or -1 when none is running.
+// Safe C entry point: `world`, `out_wave` are null-checked before use; the rest of the contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn world_current_wave(world: *const World, out_wave: *mut i32) -> DeadAimStatus {
+    ffi_guard(|| {
+        let world = unsafe { arg_ref(world) }?;
+        unsafe { write_out(out_wave, world.waves.active().map_or(-1, |i| i as i32)) }
+    })
+}
+
+/// Advance only the wave spawner by `dt` seconds, inserting due enemies into the world (dead
+/// slots are reused once it is full). `world_tick` already does this every step; call it
+/// directly only when driving the world manually. Writes the wave index cleared during this
+/// call, or -1, to `out_cleared` (may be null). `InvalidArgument` for a negative/NaN `dt`.
+// Safe C entry point: `world`, `out_cleared` are null-checked before use; the rest of the contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn world_tick_spawner(world: *mut World, dt: f32, out_cleared: *mut i32) -> DeadAimStatus {
+    ffi_guard(|| {
+        let world = unsafe { arg_mut(world) }?;
+        if dt.is_nan() || dt < 0.0 {
+            return Err(DeadAimStatus::InvalidArgument);
+        }
+        let cleared = world.step_waves(dt).unwrap_or(-1);
+        unsafe { write_opt(out_cleared, cleared) };
+        Ok(())
+    })
+}
+
+/// Put a status effect on the alive enemy at `index` for `duration` seconds, ticked by
+/// `world_tick`: slows cut movement speed by `magnitude` (a 0..=1 fraction), stuns stop
+/// movement, and burn/poison deal `magnitude` damage per second through the regular damage
+/// path. Reapplying keeps the longer timer; burns and slows keep the stronger magnitude while
+/// poison stacks. `InvalidIndex` for a bad index or a dead enemy, `InvalidArgument` for a
+/// non-positive duration or a non-finite magnitude.
+// Safe C entry point: `world` is null-checked before use; the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn world_apply_status(
+    world: *mut World,
+    index: i32,
+    kind: StatusKind,
+    duration: f32,
+    magnitude: f32,
+) -> DeadAimStatus {
+    ffi_guard(|| {
+        let world = unsafe { arg_mut(world) }?;
+        let id = world.enemy_at(index).ok().filter(|e| e.is_alive()).ok_or(DeadAimStatus::InvalidIndex)?.id;
+        if duration.is_nan() || duration <= 0.0 || !magnitude.is_finite() {
+            return Err(DeadAimStatus::InvalidArgument);
+        }
+        world.statuses.entry(id).or_default().apply(kind, duration, magnitude);
+        Ok(())
+    })
+}
+
+/// Writes the active status effects of the enemy at `index` as a bit mask (`1 << StatusKind`),
+/// for status icons; 0 for none. `InvalidIndex` for a bad index.
+// Safe C entry point: `world`, `out_mask` are null-checked before use; the rest of the contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn world_status_mask(world: *const World, index: i32, out_mask: *mut u32) -> DeadAimStatus {
+    ffi_guard(|| {
+        let world = unsafe { arg_ref(world) }?;
+        let e = world.enemy_at(index)?;
+        unsafe { write_out(out_mask, world.statuses.get(&e.id).map_or(0, |s| s.mask())) }
+    })
+}
+
+/// Writes whether `kind` is active on the enemy at `index` to `out_active`, and if so its seconds
+/// remaining and magnitude to the non-null outputs. `InvalidIndex` for a bad index.
+// Safe C entry point: `world`, `out_active`, `out_remaining`, `out_magnitude` are null-checked before use;
+// the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn world_get_status(
+    world: *const World,
+    index: i32,
+    kind: StatusKind,
+    out_active: *mut bool,
+    out_remaining: *mut f32,
+    out_magnitude: *mut f32,
+) -> DeadAimStatus {
+    ffi_guard(|| {
+        let world = unsafe { arg_ref(world) }?;
+        let e = world.enemy_at(index)?;
+        let Some((remaining, magnitude)) = world.statuses.get(&e.id).and_then(|s| s.get(kind)) else {
+            return unsafe { write_out(out_active, false) };
+        };
+        unsafe {
+            write_out(out_active, true)?;
+            write_opt(out_remaining, remaining);
+            write_opt(out_magnitude, magnitude);
+        }
+        Ok(())
+    })
+}
+
+/// Choose how the enemy at `index` moves during `world_tick` when it has no route (e.g.
+/// `Behavior::Seek` to chase the player; refresh the target as it moves). `InvalidIndex` for a
+/// bad index.
+// Safe C entry point: `world` is null-checked before use; the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn world_set_enemy_behavior(world: *mut World, index: i32, behavior: Behavior) -> DeadAimStatus {
+    ffi_guard(|| {
+        let world = unsafe { arg_mut(world) }?;
+        let id = world.enemy_at(index)?.id;
+        world.behaviors.insert(id, behavior);
+        Ok(())
+    })
+}
+
+/// Advance the world by `dt_seconds` of frame time in fixed steps (see `world_set_tick_step`), so
+/// the simulation runs the same at any frame rate. Each step moves the player, applies status
+/// effects (damage over time, slows, stuns), moves enemies (along their routes, else by their steering behavior, with
+/// swarm enemies flocking), flies projectiles (resolving their hits), ticks dodge cooldowns, and
+/// runs summoners, respawns and the wave spawner. Leftover time carries to the next call; at most 8 steps
+/// run per call and any backlog beyond that is dropped.
+/// Writes the number of steps simulated to `out_steps` (may be null).
+// Safe C entry point: `world`, `out_steps` are null-checked before use; the rest of the contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn world_tick(world: *mut World, dt_seconds: f32, out_steps: *mut i32) -> DeadAimStatus {
+    ffi_guard(|| {
+        let world = unsafe { arg_mut(world) }?;
+        if dt_seconds.is_finite() && dt_seconds > 0.0 {
+            world.accumulator += dt_seconds;
+        }
+        let mut steps = 0;
+        while world.accumulator >= world.step && steps < MAX_STEPS_PER_TICK {
+            world.step_once(world.step);
+            world.accumulator -= world.step;
+            steps += 1;
+        }
+        if steps == MAX_STEPS_PER_TICK {
+            world.accumulator = world.accumulator.min(world.step);
+        }
+        unsafe { write_opt(out_steps, steps as i32) };
+        Ok(())
+    })
+}
+
+// ---------- Sticky target lock ----------
+
+/// Break the lock when the target dies.
+pub const LOCK_BREAK_DEATH: u32 = 1;
+/// Break the lock when the target moves beyond `max_range`.
+pub const LOCK_BREAK_RANGE: u32 = 2;
+/// Break the lock when the target leaves the `fov_half_angle` cone around the aim.
+pub const LOCK_BREAK_FOV: u32 = 4;
+
+/// What `world_lock_target` may acquire and when an existing lock lets go. New targets must be
+/// alive, within `max_range` and inside the cone of `fov_half_angle` radians around
+/// (`aim_dx`, `aim_dy`) (a half-angle >= PI or a zero aim vector means all around).
+/// `break_flags` is a mask of `LOCK_BREAK_*` saying which of those conditions drop a held lock.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct LockCriteria {
+    pub player_x: f32,
+    pub player_y: f32,
+    pub aim_dx: f32,
+    pub aim_dy: f32,
+    pub max_range: f32,
+    pub fov_half_angle: f32,
+    pub break_flags: u32,
+}
+
+impl LockCriteria {
+    fn in_range(&self, e: &Enemy) -> bool {
+        (e.x - self.player_x).powi(2) + (e.y - self.player_y).powi(2) <= self.max_range * self.max_range
+    }
+
+    fn in_fov(&self, e: &Enemy) -> bool {
+        let aim_len = (self.aim_dx * self.aim_dx + self.aim_dy * self.aim_dy).sqrt();
+        if aim_len <= f32::EPSILON || self.fov_half_angle >= std::f32::consts::PI {
+            return true;
+        }
+        let (dx, dy) = (e.x - self.player_x, e.y - self.player_y);
+        let dist = (dx * dx + dy * dy).sqrt();
+        dist <= f32::EPSILON
+            || (dx * self.aim_dx + dy * self.aim_dy) / (dist * aim_len) >= self.fov_half_angle.max(0.0).cos()
+    }
+
+    /// Whether a held target survives this frame under the configured break conditions.
+    fn keeps(&self, e: &Enemy) -> bool {
+        let broken = (self.break_flags & LOCK_BREAK_DEATH != 0 && !e.is_alive())
+            || (self.break_flags & LOCK_BREAK_RANGE != 0 && !self.in_range(e))
+            || (self.break_flags & LOCK_BREAK_FOV != 0 && !self.in_fov(e));
+        !broken
+    }
+}
+
+/// Sticky lock-on: keep the currently locked enemy until one of the criteria's break conditions
+/// fires, otherwise acquire the nearest enemy meeting the criteria. Consumers call this every
+/// frame instead of re-running nearest-enemy, so equidistant enemies can't steal the lock.
+/// Writes the locked enemy's id, or -1 when nothing qualifies, to `out_id` (may be null).
+// Safe C entry point: `world`, `criteria`, `out_id` are null-checked before use;
+// the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn world_lock_target(
+    world: *mut World,
+    criteria: *const LockCriteria,
+    out_id: *mut i32,
+) -> DeadAimStatus {
+    ffi_guard(|| {
+        let world = unsafe { arg_mut(world) }?;
+        let c = unsafe { arg_ref(criteria) }?;
+        let held = world.enemies.iter().find(|e| e.id == world.locked_id && world.locked_id >= 0);
+        if !held.is_some_and(|e| c.keeps(e)) {
+            let found = nearest_matching(c.player_x, c.player_y, &world.enemies, |_, e| c.in_range(e) && c.in_fov(e));
+            world.locked_id = usize::try_from(found).map_or(-1, |i| world.enemies[i].id);
+        }
+        unsafe { write_opt(out_id, world.locked_id) };
+        Ok(())
+    })
+}
+
+/// Drop the world's lock.
+// Safe C entry point: `world` is null-checked before use; the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn world_unlock_target(world: *mut World) -> DeadAimStatus {
+    ffi_guard(|| {
+        unsafe { arg_mut(world) }?.locked_id = -1;
+        Ok(())
+    })
+}
+
+/// Writes whether a target is locked (and still exists) to `out_locked`, and if so its current
+/// position to `out_x`/`out_y`.
+// Safe C entry point: `world`, `out_locked`, `out_x`, `out_y` are null-checked before use;
+// the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn world_get_locked_target_position(
+    world: *const World,
+    out_locked: *mut bool,
+    out_x: *mut f32,
+    out_y: *mut f32,
+) -> DeadAimStatus {
+    ffi_guard(|| {
+        let world = unsafe { arg_ref(world) }?;
+        if out_locked.is_null() || out_x.is_null() || out_y.is_null() {
+            return Err(DeadAimStatus::NullPointer);
+        }
+        let locked = world.enemies.iter().find(|e| e.id == world.locked_id && world.locked_id >= 0);
+        let Some(e) = locked else {
+            return unsafe { write_out(out_locked, false) };
+        };
+        unsafe {
+            out_x.write(e.x);
+            out_y.write(e.y);
+            write_out(out_locked, true)
+        }
+    })
+}
+
+// ---------- Snapshots ----------
+//
+// Layout (always little endian): magic "DAWD" | version u16 | enemy record version u16
+//   | capacity u32 | next_id i32 | locked_id i32 | rng (algo i32 | s0 u64 | s1 u64)
+//   | grid cell f32 | tick step f32 | accumulator f32 | move speed f32 | summon minions i32
+//   | summon radius f32 | enemy collision u8 | player (9 x f32)
+//   | ai present u8 [sight f32 | attack f32 | flee fraction f32]
+//   | enemy count u32 | enemies (`serialize_enemies` records of the stored version)
+//   | per-id tables, each a count u32 then entries sorted by enemy id:
+//       routes (points | next u32 | looping u8 | tolerance f32), behaviors (tag u8 | 3 x f32),
+//       AI states (u8), tree bindings (tree u32), statuses (4 x (remaining f32, magnitude f32)),
+//       respawns (seconds left f32)
+//   | trees (count u32, each len u32 | encoding) | nav present u8 [w u32 | h u32 | cell f32 | tiles]
+//   | waves (count u32, each present u8 [count i32 | type i32 | interval f32 | points]
+//     | active i32 | spawned i32 | timer f32) | respawn points
+//   | projectiles (next id i32 | count u32, each id i32 | x, y, vx, vy, gravity, damage, age f32;
+//     absent in version 1)
+// Point lists are a count u32 then x f32, y f32 pairs. Crate-wide settings (obstacles, enemy
+// types, flocking, ...) belong to no world and are not included.
+const WORLD_MAGIC: &[u8; 4] = b"DAWD";
+const WORLD_FORMAT_VERSION: u16 = 2;
+
+/// Write `map` as a count then its entries in ascending id order, so equal worlds produce equal
+/// bytes.
+fn write_table<V>(w: &mut Writer, map: &HashMap<i32, V>, mut write: impl FnMut(&mut Writer, &V)) {
+    let mut ids: Vec<i32> = map.keys().copied().collect();
+    ids.sort_unstable();
+    w.u32(ids.len() as u32);
+    for id in ids {
+        w.i32(id);
+        write(w, &map[&id]);
+    }
+}
+
+fn read_table<V>(r: &mut Reader, mut read: impl FnMut(&mut Reader) -> Option<V>) -> Option<HashMap<i32, V>> {
+    let count = r.u32()?;
+    (0..count).map(|_| Some((r.i32()?, read(r)?))).collect()
+}
+
+impl Route {
+    fn write_snapshot(&self, w: &mut Writer) {
+        w.points(&self.points);
+        w.u32(self.next as u32);
+        w.u8(self.looping as u8);
+        w.f32(self.tolerance);
+    }
+
+    fn read_snapshot(r: &mut Reader) -> Option<Self> {
+        let (points, next) = (r.points()?, r.u32()? as usize);
+        if next >= points.len() {
+            return None;
+        }
+        Some(Route { points, next, looping: r.bool()?, tolerance: r.f32()? })
+    }
+}
+
+impl World {
+    pub(crate) fn write_snapshot(&self) -> Vec<u8> {
+        let mut w = Writer::new(false);
+        w.put(WORLD_MAGIC);
+        w.u16(WORLD_FORMAT_VERSION);
+        w.u16(ENEMY_FORMAT_VERSION);
+        w.u32(self.capacity as u32);
+        w.i32(self.next_id);
+        w.i32(self.locked_id);
+        self.rng.write_snapshot(&mut w);
+        for v in [self.grid.cell_size(), self.step, self.accumulator, self.move_speed] {
+            w.f32(v);
+        }
+        w.i32(self.summon_minions);
+        w.f32(self.summon_radius);
+        w.u8(self.enemy_collision as u8);
+        self.player.write_snapshot(&mut w);
+        w.u8(self.ai.is_some() as u8);
+        if let Some(ai) = self.ai {
+            w.f32(ai.sight_range);
+            w.f32(ai.attack_range);
+            w.f32(ai.flee_health_fraction);
+        }
+
+        w.u32(self.enemies.len() as u32);
+        for e in &self.enemies {
+            write_enemy(&mut w, e);
+        }
+        write_table(&mut w, &self.routes, |w, route| route.write_snapshot(w));
+        write_table(&mut w, &self.behaviors, |w, behavior| behavior.write_snapshot(w));
+        write_table(&mut w, &self.ai_states, |w, &state| w.u8(state as u8));
+        write_table(&mut w, &self.tree_bindings, |w, &tree| w.u32(tree as u32));
+        write_table(&mut w, &self.statuses, |w, statuses| statuses.write_snapshot(w));
+        write_table(&mut w, &self.respawns, |w, &remaining| w.f32(remaining));
+
+        w.u32(self.trees.len() as u32);
+        for tree in &self.trees {
+            w.u32(tree.bytes().len() as u32);
+            w.put(tree.bytes());
+        }
+        w.u8(self.nav.is_some() as u8);
+        if let Some(nav) = &self.nav {
+            nav.write_snapshot(&mut w);
+        }
+        self.waves.write_snapshot(&mut w);
+        w.points(&self.respawn_points);
+        self.projectiles.write_snapshot(&mut w);
+        w.buf
+    }
+
+    /// Rebuild a world from `write_snapshot` output; `None` for anything malformed, including
+    /// trailing bytes.
+    pub(crate) fn read_snapshot(bytes: &[u8]) -> Option<World> {
+        let mut r = Reader::new(bytes, false);
+        if r.take::<4>().as_ref() != Some(WORLD_MAGIC) {
+            return None;
+        }
+        let version = r.u16()?;
+        if !(1..=WORLD_FORMAT_VERSION).contains(&version) {
+            return None;
+        }
+        let enemy_version = r.u16()?;
+        enemy_len(enemy_version)?;
+        let (capacity, next_id, locked_id) = (r.u32()? as usize, r.i32()?, r.i32()?);
+        let rng = RngState::read_snapshot(&mut r)?;
+        let (cell_size, step, accumulator, move_speed) = (r.f32()?, r.f32()?, r.f32()?, r.f32()?);
+        let positive = |v: f32| v.is_finite() && v > 0.0;
+        if !positive(cell_size) || !positive(step) {
+            return None;
+        }
+        let (summon_minions, summon_radius, enemy_collision) = (r.i32()?, r.f32()?, r.bool()?);
+        let player = Player::read_snapshot(&mut r)?;
+        let ai = match r.bool()? {
+            true => Some(AiConfig { sight_range: r.f32()?, attack_range: r.f32()?, flee_health_fraction: r.f32()? }),
+            false => None,
+        };
+
+        let count = r.u32()?;
+        let enemies = (0..count).map(|_| read_enemy(&mut r, enemy_version)).collect::<Option<Vec<_>>>()?;
+        if enemies.len() > capacity {
+            return None;
+        }
+        let routes = read_table(&mut r, Route::read_snapshot)?;
+        let behaviors = read_table(&mut r, Behavior::read_snapshot)?;
+        let ai_states = read_table(&mut r, |r| AiState::from_u8(r.u8()?))?;
+        let tree_bindings = read_table(&mut r, |r| Some(r.u32()? as usize))?;
+        let statuses = read_table(&mut r, StatusSet::read_snapshot)?;
+        let respawns = read_table(&mut r, |r| r.f32())?;
+
+        let tree_count = r.u32()?;
+        let trees = (0..tree_count)
+            .map(|_| {
+                let len = r.u32()? as usize;
+                BehaviorTree::parse(r.bytes(len)?)
+            })
+            .collect::<Option<Vec<_>>>()?;
+        if tree_bindings.values().any(|&t| t >= trees.len()) {
+            return None;
+        }
+        let nav = match r.bool()? {
+            true => Some(NavGrid::read_snapshot(&mut r)?),
+            false => None,
+        };
+        let waves = WaveSpawner::read_snapshot(&mut r)?;
+        let respawn_points = r.points()?;
+        let projectiles = match version {
+            1 => ProjectileSet::new(),
+            _ => ProjectileSet::read_snapshot(&mut r)?,
+        };
+        if !r.at_end() {
+            return None;
+        }
+
+        let mut grid = UniformGrid::new(cell_size);
+        grid.rebuild(&enemies, cell_size);
+        Some(World {
+            enemies,
+            capacity,
+            next_id,
+            grid,
+            locked_id,
+            rng,
+            step,
+            accumulator,
+            move_speed,
+            summon_minions,
+            summon_radius,
+            enemy_collision,
+            nav,
+            routes,
+            behaviors,
+            player,
+            ai,
+            ai_states,
+            trees,
+            tree_bindings,
+            waves,
+            statuses,
+            respawn_points,
+            respawns,
+            projectiles,
+            match_state: MatchState::default(),
+            history: VecDeque::new(),
+            prev_positions: Vec::new(),
+        })
+    }
+}
+
+/// Writes the bytes `serialize_world` needs for the world's current state.
+// Safe C entry point: `world`, `out_size` are null-checked before use; the rest of the contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn serialized_world_size(world: *const World, out_size: *mut usize) -> DeadAimStatus {
+    ffi_guard(|| {
+        let world = unsafe { arg_ref(world) }?;
+        unsafe { write_out(out_size, world.write_snapshot().len()) }
+    })
+}
+
+/// Write a versioned snapshot of the whole world (enemies, player, RNG state, timers, routes,
+/// AI, statuses, trees, waves, respawns and projectiles; layout above) to `out_ptr` and the bytes
+/// written to `out_written`. `BufferTooSmall` if `out_cap` is smaller than
+/// `serialized_world_size`.
+// Safe C entry point: `world` is null-checked before use; the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn serialize_world(
+    world: *const World,
+    out_ptr: *mut u8,
+    out_cap: usize,
+    out_written: *mut usize,
+) -> DeadAimStatus {
+    ffi_guard(|| {
+        let world = unsafe { arg_ref(world) }?;
+        copy_out(&world.write_snapshot(), out_ptr, out_cap, out_written)
+    })
+}
+
+/// Copy `bytes` into a caller buffer of `out_cap` bytes and report the length written.
+pub(crate) fn copy_out(bytes: &[u8], out_ptr: *mut u8, out_cap: usize, out_written: *mut usize) -> FfiResult {
+    if out_ptr.is_null() || out_written.is_null() {
+        return Err(DeadAimStatus::NullPointer);
+    }
+    if out_cap < bytes.len() {
+        return Err(DeadAimStatus::BufferTooSmall);
+    }
+    unsafe {
+        std::ptr::copy_nonoverlapping(bytes.as_ptr(), out_ptr, bytes.len());
+        write_out(out_written, bytes.len())
+    }
+}
+
+/// Recreate a world from a `serialize_world` snapshot and write it to `out_world`; it continues
+/// exactly where the saved one left off. `InvalidData` for malformed input. Free with
+/// `world_destroy`.
+// Safe C entry point: `in_ptr`, `out_world` are null-checked before use; the rest of the contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn deserialize_world(in_ptr: *const u8, in_len: usize, out_world: *mut *mut World) -> DeadAimStatus {
+    ffi_guard(|| {
+        let bytes = unsafe { slice_arg(in_ptr, in_len) }?;
+        if out_world.is_null() {
+            return Err(DeadAimStatus::NullPointer);
+        }
+        let world = World::read_snapshot(bytes).ok_or(DeadAimStatus::InvalidData)?;
+        unsafe { write_out(out_world, Box::into_raw(Box::new(world))) }
+    })
+}
+
+// ---------- Desync detection ----------
+
+// Fractional steps per world unit (and per second, for timers) kept when hashing state, so
+// last-bit float noise between platforms doesn't read as a desync.
+const HASH_QUANTA_PER_UNIT: f32 = 1024.0;
+
+/// FNV-1a over the canonical state stream.
+struct StateHasher(u64);
+
+impl StateHasher {
+    fn bytes(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            self.0 = (self.0 ^ b as u64).wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+    fn u64(&mut self, v: u64) {
+        self.bytes(&v.to_le_bytes());
+    }
+    fn i32(&mut self, v: i32) {
+        self.bytes(&v.to_le_bytes());
+    }
+    /// A float quantized to `1 / HASH_QUANTA_PER_UNIT` (NaN hashes as 0).
+    fn q(&mut self, v: f32) {
+        self.u64(((v as f64) * HASH_QUANTA_PER_UNIT as f64).round() as i64 as u64);
+    }
+    /// A per-id table in ascending id order.
+    fn table<V>(&mut self, map: &HashMap<i32, V>, mut hash: impl FnMut(&mut Self, &V)) {
+        let mut ids: Vec<i32> = map.keys().copied().collect();
+        ids.sort_unstable();
+        self.u64(ids.len() as u64);
+        for id in ids {
+            self.i32(id);
+            hash(self, &map[&id]);
+        }
+    }
+}
+
+impl World {
+    pub(crate) fn state_hash(&self) -> u64 {
+        let mut h = StateHasher(0xcbf2_9ce4_8422_2325);
+        let mut rng = Writer::new(false);
+        self.rng.write_snapshot(&mut rng);
+        h.bytes(&rng.buf);
+        h.i32(self.next_id);
+        h.i32(self.locked_id);
+        h.q(self.accumulator);
+        for p in [self.player.x, self.player.y, self.player.health] {
+            h.q(p);
+        }
+        h.u64(self.enemies.len() as u64);
+        for e in &self.enemies {
+            h.i32(e.id);
+            h.i32(e.is_alive() as i32);
+            for v in [e.x, e.y, e.vx, e.vy, e.health, e.dodge_cooldown, e.summon_cooldown] {
+                h.q(v);
+            }
+            for v in [e.stun_frames, e.flinch_frames, e.taunt_frames, e.tether_id, e.type_id, e.wave_id] {
+                h.i32(v);
+            }
+        }
+        h.table(&self.routes, |h, route| h.u64(route.next as u64));
+        h.table(&self.ai_states, |h, &state| h.i32(state as i32));
+        h.table(&self.statuses, |h, statuses| {
+            for &(remaining, magnitude) in statuses.effects() {
+                h.q(remaining);
+                h.q(magnitude);
+            }
+        });
+        h.table(&self.respawns, |h, &remaining| h.q(remaining));
+        let (active, spawned, timer) = self.waves.progress();
+        h.i32(active.map_or(-1, |i| i as i32));
+        h.i32(spawned);
+        h.q(timer);
+        let (next_projectile, flights) = self.projectiles.state();
+        h.i32(next_projectile);
+        for (id, values) in flights {
+            h.i32(id);
+            for v in values {
+                h.q(v);
+            }
+        }
+        h.0
+    }
+}
+
+/// Stable 64-bit hash of the world's simulation state: enemy positions, velocities, health and
+/// timers (floats quantized to 1/1024), the player, the RNG state, AI states, statuses, route
+/// progress, respawn timers, wave progress and projectiles in flight. Configuration isn't
+/// hashed. Lockstep peers compare it after each tick; equal inputs give equal hashes on every
+/// platform. Written to `out_hash`.
+// Safe C entry point: `world`, `out_hash` are null-checked before use; the rest of the contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn world_state_hash(world: *const World, out_hash: *mut u64) -> DeadAimStatus {
+    ffi_guard(|| {
+        let world = unsafe { arg_ref(world) }?;
+        unsafe { write_out(out_hash, world.state_hash()) }
+    })
+}
+
+// ---------- Network deltas ----------
+//
+// Delta layout (little endian): magic "DADL" | version u16 | base enemy count u32
+//   | enemy count u32 | player x f32 | player y f32 | changed slot count u32
+//   | per changed slot: index u32 | mask u8 | position (x f32 | y f32) if DELTA_POSITION
+//     | alive u8 if DELTA_ALIVE | health f32 if DELTA_HEALTH | full enemy record if DELTA_FULL
+// Slots past the base count (spawns) and slots whose id changed are always sent in full.
+
+const DELTA_MAGIC: &[u8; 4] = b"DADL";
+const DELTA_FORMAT_VERSION: u16 = 1;
+const DELTA_POSITION: u8 = 1;
+const DELTA_ALIVE: u8 = 2;
+const DELTA_HEALTH: u8 = 4;
+const DELTA_FULL: u8 = 8;
+
+/// Changes from `prev` to `curr`, one slot.
+#[derive(Clone, Copy, Debug)]
+enum SlotDelta {
+    Fields { mask: u8, x: f32, y: f32, alive: bool, health: f32 },
+    Full(Enemy),
+}
+
+fn write_delta(prev: &[Enemy], curr: &World) -> Vec<u8> {
+    let mut w = Writer::new(false);
+    w.put(DELTA_MAGIC);
+    w.u16(DELTA_FORMAT_VERSION);
+    w.u32(prev.len() as u32);
+    w.u32(curr.enemies.len() as u32);
+    w.f32(curr.player.x);
+    w.f32(curr.player.y);
+
+    let mut body = Writer::new(false);
+    let mut changed = 0u32;
+    for (i, e) in curr.enemies.iter().enumerate() {
+        let mask = match prev.get(i) {
+            Some(p) if p.id == e.id => {
+                let moved = p.x.to_bits() != e.x.to_bits() || p.y.to_bits() != e.y.to_bits();
+                (moved as u8 * DELTA_POSITION)
+                    | ((p.is_alive() != e.is_alive()) as u8 * DELTA_ALIVE)
+                    | ((p.health.to_bits() != e.health.to_bits()) as u8 * DELTA_HEALTH)
+            }
+            _ => DELTA_FULL,
+        };
+        if mask == 0 {
+            continue;
+        }
+        changed += 1;
+        body.u32(i as u32);
+        body.u8(mask);
+        if mask & DELTA_FULL != 0 {
+            write_enemy(&mut body, e);
+            continue;
+        }
+        if mask & DELTA_POSITION != 0 {
+            body.f32(e.x);
+            body.f32(e.y);
+        }
+        if mask & DELTA_ALIVE != 0 {
+            body.u8(e.is_alive() as u8);
+        }
+        if mask & DELTA_HEALTH != 0 {
+            body.f32(e.health);
+        }
+    }
+    w.u32(changed);
+    w.put(&body.buf);
+    w.buf
+}
+
+/// Parsed delta: base and new enemy counts, player position and slot changes.
+type Delta = (usize, usize, (f32, f32), Vec<(usize, SlotDelta)>);
+
+fn read_delta(bytes: &[u8]) -> Option<Delta> {
+    let mut r = Reader::new(bytes, false);
+    if r.take::<4>().as_ref() != Some(DELTA_MAGIC) || r.u16()? != DELTA_FORMAT_VERSION {
+        return None;
+    }
+    let (base, count, player) = (r.u32()? as usize, r.u32()? as usize, (r.f32()?, r.f32()?));
+    let changed = r.u32()?;
+    let slots = (0..changed)
+        .map(|_| {
+            let (index, mask) = (r.u32()? as usize, r.u8()?);
+            if index >= count {
+                return None;
+            }
+            if mask & DELTA_FULL != 0 {
+                return Some((index, SlotDelta::Full(read_enemy(&mut r, ENEMY_FORMAT_VERSION)?)));
+            }
+            // slots past the base only exist as full records
+            if index >= base {
+                return None;
+            }
+            let (x, y) = match mask & DELTA_POSITION != 0 {
+                true => (r.f32()?, r.f32()?),
+                false => (0.0, 0.0),
+            };
+            let alive = mask & DELTA_ALIVE != 0 && r.bool()?;
+            let health = if mask & DELTA_HEALTH != 0 { r.f32()? } else { 0.0 };
+            Some((index, SlotDelta::Fields { mask, x, y, alive, health }))
+        })
+        .collect::<Option<Vec<_>>>()?;
+    r.at_end().then_some((base, count, player, slots))
+}
+
+/// Encode what changed between the `serialize_world` snapshot at `prev_ptr` (the state the
+/// receiver already has) and `world` now: enemy positions, alive flags and health, spawned
+/// slots and the player position (layout above). Unchanged enemies cost nothing, so a typical
+/// frame is a few bytes per moving enemy. Writes the bytes written to `out_written`.
+/// `InvalidData` for a malformed snapshot, `BufferTooSmall` if `out_cap` is too small (at most
+/// 18 bytes per enemy beyond the full records for spawns, plus 26).
+// Safe C entry point: `prev_ptr`, `world` are null-checked before use; the rest of the contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn encode_delta(
+    prev_ptr: *const u8,
+    prev_len: usize,
+    world: *const World,
+    out_ptr: *mut u8,
+    out_cap: usize,
+    out_written: *mut usize,
+) -> DeadAimStatus {
+    ffi_guard(|| {
+        let world = unsafe { arg_ref(world) }?;
+        let prev = unsafe { slice_arg(prev_ptr, prev_len) }?;
+        let prev = World::read_snapshot(prev).ok_or(DeadAimStatus::InvalidData)?;
+        copy_out(&write_delta(&prev.enemies, world), out_ptr, out_cap, out_written)
+    })
+}
+
+/// Apply an `encode_delta` diff to `world`, which must hold the base state it was encoded
+/// against (same enemy count). Only the encoded fields change; AI, routes, statuses and timers
+/// stay as they were, so this is for presenting the sender's world rather than simulating it.
+/// `InvalidData`, leaving the world untouched, for malformed bytes, a base count mismatch or a
+/// result over the world's capacity.
+// Safe C entry point: `world`, `in_ptr` are null-checked before use; the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn apply_delta(world: *mut World, in_ptr: *const u8, in_len: usize) -> DeadAimStatus {
+    ffi_guard(|| {
+        let world = unsafe { arg_mut(world) }?;
+        let bytes = unsafe { slice_arg(in_ptr, in_len) }?;
+        let (base, count, (px, py), slots) = read_delta(bytes).ok_or(DeadAimStatus::InvalidData)?;
+        if base != world.enemies.len() || count > world.capacity {
+            return Err(DeadAimStatus::InvalidData);
+        }
+        // every new slot must arrive as a full record
+        let mut filled = vec![false; count.saturating_sub(base)];
+        for &(index, _) in &slots {
+            if let Some(f) = index.checked_sub(base).and_then(|i| filled.get_mut(i)) {
+                *f = true;
+            }
+        }
+        if filled.contains(&false) {
+            return Err(DeadAimStatus::InvalidData);
+        }
+
+        world.remember_positions();
+        world.enemies.resize(count, Enemy::new(-1, 0.0, 0.0));
+        for (index, slot) in slots {
+            let e = &mut world.enemies[index];
+            match slot {
+                SlotDelta::Full(full) => *e = full,
+                SlotDelta::Fields { mask, x, y, alive, health } => {
+                    if mask & DELTA_POSITION != 0 {
+                        e.x = x;
+                        e.y = y;
+                    }
+                    if mask & DELTA_ALIVE != 0 {
+                        e.set_alive(alive);
+                    }
+                    if mask & DELTA_HEALTH != 0 {
+                        e.health = health;
+                    }
+                }
+            }
+        }
+        world.next_id = world.enemies.iter().map(|e| e.id + 1).fold(world.next_id, i32::max);
+        world.player.x = px;
+        world.player.y = py;
+        world.resync_grid();
+        Ok(())
+    })
+}
+
+// ---------- Lag compensation ----------
+
+// How far back (ms) `world_fire_hitscan_at_time` can rewind; older frames are dropped.
+const LAG_HISTORY_MS: u64 = 1000;
+
+/// Every enemy's id, position and alive flag at `time_ms`, in slot order. Rewinds match enemies
+/// by id, since dead slots are reused by later spawns.
+#[derive(Clone, Debug)]
+struct HistoryFrame {
+    time_ms: u64,
+    enemies: Vec<(i32, f32, f32, bool)>,
+}
+
+impl HistoryFrame {
+    /// Position and alive flag of enemy `id`, looked up at its current slot `index` first.
+    fn find(&self, index: usize, id: i32) -> Option<(f32, f32, bool)> {
+        let hit = |&&(eid, ..): &&(i32, f32, f32, bool)| eid == id;
+        let entry = self.enemies.get(index).filter(hit).or_else(|| self.enemies.iter().find(hit))?;
+        Some((entry.1, entry.2, entry.3))
+    }
+}
+
+impl World {
+    /// The enemies as they stood at `time_ms`, interpolated between the recorded frames around
+    /// it (clamped to the oldest frame). Enemies absent from the earlier frame hadn't spawned yet
+    /// and count as dead. `None` when nothing has been recorded or `time_ms` is at or after the
+    /// newest frame.
+    fn rewound_enemies(&self, time_ms: u64) -> Option<Vec<Enemy>> {
+        let newest = self.history.back()?;
+        if time_ms >= newest.time_ms {
+            return None;
+        }
+        let after = self.history.iter().position(|f| f.time_ms > time_ms).unwrap_or(0);
+        let next = &self.history[after];
+        let prev = after.checked_sub(1).map_or(next, |i| &self.history[i]);
+        let span = next.time_ms.saturating_sub(prev.time_ms);
+        let t = if span == 0 { 0.0 } else { time_ms.saturating_sub(prev.time_ms) as f32 / span as f32 };
+
+        let mut enemies = self.enemies.clone();
+        for (i, e) in enemies.iter_mut().enumerate() {
+            let Some((x0, y0, alive)) = prev.find(i, e.id) else {
+                e.set_alive(false);
+                continue;
+            };
+            let (x1, y1, _) = next.find(i, e.id).unwrap_or((x0, y0, alive));
+            e.x = x0 + (x1 - x0) * t;
+            e.y = y0 + (y1 - y0) * t;
+            e.set_alive(alive);
+        }
+        Some(enemies)
+    }
+}
+
+/// Record where every enemy stands at server time `now_ms`; call after each `world_tick` so
+/// `world_fire_hitscan_at_time` can rewind up to a second. Times earlier than the last recorded
+/// frame are ignored.
+// Safe C entry point: `world` is null-checked before use; the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn world_record_history(world: *mut World, now_ms: u64) -> DeadAimStatus {
+    ffi_guard(|| {
+        let world = unsafe { arg_mut(world) }?;
+        if world.history.back().is_some_and(|f| now_ms < f.time_ms) {
+            return Ok(());
+        }
+        let enemies = world.enemies.iter().map(|e| (e.id, e.x, e.y, e.is_alive())).collect();
+        world.history.push_back(HistoryFrame { time_ms: now_ms, enemies });
+        while world.history.front().is_some_and(|f| f.time_ms + LAG_HISTORY_MS < now_ms) {
+            world.history.pop_front();
+        }
+        Ok(())
+    })
+}
+
+/// Server-side hit check for a shot the client fired at `client_time_ms` (server time of the
+/// world state it was looking at): rewind the enemies to that moment using the frames from
+/// `world_record_history`, then cast a ray of `max_range` from the origin along (`dir_x`, `dir_y`)
+/// and write the index of the first enemy hit (-1 on a miss) to `out_index`, so high-ping players
+/// hit what they saw. Times older than the history reach are clamped to its oldest frame; current
+/// or future times use the live positions. No spread is applied and nothing is damaged.
+/// `InvalidArgument` for a zero direction or a non-positive range. Counts toward the running
+/// match's accuracy.
+// Safe C entry point: `world`, `out_index` are null-checked before use; the rest of the contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn world_fire_hitscan_at_time(
+    world: *const World,
+    origin_x: f32,
+    origin_y: f32,
+    dir_x: f32,
+    dir_y: f32,
+    max_range: f32,
+    client_time_ms: u64,
+    out_index: *mut i32,
+) -> DeadAimStatus {
+    ffi_guard(|| {
+        let world = unsafe { arg_ref(world) }?;
+        if out_index.is_null() {
+            return Err(DeadAimStatus::NullPointer);
+        }
+        let len = (dir_x * dir_x + dir_y * dir_y).sqrt();
+        if len <= f32::EPSILON || max_range.is_nan() || max_range <= 0.0 {
+            return Err(DeadAimStatus::InvalidArgument);
+        }
+        let (end_x, end_y) = (origin_x + dir_x / len * max_range, origin_y + dir_y / len * max_range);
+        let rewound = world.rewound_enemies(client_time_ms);
+        let enemies = rewound.as_deref().unwrap_or(&world.enemies);
+        let hit = first_enemy_on_segment(enemies, origin_x, origin_y, end_x, end_y);
+        record_shot(hit.is_some());
+        unsafe { write_out(out_index, hit.map_or(-1, |(i, _)| i as i32)) }
+    })
+}
+
+// ---------- Render interpolation ----------
+
+// Largest blend factor `world_get_interpolated_positions` accepts: one step past the current
+// state, so a missing update extrapolates briefly instead of freezing, but not indefinitely.
+const MAX_INTERPOLATION_ALPHA: f32 = 2.0;
+
+impl World {
+    fn remember_positions(&mut self) {
+        self.prev_positions.clear();
+        self.prev_positions.extend(self.enemies.iter().map(|e| (e.x, e.y, e.is_alive())));
+    }
+}
+
+/// Writes how far the unsimulated time carried by `world_tick` is into the next fixed step (0..1);
+/// pass it as `alpha` to `world_get_interpolated_positions` to render between ticks.
+// Safe C entry point: `world`, `out_alpha` are null-checked before use; the rest of the contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn world_interpolation_alpha(world: *const World, out_alpha: *mut f32) -> DeadAimStatus {
+    ffi_guard(|| {
+        let world = unsafe { arg_ref(world) }?;
+        unsafe { write_out(out_alpha, (world.accumulator / world.step).clamp(0.0, 1.0)) }
+    })
+}
+
+/// Write render positions for every enemy slot as (x, y) pairs into `out_xy` (room for
+/// `out_cap` pairs): `alpha` 0 is where they were before the last simulated step (or applied
+/// delta) and 1 is where they are now; values above 1 extrapolate along that movement, up to 2
+/// (e.g. while a network update is late). Enemies that spawned, died or respawned since use
+/// their current position. Writes the number of slots, which may exceed `out_cap`, to
+/// `out_total`.
+// Safe C entry point: `world`, `out_xy`, `out_total` are null-checked before use;
+// the rest of the pointer contract is the caller's.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+#[no_mangle]
+pub extern "C" fn world_get_interpolated_positions(
+    world: *const World,
+    alpha: f32,
+    out_xy: *mut f32,
+    out_cap: i32,
+    out_total: *mut i32,
+) -> DeadAimStatus {
+    ffi_guard(|| {
+        let world = unsafe { arg_ref(world) }?;
+        let cap = usize::try_from(out_cap).map_err(|_| DeadAimStatus::InvalidArgument)?;
+        let out = unsafe { slice_arg_mut(out_xy, cap * 2) }?;
+        if out_total.is_null() {
+            return Err(DeadAimStatus::NullPointer);
+        }
+        let alpha = if alpha.is_nan() { 1.0 } else { alpha.clamp(0.0, MAX_INTERPOLATION_ALPHA) };
+        for (i, (e, o)) in world.enemies.iter().zip(out.chunks_exact_mut(2)).enumerate() {
+            let (x, y) = match world.prev_positions.get(i) {
+                Some(&(px, py, alive)) if alive == e.is_alive() => (px + (e.x - px) * alpha, py + (e.y - py) * alpha),
+                _ => (e.x, e.y),
+            };
+            o[0] = x;
+            o[1] = y;
+        }
+        unsafe { write_out(out_total, world.enemies.len() as i32) }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::combat::set_safe_zones;
+    use crate::obstacles::Rect;
+    use crate::spawner::set_on_wave_cleared;
+    use std::sync::Mutex;
+
+    fn player_health(world: *const World) -> f32 {
+        let mut health = f32::NAN;
+        assert_eq!(world_get_player_health(world, &mut health), DeadAimStatus::Ok);
+        health
+    }
+
+    #[test]
+    fn safe_zone_blocks_touch_damage_only_inside() {
+        let mut world = std::ptr::null_mut();
+        assert_eq!(world_create(4, &mut world), DeadAimStatus::Ok);
+        world_configure_player(world, 0.0, 1.0, 100.0, 10.0);
+        world_set_player_position(world, 5.0, 5.0);
+        world_add_enemy(world, 5.0, 5.0, std::ptr::null_mut());
+        let zone = Rect { min_x: 0.0, min_y: 0.0, max_x: 10.0, max_y: 10.0 };
+        set_safe_zones(&zone, 1);
+
+        world_tick(world, 0.5, std::ptr::null_mut());
+        assert_eq!(player_health(world), 100.0);
+
+        world_set_player_position(world, 20.0, 20.0);
+        world_add_enemy(world, 20.0, 20.0, std::ptr::null_mut());
+        world_tick(world, 0.5, std::ptr::null_mut());
+        assert!(player_health(world) < 100.0);
+
+        set_safe_zones(std::ptr::null(), 0);
+        world_destroy(world);
+    }
+
+    #[test]
+    fn status_codes_for_bad_arguments() {
+        let mut world = std::ptr::null_mut();
+        assert_eq!(world_create(-1, &mut world), DeadAimStatus::InvalidArgument);
+        assert_eq!(world_create(1, std::ptr::null_mut()), DeadAimStatus::NullPointer);
+        assert_eq!(world_create(1, &mut world), DeadAimStatus::Ok);
+        let mut index = 0;
+        assert_eq!(world_add_enemy(world, 0.0, 0.0, &mut index), DeadAimStatus::Ok);
+        assert_eq!(index, 0);
+        assert_eq!(world_add_enemy(world, 1.0, 0.0, &mut index), DeadAimStatus::InvalidArgument);
+        assert_eq!(world_shoot_enemy(world, 3), DeadAimStatus::InvalidIndex);
+        assert_eq!(world_find_nearest_enemy(std::ptr::null(), 0.0, 0.0, &mut index), DeadAimStatus::NullPointer);
+        world_destroy(world);
+    }
+
+    #[test]
+    fn rewound_shot_ignores_an_enemy_spawned_into_a_reused_slot() {
+        let mut world = std::ptr::null_mut();
+        assert_eq!(world_create(1, &mut world), DeadAimStatus::Ok);
+        world_add_enemy(world, 5.0, 0.0, std::ptr::null_mut());
+        world_record_history(world, 100);
+        assert_eq!(world_shoot_enemy(world, 0), DeadAimStatus::Ok);
+        world_record_history(world, 200);
+
+        let spawn = [5.0f32, 0.0];
+        assert_eq!(world_configure_wave(world, 0, 1, 0, 0.0, spawn.as_ptr(), 1), DeadAimStatus::Ok);
+        assert_eq!(world_start_wave(world, 0), DeadAimStatus::Ok);
+        assert_eq!(world_tick_spawner(world, 0.1, std::ptr::null_mut()), DeadAimStatus::Ok);
+        let mut refill = Enemy::new(-1, 0.0, 0.0);
+        assert_eq!(world_get_enemy(world, 0, &mut refill), DeadAimStatus::Ok);
+        assert!(refill.is_alive() && refill.id != 0);
+        world_record_history(world, 300);
+
+        let fire = |time_ms| {
+            let mut index = -2;
+            let status = world_fire_hitscan_at_time(world, 0.0, 0.0, 1.0, 0.0, 50.0, time_ms, &mut index);
+            assert_eq!(status, DeadAimStatus::Ok);
+            index
+        };
+        // before the refill the slot held the first enemy, dead (t = 200) or not yet there
+        assert_eq!(fire(150), -1);
+        assert_eq!(fire(250), -1);
+        assert_eq!(fire(300), 0);
+        world_destroy(world);
+    }
+
+    static CLEARED_WAVES: Mutex<Vec<i32>> = Mutex::new(Vec::new());
+
+    extern "C" fn record_cleared_wave(wave_id: i32) {
+        CLEARED_WAVES.lock().unwrap_or_else(|e| e.into_inner()).push(wave_id);
+    }
+
+    #[test]
+    fn shooting_the_last_wave_enemy_clears_the_wave_without_a_tick() {
+        let mut world = std::ptr::null_mut();
+        assert_eq!(world_create(4, &mut world), DeadAimStatus::Ok);
+        assert_eq!(world_configure_wave(world, 7, 2, 0, 0.0, std::ptr::null(), 0), DeadAimStatus::Ok);
+        assert_eq!(world_start_wave(world, 7), DeadAimStatus::Ok);
+        assert_eq!(world_tick_spawner(world, 0.1, std::ptr::null_mut()), DeadAimStatus::Ok);
+        set_on_wave_cleared(Some(record_cleared_wave));
+
+        let mut wave = -2;
+        assert_eq!(world_shoot_enemy(world, 0), DeadAimStatus::Ok);
+        world_current_wave(world, &mut wave);
+        assert_eq!(wave, 7);
+        assert_eq!(world_kill_enemy_with_respawn(world, 1, 5.0), DeadAimStatus::Ok);
+        world_current_wave(world, &mut wave);
+        assert_eq!(wave, -1);
+        assert!(CLEARED_WAVES.lock().unwrap_or_else(|e| e.into_inner()).contains(&7));
+        set_on_wave_cleared(None);
+        world_destroy(world);
+    }
+
+    #[test]
+    fn huge_capacity_does_not_preallocate() {
+        let mut world = std::ptr::null_mut();
+        assert_eq!(world_create(i32::MAX, &mut world), DeadAimStatus::Ok);
+        let mut index = -1;
+        assert_eq!(world_add_enemy(world, 1.0, 2.0, &mut index), DeadAimStatus::Ok);
+        assert_eq!(index, 0);
+        world_destroy(world);
+
+        let mut id = 0;
+        assert_eq!(crate::registry::create_world(i32::MAX, &mut id), DeadAimStatus::Ok);
+        assert_eq!(crate::registry::destroy_world(id), DeadAimStatus::Ok);
+    }
+}